@@ -0,0 +1,201 @@
+//! Admittance Controller
+//!
+//! Stiff position-controlled robots, e.g. industrial arms used as slaves,
+//! cannot render forces directly. An admittance controller measures the
+//! interaction force instead and integrates the motion of a virtual
+//! mass-damper `M * a + B * v = F`, which is then commanded to the position
+//! or velocity controller of the robot. The commanded velocity and position
+//! can be limited to keep the robot in its safe range.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{OutputLimits, PositionMessage};
+
+/// Virtual mass-damper mapping forces to motion.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{Admittance, OutputLimits};
+///
+/// let mut admittance = Admittance::<f64, U1>::new(Vector1::new(2.0), Vector1::new(10.0));
+///
+/// // A constant force accelerates the virtual mass until the damping balances it.
+/// for _ in 0..10000 {
+///     admittance.step(&Vector1::new(10.0), 0.001);
+/// }
+/// assert!((admittance.vel()[0] - 1.0f64).abs() < 1e-6);
+///
+/// // The commanded velocity is clamped to its limits.
+/// admittance.set_velocity_limits(Some(OutputLimits::symmetric(Vector1::new(0.5))));
+/// let command = admittance.step(&Vector1::new(10.0), 0.001);
+/// assert_eq!(command.vel, Vector1::new(0.5));
+/// assert!(admittance.is_saturated());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Admittance<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    mass: VectorN<N, D>,
+    damping: VectorN<N, D>,
+    pos: VectorN<N, D>,
+    vel: VectorN<N, D>,
+    velocity_limits: Option<OutputLimits<N, D>>,
+    position_limits: Option<OutputLimits<N, D>>,
+    saturated: bool,
+}
+
+impl<N, D> Admittance<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `Admittance` controller with a `mass` and `damping` per
+    /// axis at rest in the origin.
+    pub fn new(mass: VectorN<N, D>, damping: VectorN<N, D>) -> Self {
+        assert!(
+            mass.iter().all(|mass| *mass > N::zero()),
+            "the mass has to be positive"
+        );
+        assert!(
+            damping.iter().all(|damping| *damping >= N::zero()),
+            "the damping must not be negative"
+        );
+        Self {
+            mass,
+            damping,
+            pos: VectorN::zeros(),
+            vel: VectorN::zeros(),
+            velocity_limits: None,
+            position_limits: None,
+            saturated: false,
+        }
+    }
+
+    /// Resets the virtual mass to rest in the origin.
+    pub fn reset(&mut self) {
+        self.pos = VectorN::zeros();
+        self.vel = VectorN::zeros();
+        self.saturated = false;
+    }
+}
+
+impl<N, D> Admittance<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Steps the virtual mass with the measured interaction `force` over `dt`
+    /// and returns the commanded position and velocity.
+    pub fn step(&mut self, force: &VectorN<N, D>, dt: N) -> PositionMessage<N, D> {
+        let acc = (force - self.damping.component_mul(&self.vel)).component_div(&self.mass);
+        let vel = &self.vel + acc * dt;
+        let (vel, vel_saturated) = match &self.velocity_limits {
+            Some(velocity_limits) => velocity_limits.apply(&vel),
+            None => (vel, false),
+        };
+        self.vel = vel;
+
+        let pos = &self.pos + &self.vel * dt;
+        let (pos, pos_saturated) = match &self.position_limits {
+            Some(position_limits) => position_limits.apply(&pos),
+            None => (pos, false),
+        };
+        if pos_saturated {
+            // Axes held at a position limit stop instead of pushing on.
+            let unclamped = &self.pos + &self.vel * dt;
+            for ((vel, pos), unclamped) in self.vel.iter_mut().zip(pos.iter()).zip(unclamped.iter())
+            {
+                if pos != unclamped {
+                    *vel = N::zero();
+                }
+            }
+        }
+        self.pos = pos;
+        self.saturated = vel_saturated || pos_saturated;
+
+        PositionMessage {
+            pos: self.pos.clone(),
+            vel: self.vel.clone(),
+        }
+    }
+
+    /// Sets the position and the velocity of the virtual mass, e.g. to the
+    /// measured state of the robot.
+    pub fn set_state(&mut self, pos: VectorN<N, D>, vel: VectorN<N, D>) {
+        self.pos = pos;
+        self.vel = vel;
+    }
+
+    /// Returns the commanded position.
+    pub fn pos(&self) -> &VectorN<N, D> {
+        &self.pos
+    }
+
+    /// Returns the commanded velocity.
+    pub fn vel(&self) -> &VectorN<N, D> {
+        &self.vel
+    }
+
+    /// Returns the mass.
+    pub fn mass(&self) -> &VectorN<N, D> {
+        &self.mass
+    }
+
+    /// Sets the mass per axis.
+    pub fn set_mass(&mut self, mass: VectorN<N, D>) {
+        assert!(
+            mass.iter().all(|mass| *mass > N::zero()),
+            "the mass has to be positive"
+        );
+        self.mass = mass;
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> &VectorN<N, D> {
+        &self.damping
+    }
+
+    /// Sets the damping per axis.
+    pub fn set_damping(&mut self, damping: VectorN<N, D>) {
+        assert!(
+            damping.iter().all(|damping| *damping >= N::zero()),
+            "the damping must not be negative"
+        );
+        self.damping = damping;
+    }
+
+    /// Returns the velocity limits.
+    pub fn velocity_limits(&self) -> Option<&OutputLimits<N, D>> {
+        self.velocity_limits.as_ref()
+    }
+
+    /// Sets the limits the commanded velocity is clamped to, defaults to
+    /// `None`.
+    pub fn set_velocity_limits(&mut self, velocity_limits: Option<OutputLimits<N, D>>) {
+        self.velocity_limits = velocity_limits;
+    }
+
+    /// Returns the position limits.
+    pub fn position_limits(&self) -> Option<&OutputLimits<N, D>> {
+        self.position_limits.as_ref()
+    }
+
+    /// Sets the limits the commanded position is clamped to, defaults to
+    /// `None`.
+    pub fn set_position_limits(&mut self, position_limits: Option<OutputLimits<N, D>>) {
+        self.position_limits = position_limits;
+    }
+
+    /// Returns `true` if the last command had to be clamped.
+    pub fn is_saturated(&self) -> bool {
+        self.saturated
+    }
+}
@@ -0,0 +1,268 @@
+//! Relay Autotuning
+//!
+//! The Åström–Hägglund relay experiment replaces the controller by a relay
+//! that switches the force between `+d` and `-d` whenever the position crosses
+//! the setpoint. Most plants then settle into a limit cycle whose amplitude `a`
+//! and period `T_u` yield the ultimate gain `K_u = 4 d / (π a)`, from which the
+//! PID gains are derived with a tuning rule.
+use nalgebra::{convert, RealField};
+
+/// Rule that maps the ultimate gain and period onto PID gains.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TuningRule {
+    /// Ziegler–Nichols: `k_p = 0.6 K_u`, `T_i = T_u / 2`, `T_d = T_u / 8`.
+    #[default]
+    ZieglerNichols,
+    /// Tyreus–Luyben: `k_p = K_u / 2.2`, `T_i = 2.2 T_u`, `T_d = T_u / 6.3`,
+    /// which is less aggressive than Ziegler–Nichols.
+    TyreusLuyben,
+}
+
+/// 1-DOF plant the relay experiment is run on, e.g. one axis of a haptic
+/// device.
+pub trait Plant<N>
+where
+    N: RealField,
+{
+    /// Returns the measured position.
+    fn position(&self) -> N;
+
+    /// Applies the force for the duration `dt`.
+    fn apply_force(&mut self, force: N, dt: N);
+}
+
+/// Runs the relay experiment and recommends PID gains.
+///
+/// ```rust
+/// use haptic_toolbox::{Plant, RelayAutotuner, TuningRule, PID};
+/// use nalgebra::U1;
+///
+/// // Mass-damper with a first order actuator lag.
+/// struct Device {
+///     force: f64,
+///     pos: f64,
+///     vel: f64,
+/// }
+///
+/// impl Plant<f64> for Device {
+///     fn position(&self) -> f64 {
+///         self.pos
+///     }
+///
+///     fn apply_force(&mut self, force: f64, dt: f64) {
+///         self.force += (force - self.force) * dt / 0.02;
+///         self.vel += (self.force - 2.0 * self.vel) / 0.5 * dt;
+///         self.pos += self.vel * dt;
+///     }
+/// }
+///
+/// let mut device = Device { force: 0.0, pos: 0.0, vel: 0.0 };
+/// let mut autotuner = RelayAutotuner::new(0.01, 1.0, 0.0001);
+/// let (k_p, k_i, k_d) = autotuner.run(&mut device, 0.0001, 1_000_000).unwrap();
+/// assert!(k_p > 0.0 && k_i > 0.0 && k_d > 0.0);
+///
+/// autotuner.set_rule(TuningRule::TyreusLuyben);
+/// let (k_p_tl, _, _) = autotuner.gains().unwrap();
+/// assert!(k_p_tl < k_p);
+///
+/// let _pid = PID::<f64, U1>::new(k_p, k_i, k_d);
+/// ```
+#[derive(Debug)]
+pub struct RelayAutotuner<N>
+where
+    N: RealField,
+{
+    setpoint: N,
+    relay_amplitude: N,
+    hysteresis: N,
+    rule: TuningRule,
+    cycles: usize,
+
+    time: N,
+    high: Option<bool>,
+    last_rising: Option<N>,
+    cycle_min: N,
+    cycle_max: N,
+    periods: Vec<N>,
+    amplitudes: Vec<N>,
+}
+
+impl<N> RelayAutotuner<N>
+where
+    N: RealField,
+{
+    /// Creates a new relay autotuner that oscillates around `setpoint` with
+    /// the force amplitude `relay_amplitude`.
+    ///
+    /// The relay only switches once the error exceeds `hysteresis`, which
+    /// keeps measurement noise from triggering spurious switches.
+    pub fn new(setpoint: N, relay_amplitude: N, hysteresis: N) -> Self {
+        assert!(
+            relay_amplitude > N::zero(),
+            "the relay amplitude has to be positive"
+        );
+        assert!(
+            hysteresis >= N::zero(),
+            "the hysteresis must not be negative"
+        );
+        Self {
+            setpoint,
+            relay_amplitude,
+            hysteresis,
+            rule: TuningRule::default(),
+            cycles: 4,
+            time: N::zero(),
+            high: None,
+            last_rising: None,
+            cycle_min: N::zero(),
+            cycle_max: N::zero(),
+            periods: Vec::new(),
+            amplitudes: Vec::new(),
+        }
+    }
+
+    /// Returns the relay force for the measured position.
+    ///
+    /// Returns zero once enough cycles have been measured.
+    pub fn update(&mut self, pos: N, dt: N) -> N {
+        if self.is_finished() {
+            return N::zero();
+        }
+
+        self.time += dt;
+        self.cycle_min = self.cycle_min.min(pos);
+        self.cycle_max = self.cycle_max.max(pos);
+
+        let error = self.setpoint - pos;
+        match self.high {
+            None => {
+                self.high = Some(error >= N::zero());
+                self.cycle_min = pos;
+                self.cycle_max = pos;
+            }
+            Some(false) if error > self.hysteresis => {
+                self.high = Some(true);
+                if let Some(last_rising) = self.last_rising {
+                    self.periods.push(self.time - last_rising);
+                    self.amplitudes
+                        .push((self.cycle_max - self.cycle_min) / convert(2.0));
+                }
+                self.last_rising = Some(self.time);
+                self.cycle_min = pos;
+                self.cycle_max = pos;
+            }
+            Some(true) if error < -self.hysteresis => self.high = Some(false),
+            _ => {}
+        }
+
+        if self.is_finished() {
+            N::zero()
+        } else if self.high == Some(true) {
+            self.relay_amplitude
+        } else {
+            -self.relay_amplitude
+        }
+    }
+
+    /// Runs the relay experiment on `plant` for at most `max_steps` steps and
+    /// returns the recommended gains.
+    pub fn run<P>(&mut self, plant: &mut P, dt: N, max_steps: usize) -> Option<(N, N, N)>
+    where
+        P: Plant<N>,
+    {
+        for _ in 0..max_steps {
+            let force = self.update(plant.position(), dt);
+            if self.is_finished() {
+                break;
+            }
+            plant.apply_force(force, dt);
+        }
+        self.gains()
+    }
+
+    /// Returns `true` if enough cycles have been measured.
+    ///
+    /// The first cycle is discarded because it still contains the transient.
+    pub fn is_finished(&self) -> bool {
+        self.periods.len() > self.cycles
+    }
+
+    /// Restarts the relay experiment.
+    pub fn reset(&mut self) {
+        self.time = N::zero();
+        self.high = None;
+        self.last_rising = None;
+        self.periods.clear();
+        self.amplitudes.clear();
+    }
+
+    /// Returns the measured ultimate period.
+    pub fn ultimate_period(&self) -> Option<N> {
+        if !self.is_finished() {
+            return None;
+        }
+        Some(Self::mean(&self.periods[1..]))
+    }
+
+    /// Returns the measured ultimate gain.
+    pub fn ultimate_gain(&self) -> Option<N> {
+        if !self.is_finished() {
+            return None;
+        }
+        let amplitude = Self::mean(&self.amplitudes[1..]);
+        if amplitude <= self.hysteresis {
+            return None;
+        }
+        let four: N = convert(4.0);
+        Some(
+            four * self.relay_amplitude
+                / (N::pi() * (amplitude * amplitude - self.hysteresis * self.hysteresis).sqrt()),
+        )
+    }
+
+    /// Returns the recommended `(k_p, k_i, k_d)` according to the tuning
+    /// rule.
+    pub fn gains(&self) -> Option<(N, N, N)> {
+        let ultimate_gain = self.ultimate_gain()?;
+        let ultimate_period = self.ultimate_period()?;
+        let (k_p, t_i, t_d) = match self.rule {
+            TuningRule::ZieglerNichols => (
+                ultimate_gain * convert(0.6),
+                ultimate_period / convert(2.0),
+                ultimate_period / convert(8.0),
+            ),
+            TuningRule::TyreusLuyben => (
+                ultimate_gain / convert(2.2),
+                ultimate_period * convert(2.2),
+                ultimate_period / convert(6.3),
+            ),
+        };
+        Some((k_p, k_p / t_i, k_p * t_d))
+    }
+
+    /// Returns the tuning rule.
+    pub fn rule(&self) -> TuningRule {
+        self.rule
+    }
+
+    /// Sets the tuning rule, defaults to `TuningRule::ZieglerNichols`.
+    pub fn set_rule(&mut self, rule: TuningRule) {
+        self.rule = rule;
+    }
+
+    /// Returns the number of measured cycles.
+    pub fn cycles(&self) -> usize {
+        self.cycles
+    }
+
+    /// Sets the number of cycles that are averaged, defaults to 4.
+    pub fn set_cycles(&mut self, cycles: usize) {
+        assert!(cycles > 0, "at least one cycle has to be measured");
+        self.cycles = cycles;
+    }
+
+    fn mean(values: &[N]) -> N {
+        let sum = values.iter().fold(N::zero(), |sum, value| sum + *value);
+        sum / convert(values.len() as f64)
+    }
+}
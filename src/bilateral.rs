@@ -0,0 +1,263 @@
+//! Bilateral Architectures
+//!
+//! Ready-made two-channel architectures for bilateral teleoperation. In both
+//! the slave tracks the position of the master with a `PID` controller. With
+//! `PositionPosition` the master is pulled towards the slave position by a
+//! `PD` controller, with `PositionForce` the master displays the measured
+//! environment force. The force displayed at the master passes through a
+//! `Stabilizer`, which keeps the delayed channel stable.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{Stabilizer, PD, PID};
+
+/// Position and velocity of a device that is sent to the other side.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionMessage<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Position of the device.
+    pub pos: VectorN<N, D>,
+    /// Velocity of the device.
+    pub vel: VectorN<N, D>,
+}
+
+/// Force of the environment on the slave that is sent to the master.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ForceMessage<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Force of the environment on the slave.
+    pub force: VectorN<N, D>,
+}
+
+/// Position-position architecture.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{PositionMessage, PositionPosition, PD, PID, TDPA};
+///
+/// let mut architecture = PositionPosition::new(
+///     PD::<f64, U1>::new(100.0, 1.0),
+///     PID::new(200.0, 0.0, 2.0),
+///     TDPA::default(),
+/// );
+/// let master = PositionMessage { pos: Vector1::new(0.1), vel: Vector1::new(0.0) };
+/// let slave = PositionMessage { pos: Vector1::new(0.0), vel: Vector1::new(0.0) };
+///
+/// let force_s = architecture.calculate_slave_force(&slave, &master, 0.001);
+/// assert!((force_s[0] - 20.0f64).abs() < 1e-12);
+/// let force_m = architecture.calculate_master_force(&master, &slave, 0.001);
+/// assert!((force_m[0] + 10.0f64).abs() < 1e-12);
+///
+/// // Resisting the motion of the master absorbs energy, so it is not damped.
+/// let moving = PositionMessage { pos: Vector1::new(0.1), vel: Vector1::new(0.5) };
+/// let force_m = architecture.calculate_master_force(&moving, &slave, 0.001);
+/// assert!((force_m[0] + 10.5f64).abs() < 1e-12);
+/// ```
+pub struct PositionPosition<N, D, S>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    master: PD<N, D>,
+    slave: PID<N, D>,
+    stabilizer: S,
+}
+
+impl<N, D, S> PositionPosition<N, D, S>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    /// Creates a new `PositionPosition` with the controllers of master and
+    /// slave and the stabilizer of the force displayed at the master.
+    pub fn new(master: PD<N, D>, slave: PID<N, D>, stabilizer: S) -> Self {
+        Self {
+            master,
+            slave,
+            stabilizer,
+        }
+    }
+
+    /// Calculates the force displayed at the master from the `master` state
+    /// and the latest received `slave` state.
+    pub fn calculate_master_force(
+        &mut self,
+        master: &PositionMessage<N, D>,
+        slave: &PositionMessage<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let force = self
+            .master
+            .calculate_force(&slave.pos, &master.pos, &slave.vel, &master.vel);
+        self.stabilizer
+            .stabilize_command(&master.pos, &master.vel, &force, dt)
+    }
+
+    /// Calculates the force commanded to the slave from the `slave` state and
+    /// the latest received `master` state.
+    pub fn calculate_slave_force(
+        &mut self,
+        slave: &PositionMessage<N, D>,
+        master: &PositionMessage<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.slave
+            .calculate_force(&master.pos, &slave.pos, &master.vel, &slave.vel, dt)
+    }
+
+    /// Returns the controller of the master.
+    pub fn master(&self) -> &PD<N, D> {
+        &self.master
+    }
+
+    /// Returns the controller of the master mutably.
+    pub fn master_mut(&mut self) -> &mut PD<N, D> {
+        &mut self.master
+    }
+
+    /// Returns the controller of the slave.
+    pub fn slave(&self) -> &PID<N, D> {
+        &self.slave
+    }
+
+    /// Returns the controller of the slave mutably.
+    pub fn slave_mut(&mut self) -> &mut PID<N, D> {
+        &mut self.slave
+    }
+
+    /// Returns the stabilizer.
+    pub fn stabilizer(&self) -> &S {
+        &self.stabilizer
+    }
+
+    /// Returns the stabilizer mutably.
+    pub fn stabilizer_mut(&mut self) -> &mut S {
+        &mut self.stabilizer
+    }
+}
+
+/// Position-force architecture.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{ForceMessage, PositionForce, PositionMessage, PID, TDPA};
+///
+/// let mut architecture = PositionForce::new(PID::<f64, U1>::new(200.0, 0.0, 2.0), 0.5, TDPA::default());
+/// let master = PositionMessage { pos: Vector1::new(0.1), vel: Vector1::new(0.0) };
+/// let slave = PositionMessage { pos: Vector1::new(0.0), vel: Vector1::new(0.0) };
+///
+/// let force_s = architecture.calculate_slave_force(&slave, &master, 0.001);
+/// assert!((force_s[0] - 20.0f64).abs() < 1e-12);
+///
+/// // The environment pushes back and the master displays half of it.
+/// let environment = ForceMessage { force: Vector1::new(-4.0) };
+/// let force_m = architecture.calculate_master_force(&master, &environment, 0.001);
+/// assert_eq!(force_m, Vector1::new(-2.0));
+/// ```
+pub struct PositionForce<N, D, S>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    slave: PID<N, D>,
+    force_scale: N,
+    stabilizer: S,
+}
+
+impl<N, D, S> PositionForce<N, D, S>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    /// Creates a new `PositionForce` with the controller of the slave, the
+    /// scale of the force displayed at the master and its stabilizer.
+    pub fn new(slave: PID<N, D>, force_scale: N, stabilizer: S) -> Self {
+        assert!(
+            force_scale >= N::zero(),
+            "the force scale must not be negative"
+        );
+        Self {
+            slave,
+            force_scale,
+            stabilizer,
+        }
+    }
+
+    /// Calculates the force displayed at the master from the `master` state
+    /// and the latest received environment force.
+    pub fn calculate_master_force(
+        &mut self,
+        master: &PositionMessage<N, D>,
+        environment: &ForceMessage<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let force = &environment.force * self.force_scale;
+        self.stabilizer
+            .stabilize_command(&master.pos, &master.vel, &force, dt)
+    }
+
+    /// Calculates the force commanded to the slave from the `slave` state and
+    /// the latest received `master` state.
+    pub fn calculate_slave_force(
+        &mut self,
+        slave: &PositionMessage<N, D>,
+        master: &PositionMessage<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.slave
+            .calculate_force(&master.pos, &slave.pos, &master.vel, &slave.vel, dt)
+    }
+
+    /// Returns the controller of the slave.
+    pub fn slave(&self) -> &PID<N, D> {
+        &self.slave
+    }
+
+    /// Returns the controller of the slave mutably.
+    pub fn slave_mut(&mut self) -> &mut PID<N, D> {
+        &mut self.slave
+    }
+
+    /// Returns the scale of the force displayed at the master.
+    pub fn force_scale(&self) -> N {
+        self.force_scale
+    }
+
+    /// Sets the scale of the force displayed at the master.
+    pub fn set_force_scale(&mut self, force_scale: N) {
+        assert!(
+            force_scale >= N::zero(),
+            "the force scale must not be negative"
+        );
+        self.force_scale = force_scale;
+    }
+
+    /// Returns the stabilizer.
+    pub fn stabilizer(&self) -> &S {
+        &self.stabilizer
+    }
+
+    /// Returns the stabilizer mutably.
+    pub fn stabilizer_mut(&mut self) -> &mut S {
+        &mut self.stabilizer
+    }
+}
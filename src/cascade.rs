@@ -0,0 +1,196 @@
+//! Cascade Control
+//!
+//! An outer position loop calculates the velocity reference for an inner
+//! velocity loop, which in turn calculates the force. The inner loop usually
+//! runs faster than the outer loop, so the outer loop is only updated every
+//! `rate_division` calls and its output is held in between.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+use num_traits::Zero;
+
+use crate::{Gain, PD, PID};
+
+/// Control loop that can be used as part of a `Cascade`.
+pub trait CascadeLoop<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Calculates the output for tracking `reference` from the `measurement`
+    /// and its rate of change, and returns it together with `true` if it had
+    /// to be clamped.
+    fn output(
+        &mut self,
+        reference: &VectorN<N, D>,
+        measurement: &VectorN<N, D>,
+        rate: &VectorN<N, D>,
+        dt: N,
+    ) -> (VectorN<N, D>, bool);
+
+    /// Feeds back the output that was actually achieved.
+    fn track_saturation(&mut self, _achieved_output: &VectorN<N, D>, _dt: N) {}
+}
+
+/// The derivative component only uses the rate of the measurement.
+impl<N, D, G> CascadeLoop<N, D> for PD<N, D, G>
+where
+    N: RealField,
+    D: Dim + DimName,
+    G: Gain<N, D>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn output(
+        &mut self,
+        reference: &VectorN<N, D>,
+        measurement: &VectorN<N, D>,
+        rate: &VectorN<N, D>,
+        _dt: N,
+    ) -> (VectorN<N, D>, bool) {
+        self.calculate_force_saturated(reference, measurement, &VectorN::zero(), rate)
+    }
+}
+
+/// The derivative component only uses the rate of the measurement.
+impl<N, D, G> CascadeLoop<N, D> for PID<N, D, G>
+where
+    N: RealField,
+    D: Dim + DimName,
+    G: Gain<N, D>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn output(
+        &mut self,
+        reference: &VectorN<N, D>,
+        measurement: &VectorN<N, D>,
+        rate: &VectorN<N, D>,
+        dt: N,
+    ) -> (VectorN<N, D>, bool) {
+        let force = self.calculate_force(reference, measurement, &VectorN::zero(), rate, dt);
+        (force, self.is_saturated())
+    }
+
+    fn track_saturation(&mut self, achieved_output: &VectorN<N, D>, dt: N) {
+        PID::track_saturation(self, achieved_output, dt)
+    }
+}
+
+/// Outer position loop feeding an inner velocity loop.
+///
+/// If the inner loop saturates, the measured velocity is fed back as the
+/// achieved output of the outer loop, so an outer `PID` with
+/// `AntiWindup::BackCalculation` stops winding up.
+///
+/// ```rust
+/// use haptic_toolbox::{Cascade, PID};
+/// use nalgebra::{Vector1, U1};
+///
+/// let outer = PID::<f64, U1>::new(5.0, 0.0, 0.0);
+/// let inner = PID::<f64, U1>::new(50.0, 10.0, 0.0);
+/// let mut cascade = Cascade::new(outer, inner, 4);
+///
+/// // Unit mass that should move to 0.1.
+/// let (pos_ref, mut pos, mut vel) = (Vector1::new(0.1), Vector1::zeros(), Vector1::zeros());
+/// let dt = 0.001;
+/// for _ in 0..5000 {
+///     let force = cascade.update(&pos_ref, &pos, &vel, &Vector1::zeros(), dt);
+///     vel += force * dt;
+///     pos += vel * dt;
+/// }
+/// assert!((pos - pos_ref).norm() < 1e-3);
+/// ```
+pub struct Cascade<Outer, Inner, N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    outer: Outer,
+    inner: Inner,
+    rate_division: usize,
+
+    steps: usize,
+    outer_dt: N,
+    vel_ref: VectorN<N, D>,
+}
+
+impl<Outer, Inner, N, D> Cascade<Outer, Inner, N, D>
+where
+    Outer: CascadeLoop<N, D>,
+    Inner: CascadeLoop<N, D>,
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new cascade where the outer loop is updated every
+    /// `rate_division` updates of the inner loop.
+    pub fn new(outer: Outer, inner: Inner, rate_division: usize) -> Self {
+        assert!(rate_division > 0, "the rate division has to be positive");
+        Self {
+            outer,
+            inner,
+            rate_division,
+            steps: 0,
+            outer_dt: N::zero(),
+            vel_ref: Zero::zero(),
+        }
+    }
+
+    /// Calculates the force for tracking the reference position.
+    ///
+    /// `acc` is only used by the derivative component of the inner loop.
+    pub fn update(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        acc: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.outer_dt += dt;
+        if self.steps == 0 {
+            self.vel_ref = self.outer.output(pos_ref, pos, vel, self.outer_dt).0;
+            self.outer_dt = N::zero();
+        }
+        self.steps = (self.steps + 1) % self.rate_division;
+
+        let (force, saturated) = self.inner.output(&self.vel_ref, vel, acc, dt);
+        if saturated {
+            self.outer.track_saturation(vel, dt);
+        }
+        force
+    }
+
+    /// Returns the velocity reference of the outer loop.
+    pub fn vel_ref(&self) -> &VectorN<N, D> {
+        &self.vel_ref
+    }
+
+    /// Returns the outer loop.
+    pub fn outer(&self) -> &Outer {
+        &self.outer
+    }
+
+    /// Returns the outer loop mutably.
+    pub fn outer_mut(&mut self) -> &mut Outer {
+        &mut self.outer
+    }
+
+    /// Returns the inner loop.
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    /// Returns the inner loop mutably.
+    pub fn inner_mut(&mut self) -> &mut Inner {
+        &mut self.inner
+    }
+
+    /// Returns the rate division.
+    pub fn rate_division(&self) -> usize {
+        self.rate_division
+    }
+}
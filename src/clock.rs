@@ -0,0 +1,71 @@
+//! Timing
+//!
+//! The controllers take the time step `dt` as a scalar in seconds. `seconds`
+//! converts a `Duration` into such a time step and `Clock` measures the time
+//! between consecutive calls with a monotonic clock, so that the time step does
+//! not have to be tracked by hand.
+use nalgebra::{convert, RealField};
+use std::time::{Duration, Instant};
+
+/// Converts `duration` into seconds.
+///
+/// ```rust
+/// use haptic_toolbox::seconds;
+/// use std::time::Duration;
+///
+/// assert_eq!(seconds::<f64>(Duration::from_millis(1)), 0.001);
+/// ```
+pub fn seconds<N>(duration: Duration) -> N
+where
+    N: RealField,
+{
+    convert(duration.as_secs_f64())
+}
+
+/// Monotonic clock that measures the time between ticks.
+///
+/// ```rust
+/// use haptic_toolbox::Clock;
+/// use std::time::Duration;
+///
+/// let mut clock = Clock::new();
+/// assert_eq!(clock.tick(), Duration::from_secs(0));
+///
+/// std::thread::sleep(Duration::from_millis(1));
+/// assert!(clock.tick_seconds::<f64>() >= 0.001);
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Clock {
+    last_tick: Option<Instant>,
+}
+
+impl Clock {
+    /// Creates a new clock that has not ticked yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the time since the last tick, or zero for the first tick.
+    pub fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let elapsed = self
+            .last_tick
+            .map_or(Duration::from_secs(0), |last_tick| now - last_tick);
+        self.last_tick = Some(now);
+        elapsed
+    }
+
+    /// Returns the time since the last tick in seconds, or zero for the first
+    /// tick.
+    pub fn tick_seconds<N>(&mut self) -> N
+    where
+        N: RealField,
+    {
+        seconds(self.tick())
+    }
+
+    /// Resets the clock, so the next tick is the first one again.
+    pub fn reset(&mut self) {
+        self.last_tick = None;
+    }
+}
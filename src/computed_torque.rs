@@ -0,0 +1,179 @@
+//! Computed-Torque Controller
+//!
+//! A computed-torque controller linearizes the dynamics of a multi-joint arm
+//! with its model: the PD or PID error terms are calculated as a correction
+//! of the reference acceleration, which is then mapped to torques through
+//! the inverse dynamics `tau = M(q) * (qdd_ref + u) + c(q, qd) + g(q)`. With
+//! an accurate model every joint behaves like a decoupled unit mass, so the
+//! same gains track equally well all over the workspace.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+use std::marker::PhantomData;
+
+use crate::{Dynamics, Gain, PD, PID};
+
+/// Feedback loop calculating the error terms of a `ComputedTorque`
+/// controller.
+pub trait TrackingLoop<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Calculates the correction for tracking reference position and
+    /// velocity.
+    fn correction(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D>;
+}
+
+impl<N, D, G> TrackingLoop<N, D> for PD<N, D, G>
+where
+    N: RealField,
+    D: Dim,
+    G: Gain<N, D>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn correction(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        _dt: N,
+    ) -> VectorN<N, D> {
+        self.calculate_force(pos_ref, pos, vel_ref, vel)
+    }
+}
+
+impl<N, D, G> TrackingLoop<N, D> for PID<N, D, G>
+where
+    N: RealField,
+    D: Dim + DimName,
+    G: Gain<N, D>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn correction(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.calculate_force(pos_ref, pos, vel_ref, vel, dt)
+    }
+}
+
+/// Inverse dynamics controller for multi-joint arms.
+///
+/// ```rust
+/// use nalgebra::{Matrix2, Vector2, U2};
+/// use haptic_toolbox::{ComputedTorque, Dynamics, PD};
+///
+/// /// Two decoupled joints with different inertia and gravity on the second.
+/// struct Arm;
+///
+/// impl Dynamics<f64, U2> for Arm {
+///     fn mass_matrix(&self, _pos: &Vector2<f64>) -> Matrix2<f64> {
+///         Matrix2::new(1.0, 0.0, 0.0, 4.0)
+///     }
+///
+///     fn coriolis(&self, _pos: &Vector2<f64>, _vel: &Vector2<f64>) -> Vector2<f64> {
+///         Vector2::zeros()
+///     }
+///
+///     fn gravity(&self, _pos: &Vector2<f64>) -> Vector2<f64> {
+///         Vector2::new(0.0, 9.81)
+///     }
+/// }
+///
+/// let mut controller = ComputedTorque::new(Arm, PD::<f64, U2>::new(100.0, 20.0));
+///
+/// // Both joints get the same unit-mass correction, scaled by their inertia,
+/// // and the second holds its weight.
+/// let zeros = Vector2::zeros();
+/// let torque = controller.calculate_force(
+///     &Vector2::new(0.1, 0.1),
+///     &zeros,
+///     &zeros,
+///     &zeros,
+///     &zeros,
+///     0.001,
+/// );
+/// assert!((torque - Vector2::new(10.0, 49.81)).norm() < 1e-12);
+/// ```
+pub struct ComputedTorque<N, D, M, C>
+where
+    N: RealField,
+    D: Dim,
+    M: Dynamics<N, D>,
+    C: TrackingLoop<N, D>,
+    DefaultAllocator: Allocator<N, D> + Allocator<N, D, D>,
+{
+    dynamics: M,
+    controller: C,
+    _phantom: PhantomData<(N, D)>,
+}
+
+impl<N, D, M, C> ComputedTorque<N, D, M, C>
+where
+    N: RealField,
+    D: Dim,
+    M: Dynamics<N, D>,
+    C: TrackingLoop<N, D>,
+    DefaultAllocator: Allocator<N, D> + Allocator<N, D, D>,
+{
+    /// Creates a new `ComputedTorque` controller with the `dynamics` model of
+    /// the arm and the `controller` calculating the error terms.
+    pub fn new(dynamics: M, controller: C) -> Self {
+        Self {
+            dynamics,
+            controller,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Calculates the torques for tracking reference position, velocity and
+    /// acceleration.
+    pub fn calculate_force(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        acc_ref: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let acc = acc_ref + self.controller.correction(pos_ref, pos, vel_ref, vel, dt);
+        self.dynamics.inverse_dynamics(pos, vel, &acc)
+    }
+
+    /// Returns the dynamics model.
+    pub fn dynamics(&self) -> &M {
+        &self.dynamics
+    }
+
+    /// Returns the dynamics model mutably, e.g. to update a payload.
+    pub fn dynamics_mut(&mut self) -> &mut M {
+        &mut self.dynamics
+    }
+
+    /// Returns the controller of the error terms.
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    /// Returns the controller of the error terms mutably.
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+}
@@ -0,0 +1,192 @@
+//! Congestion-Adaptive Deadband Threshold
+//!
+//! Adjusts the deadband threshold online from network feedback. When the link
+//! is congested the threshold is increased to reduce the packet rate at the
+//! cost of perceptual fidelity, when there is spare capacity it is decreased
+//! again.
+use nalgebra::{convert, RealField};
+
+/// Feedback about the state of the network.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct NetworkFeedback<N>
+where
+    N: RealField,
+{
+    /// Current rate of the sender.
+    pub send_rate: N,
+    /// Rate the network can currently sustain, in the same unit as
+    /// `send_rate`.
+    pub available_rate: N,
+    /// Queueing delay in seconds.
+    pub queue_delay: N,
+    /// Fraction of lost packets between 0 and 1.
+    pub loss_rate: N,
+}
+
+/// Controller for the deadband threshold driven by network feedback.
+///
+/// The congestion level is the largest of the ratios of send rate to available
+/// rate, queue delay to target queue delay and loss rate to target loss rate.
+/// The threshold is scaled by the congestion level, clamped to its bounds and
+/// low-pass filtered with the smoothing time constant.
+///
+/// ```rust
+/// use haptic_toolbox::{CongestionAdaptiveThreshold, NetworkFeedback};
+///
+/// let mut controller = CongestionAdaptiveThreshold::new(0.1f64, 0.05, 0.3, 0.1);
+/// let congested = NetworkFeedback {
+///     send_rate: 1000.0,
+///     available_rate: 500.0,
+///     queue_delay: 0.0,
+///     loss_rate: 0.0,
+/// };
+/// for _ in 0..1000 {
+///     controller.update(&congested, 0.01);
+/// }
+/// assert!((controller.threshold() - 0.3).abs() < 1e-6);
+///
+/// let idle = NetworkFeedback { send_rate: 100.0, ..congested };
+/// for _ in 0..1000 {
+///     controller.update(&idle, 0.01);
+/// }
+/// assert!((controller.threshold() - 0.05).abs() < 1e-6);
+/// ```
+#[derive(Debug)]
+pub struct CongestionAdaptiveThreshold<N>
+where
+    N: RealField,
+{
+    threshold: N,
+    min_threshold: N,
+    max_threshold: N,
+    time_constant: N,
+    target_queue_delay: N,
+    target_loss_rate: N,
+}
+
+impl<N> CongestionAdaptiveThreshold<N>
+where
+    N: RealField,
+{
+    /// Creates a new `CongestionAdaptiveThreshold` starting at `threshold`.
+    ///
+    /// The target queue delay defaults to 10 ms and the target loss rate to
+    /// 1 %.
+    pub fn new(threshold: N, min_threshold: N, max_threshold: N, time_constant: N) -> Self {
+        assert!(
+            min_threshold > N::zero(),
+            "the minimum threshold has to be positive"
+        );
+        assert!(
+            min_threshold <= max_threshold,
+            "the minimum threshold cannot be above the maximum threshold"
+        );
+        assert!(
+            time_constant >= N::zero(),
+            "cannot assign a negative time constant"
+        );
+        Self {
+            threshold: threshold.max(min_threshold).min(max_threshold),
+            min_threshold,
+            max_threshold,
+            time_constant,
+            target_queue_delay: convert(0.01),
+            target_loss_rate: convert(0.01),
+        }
+    }
+
+    /// Updates the threshold from the latest network `feedback` and returns
+    /// it.
+    pub fn update(&mut self, feedback: &NetworkFeedback<N>, dt: N) -> N {
+        let congestion = self.congestion(feedback);
+        let target = (self.threshold * congestion)
+            .max(self.min_threshold)
+            .min(self.max_threshold);
+        let alpha = if self.time_constant + dt > N::zero() {
+            dt / (self.time_constant + dt)
+        } else {
+            N::one()
+        };
+        self.threshold += (target - self.threshold) * alpha;
+        self.threshold
+    }
+
+    /// Returns the congestion level for `feedback`, values above 1 mean the
+    /// link is congested.
+    pub fn congestion(&self, feedback: &NetworkFeedback<N>) -> N {
+        let rate = if feedback.available_rate > N::zero() {
+            feedback.send_rate / feedback.available_rate
+        } else {
+            N::max_value()
+        };
+        rate.max(feedback.queue_delay / self.target_queue_delay)
+            .max(feedback.loss_rate / self.target_loss_rate)
+    }
+
+    /// Returns the current threshold.
+    pub fn threshold(&self) -> N {
+        self.threshold
+    }
+
+    /// Returns the bounds of the threshold.
+    pub fn bounds(&self) -> (N, N) {
+        (self.min_threshold, self.max_threshold)
+    }
+
+    /// Sets the bounds of the threshold.
+    pub fn set_bounds(&mut self, min_threshold: N, max_threshold: N) {
+        assert!(
+            min_threshold > N::zero(),
+            "the minimum threshold has to be positive"
+        );
+        assert!(
+            min_threshold <= max_threshold,
+            "the minimum threshold cannot be above the maximum threshold"
+        );
+        self.min_threshold = min_threshold;
+        self.max_threshold = max_threshold;
+        self.threshold = self.threshold.max(min_threshold).min(max_threshold);
+    }
+
+    /// Returns the smoothing time constant.
+    pub fn time_constant(&self) -> N {
+        self.time_constant
+    }
+
+    /// Sets the smoothing time constant.
+    pub fn set_time_constant(&mut self, time_constant: N) {
+        assert!(
+            time_constant >= N::zero(),
+            "cannot assign a negative time constant"
+        );
+        self.time_constant = time_constant;
+    }
+
+    /// Returns the queue delay that is considered congested.
+    pub fn target_queue_delay(&self) -> N {
+        self.target_queue_delay
+    }
+
+    /// Sets the queue delay that is considered congested.
+    pub fn set_target_queue_delay(&mut self, target_queue_delay: N) {
+        assert!(
+            target_queue_delay > N::zero(),
+            "the target queue delay has to be positive"
+        );
+        self.target_queue_delay = target_queue_delay;
+    }
+
+    /// Returns the loss rate that is considered congested.
+    pub fn target_loss_rate(&self) -> N {
+        self.target_loss_rate
+    }
+
+    /// Sets the loss rate that is considered congested.
+    pub fn set_target_loss_rate(&mut self, target_loss_rate: N) {
+        assert!(
+            target_loss_rate > N::zero(),
+            "the target loss rate has to be positive"
+        );
+        self.target_loss_rate = target_loss_rate;
+    }
+}
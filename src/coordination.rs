@@ -0,0 +1,206 @@
+//! Multi-Master Multi-Slave Coordination
+//!
+//! Manages several master/slave pairs, where every pair is a position-position
+//! teleoperator with a `PD` controller on both sides. Couplings between the
+//! slaves of two pairs add a spring-damper that keeps their distance at an
+//! offset, e.g. for a formation or for cooperatively manipulating an object.
+//! The forces of the whole system are calculated in one `step`.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+use crate::{PositionMessage, PD};
+
+/// Forces commanded to a master/slave pair.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairForces<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Force commanded to the master.
+    pub master: VectorN<N, D>,
+    /// Force commanded to the slave.
+    pub slave: VectorN<N, D>,
+}
+
+struct Pair<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    master: PD<N, D>,
+    slave: PD<N, D>,
+}
+
+struct Coupling<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    first: usize,
+    second: usize,
+    offset: VectorN<N, D>,
+    stiffness: N,
+    damping: N,
+}
+
+/// Coordinates master/slave pairs with couplings between their slaves.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{Coordination, PositionMessage, PD};
+///
+/// let mut coordination = Coordination::<f64, U1>::new();
+/// let left = coordination.push_pair(PD::new(100.0, 0.0), PD::new(100.0, 0.0));
+/// let right = coordination.push_pair(PD::new(100.0, 0.0), PD::new(100.0, 0.0));
+/// // Hold an object of width 0.2 between both slaves.
+/// coordination.push_coupling(left, right, Vector1::new(0.2), 500.0, 0.0);
+///
+/// let state = |pos| PositionMessage { pos: Vector1::new(pos), vel: Vector1::new(0.0) };
+/// let forces = coordination.step(&[state(0.0), state(0.2)], &[state(0.0), state(0.3)]);
+///
+/// // The right slave is pulled back to its master and towards the left slave.
+/// assert!((forces[right].slave[0] + 60.0f64).abs() < 1e-12);
+/// assert!((forces[left].slave[0] - 50.0f64).abs() < 1e-12);
+/// assert!((forces[right].master[0] - 10.0f64).abs() < 1e-12);
+/// ```
+pub struct Coordination<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    pairs: Vec<Pair<N, D>>,
+    couplings: Vec<Coupling<N, D>>,
+}
+
+impl<N, D> Default for Coordination<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, D> Coordination<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `Coordination` without pairs.
+    pub fn new() -> Self {
+        Self {
+            pairs: Vec::new(),
+            couplings: Vec::new(),
+        }
+    }
+
+    /// Appends a pair with the controllers of master and slave and returns
+    /// its index.
+    pub fn push_pair(&mut self, master: PD<N, D>, slave: PD<N, D>) -> usize {
+        self.pairs.push(Pair { master, slave });
+        self.pairs.len() - 1
+    }
+
+    /// Couples the slaves of the pairs `first` and `second`, so that the
+    /// position of the second slave relative to the first is held at
+    /// `offset`.
+    pub fn push_coupling(
+        &mut self,
+        first: usize,
+        second: usize,
+        offset: VectorN<N, D>,
+        stiffness: N,
+        damping: N,
+    ) {
+        assert!(
+            first < self.pairs.len() && second < self.pairs.len(),
+            "there is no such pair"
+        );
+        assert!(first != second, "a pair cannot be coupled with itself");
+        assert!(stiffness >= N::zero(), "the stiffness must not be negative");
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        self.couplings.push(Coupling {
+            first,
+            second,
+            offset,
+            stiffness,
+            damping,
+        });
+    }
+
+    /// Calculates the forces of all pairs from the latest states of the
+    /// `masters` and the `slaves`, which are ordered like the pairs.
+    pub fn step(
+        &self,
+        masters: &[PositionMessage<N, D>],
+        slaves: &[PositionMessage<N, D>],
+    ) -> Vec<PairForces<N, D>> {
+        assert!(
+            masters.len() == self.pairs.len() && slaves.len() == self.pairs.len(),
+            "there has to be one master and one slave state per pair"
+        );
+        let mut forces = self
+            .pairs
+            .iter()
+            .zip(masters.iter().zip(slaves))
+            .map(|(pair, (master, slave))| PairForces {
+                master: pair.master.calculate_force(
+                    &slave.pos,
+                    &master.pos,
+                    &slave.vel,
+                    &master.vel,
+                ),
+                slave: pair
+                    .slave
+                    .calculate_force(&master.pos, &slave.pos, &master.vel, &slave.vel),
+            })
+            .collect::<Vec<_>>();
+
+        for coupling in &self.couplings {
+            let first = &slaves[coupling.first];
+            let second = &slaves[coupling.second];
+            let force = (&second.pos - &first.pos - &coupling.offset) * coupling.stiffness
+                + (&second.vel - &first.vel) * coupling.damping;
+            forces[coupling.first].slave += &force;
+            forces[coupling.second].slave -= force;
+        }
+        forces
+    }
+
+    /// Returns the number of pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns `true` if there are no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Returns the controller of the master of `pair`.
+    pub fn master(&self, pair: usize) -> &PD<N, D> {
+        &self.pairs[pair].master
+    }
+
+    /// Returns the controller of the master of `pair` mutably.
+    pub fn master_mut(&mut self, pair: usize) -> &mut PD<N, D> {
+        &mut self.pairs[pair].master
+    }
+
+    /// Returns the controller of the slave of `pair`.
+    pub fn slave(&self, pair: usize) -> &PD<N, D> {
+        &self.pairs[pair].slave
+    }
+
+    /// Returns the controller of the slave of `pair` mutably.
+    pub fn slave_mut(&mut self, pair: usize) -> &mut PD<N, D> {
+        &mut self.pairs[pair].slave
+    }
+}
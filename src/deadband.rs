@@ -1,48 +1,227 @@
-/// Detector for values that are in the deadband of the previous value.
-///
-/// A value is in the deadband of another value if its magnitude is withing
-/// a certain threshold.
-///
-/// Deadband compression is a technique that helps to filter out values
-/// whose impact is significant. For example in haptic applications where
-/// the feedback is some kind of force users are not able to differentiate
-/// between forces that are very close to another.
-/// Thresholds for just noticeable differences are available and often build
-/// upon models like the [Weber Fechner Law](https://en.wikipedia.org/wiki/Weber%E2%80%93Fechner_law)
-/// or (Steven's power law)[https://en.wikipedia.org/wiki/Stevens%27s_power_law].
-/// Filtering out values that are not noticeable can help to significantly
-/// reduce the network traffic in applications.
-///
-/// ```rust
-/// use nalgebra::Vector3;
-/// use haptic_toolbox::DeadbandDetector;
-///
-/// // Create a new deadband detector with a threshold of 10%.
-/// // All values that are within 10% of the previous value are considered to be
-/// // in its deadband. The initial value for deadband detection is set to 0.0.
-/// let mut deadband_detector = DeadbandDetector::new(0.1, Vector3::zeros());
-///
-/// assert!(!deadband_detector.is_in_deadband(&Vector3::new(0.1, 0.1, 0.1)));
-/// assert!(deadband_detector.is_in_deadband(&Vector3::new(0.11, 0.11, 0.11)));
-/// assert!(!deadband_detector.is_in_deadband(&Vector3::new(0.12, 0.12, 0.12)));
-/// assert!(!deadband_detector.is_in_deadband(&Vector3::new(0.0, 0.0, 0.0)));
-/// ```
+//! Deadband Compression
+//!
+//! A value is in the deadband of another value if its magnitude is withing
+//! a certain threshold.
+//!
+//! Deadband compression is a technique that helps to filter out values
+//! whose impact is significant. For example in haptic applications where
+//! the feedback is some kind of force users are not able to differentiate
+//! between forces that are very close to another.
+//! Thresholds for just noticeable differences are available and often build
+//! upon models like the [Weber Fechner Law](https://en.wikipedia.org/wiki/Weber%E2%80%93Fechner_law)
+//! or (Steven's power law)[https://en.wikipedia.org/wiki/Stevens%27s_power_law].
+//! Filtering out values that are not noticeable can help to significantly
+//! reduce the network traffic in applications.
+//!
+//! ```rust
+//! use nalgebra::Vector3;
+//! use haptic_toolbox::DeadbandDetector;
+//!
+//! // Create a new deadband detector with a threshold of 10%.
+//! // All values that are within 10% of the previous value are considered to be
+//! // in its deadband. The initial value for deadband detection is set to 0.0.
+//! let mut deadband_detector = DeadbandDetector::new(0.1, Vector3::zeros());
+//!
+//! assert!(!deadband_detector.is_in_deadband(&Vector3::new(0.1, 0.1, 0.1)));
+//! assert!(deadband_detector.is_in_deadband(&Vector3::new(0.11, 0.11, 0.11)));
+//! assert!(!deadband_detector.is_in_deadband(&Vector3::new(0.12, 0.12, 0.12)));
+//! assert!(!deadband_detector.is_in_deadband(&Vector3::new(0.0, 0.0, 0.0)));
+//! ```
 use nalgebra::{
     allocator::Allocator,
+    convert,
     dimension::{Dim, DimName},
     DefaultAllocator, RealField, VectorN,
 };
 
+use std::fmt;
+
+use crate::{JndModel, Weber};
+
+/// Mapping from the norm of the velocity to the factor the deadband gets
+/// scaled with.
+pub type VelocityMapping<N> = Box<dyn Fn(N) -> N + Send + Sync>;
+
+struct BoxedVelocityMapping<N>(VelocityMapping<N>);
+
+impl<N> fmt::Debug for BoxedVelocityMapping<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("VelocityMapping")
+    }
+}
+
+/// How the difference between two values is compared to the deadband.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DeadbandMode {
+    /// The norm of the difference is compared to the threshold relative to the
+    /// norm of the previous values.
+    #[default]
+    Norm,
+    /// Every component is compared to the threshold relative to its own
+    /// previous value. Values are outside the deadband as soon as a single
+    /// component is.
+    PerAxis,
+}
+
+/// Why a sample has to be transmitted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransmitReason {
+    /// The change of the values exceeds the deadband.
+    Magnitude,
+    /// The change of direction exceeds the angle threshold.
+    Direction,
+    /// The maximum number of consecutive samples was held.
+    KeepAlive,
+}
+
+/// Decision of a `DeadbandDetector` about a sample.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DeadbandDecision<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// The sample has to be transmitted and becomes the new reference value.
+    Transmit {
+        reason: TransmitReason,
+        value: VectorN<N, D>,
+    },
+    /// The sample is in the deadband, the receiver keeps reconstructing
+    /// `value`.
+    Hold { value: VectorN<N, D> },
+}
+
+impl<N, D> DeadbandDecision<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns `true` if the sample has to be transmitted.
+    pub fn is_transmit(&self) -> bool {
+        match self {
+            DeadbandDecision::Transmit { .. } => true,
+            DeadbandDecision::Hold { .. } => false,
+        }
+    }
+
+    /// Returns the value the receiver reconstructs after this decision.
+    pub fn value(&self) -> &VectorN<N, D> {
+        match self {
+            DeadbandDecision::Transmit { value, .. } => value,
+            DeadbandDecision::Hold { value } => value,
+        }
+    }
+
+    /// Returns why the sample has to be transmitted, if it has to be.
+    pub fn reason(&self) -> Option<TransmitReason> {
+        match self {
+            DeadbandDecision::Transmit { reason, .. } => Some(*reason),
+            DeadbandDecision::Hold { .. } => None,
+        }
+    }
+}
+
+/// Statistics about the samples processed by a `DeadbandDetector`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DeadbandStats<N>
+where
+    N: RealField,
+{
+    samples: usize,
+    transmitted: usize,
+    error_sum: N,
+    max_error: N,
+}
+
+impl<N> Default for DeadbandStats<N>
+where
+    N: RealField,
+{
+    fn default() -> Self {
+        Self {
+            samples: 0,
+            transmitted: 0,
+            error_sum: N::zero(),
+            max_error: N::zero(),
+        }
+    }
+}
+
+impl<N> DeadbandStats<N>
+where
+    N: RealField,
+{
+    /// Returns the number of samples that were checked.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    /// Returns the number of samples that were outside the deadband and need
+    /// to be transmitted.
+    pub fn transmitted(&self) -> usize {
+        self.transmitted
+    }
+
+    /// Returns the fraction of samples that did not need to be transmitted.
+    pub fn compression_ratio(&self) -> N {
+        if self.samples == 0 {
+            return N::zero();
+        }
+        N::one() - convert::<_, N>(self.transmitted as f64) / convert(self.samples as f64)
+    }
+
+    /// Returns the mean error between the samples and the held values a
+    /// receiver reconstructs.
+    pub fn mean_error(&self) -> N {
+        if self.samples == 0 {
+            return N::zero();
+        }
+        self.error_sum / convert(self.samples as f64)
+    }
+
+    /// Returns the maximum error between a sample and the held values a
+    /// receiver reconstructs.
+    pub fn max_error(&self) -> N {
+        self.max_error
+    }
+
+    fn record(&mut self, error: N, transmitted: bool) {
+        self.samples += 1;
+        if transmitted {
+            self.transmitted += 1;
+        }
+        self.error_sum += error;
+        self.max_error = self.max_error.max(error);
+    }
+}
+
+/// Detector for values that are in the deadband of the previous value.
+///
+/// The size of the deadband is computed by a `JndModel`, by default Weber's law
+/// with a relative threshold.
 #[derive(Debug)]
-pub struct DeadbandDetector<N, D>
+pub struct DeadbandDetector<N, D, M = Weber<N>>
 where
     N: RealField,
     D: Dim,
     DefaultAllocator: Allocator<N, D>,
+    M: JndModel<N>,
 {
     prev_vals: VectorN<N, D>,
-    threshold: N,
-    deadband: N,
+    model: M,
+    mode: DeadbandMode,
+    velocity_mapping: Option<BoxedVelocityMapping<N>>,
+    stats: DeadbandStats<N>,
+    max_hold: Option<usize>,
+    held: usize,
+    angle_threshold: Option<N>,
+    hysteresis: Option<N>,
+    transmitting: bool,
+    min_deadband: N,
+    max_deadband: Option<N>,
+    axis_scales: Option<VectorN<N, D>>,
 }
 
 impl<N, D> DeadbandDetector<N, D>
@@ -53,36 +232,473 @@ where
 {
     /// Creates a new `DeadbandDetector`.
     pub fn new(threshold: N, initial_vals: VectorN<N, D>) -> Self {
-        let mut deadband_detector = Self {
-            prev_vals: initial_vals,
-            deadband: N::zero(),
-            threshold,
-        };
-        deadband_detector.set_deadband();
-        deadband_detector
+        Self::with_mode(threshold, initial_vals, DeadbandMode::default())
     }
 
-    /// Checks if `vals` are in the deadband of the previously saved vals.
-    pub fn is_in_deadband(&mut self, vals: &VectorN<N, D>) -> bool {
-        let diff = (&self.prev_vals - vals).norm();
-        if diff > self.deadband {
-            self.prev_vals = vals.clone();
-            self.set_deadband();
-            false
-        } else {
-            true
-        }
+    /// Creates a new `DeadbandDetector` that compares values according to
+    /// `mode`.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector3;
+    /// use haptic_toolbox::{DeadbandDetector, DeadbandMode};
+    ///
+    /// let initial_vals = Vector3::new(10.0, 10.0, 0.1);
+    /// let mut norm_detector = DeadbandDetector::new(0.1, initial_vals);
+    /// let mut per_axis_detector =
+    ///     DeadbandDetector::with_mode(0.1, initial_vals, DeadbandMode::PerAxis);
+    ///
+    /// // Doubling the small component is masked by the large ones in norm mode.
+    /// let vals = Vector3::new(10.0, 10.0, 0.2);
+    /// assert!(norm_detector.is_in_deadband(&vals));
+    /// assert!(!per_axis_detector.is_in_deadband(&vals));
+    /// ```
+    pub fn with_mode(threshold: N, initial_vals: VectorN<N, D>, mode: DeadbandMode) -> Self {
+        Self::with_model(Weber::new(threshold), initial_vals, mode)
+    }
+
+    /// Creates a new `DeadbandDetector` with an independent relative threshold
+    /// for every axis.
+    ///
+    /// In `DeadbandMode::Norm` the deadband becomes an ellipsoid whose
+    /// half-axes are the `thresholds` times the norm of the previous values.
+    /// The thresholds are stored as axis scales of a Weber model with a
+    /// fraction of 1.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector3;
+    /// use haptic_toolbox::{DeadbandDetector, DeadbandMode};
+    ///
+    /// let thresholds = Vector3::new(0.1, 0.1, 0.01);
+    /// let mut deadband_detector =
+    ///     DeadbandDetector::with_thresholds(thresholds, Vector3::repeat(1.0), DeadbandMode::PerAxis);
+    ///
+    /// assert!(deadband_detector.is_in_deadband(&Vector3::new(1.05, 0.95, 1.0)));
+    /// assert!(!deadband_detector.is_in_deadband(&Vector3::new(1.0, 1.0, 1.05)));
+    /// ```
+    pub fn with_thresholds(
+        thresholds: VectorN<N, D>,
+        initial_vals: VectorN<N, D>,
+        mode: DeadbandMode,
+    ) -> Self {
+        let mut deadband_detector = Self::with_model(Weber::new(N::one()), initial_vals, mode);
+        deadband_detector.set_axis_scales(Some(thresholds));
+        deadband_detector
     }
 
     /// Sets the new deadband threshold.
     pub fn set_threshold(&mut self, threshold: N) {
-        assert!(threshold >= N::zero(), "cannot assign a negative threshold");
-        self.threshold = threshold;
+        self.model.set_fraction(threshold);
     }
 
     /// Returns the current deadband threshold.
     pub fn threshold(&self) -> N {
-        self.threshold
+        self.model.fraction()
+    }
+
+    /// Sets the threshold values have to exceed to start being transmitted
+    /// and the lower threshold they have to fall below to stop, which sets
+    /// the deadband threshold to `enter` and the hysteresis factor to
+    /// `exit / enter`.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0));
+    /// deadband_detector.set_hysteresis_thresholds(0.1, 0.05);
+    /// assert_eq!(deadband_detector.hysteresis(), Some(0.5));
+    ///
+    /// assert!(!deadband_detector.is_in_deadband(&Vector1::new(1.2)));
+    /// assert!(!deadband_detector.is_in_deadband(&Vector1::new(1.29)));
+    /// assert!(deadband_detector.is_in_deadband(&Vector1::new(1.3)));
+    /// ```
+    pub fn set_hysteresis_thresholds(&mut self, enter: N, exit: N) {
+        assert!(
+            enter > N::zero() && exit >= N::zero() && exit <= enter,
+            "the exit threshold has to be between 0 and the enter threshold"
+        );
+        self.set_threshold(enter);
+        self.set_hysteresis(Some(exit / enter));
+    }
+}
+
+impl<N, D, M> DeadbandDetector<N, D, M>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+    M: JndModel<N>,
+{
+    /// Creates a new `DeadbandDetector` whose deadband is computed by the JND
+    /// `model`.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector3;
+    /// use haptic_toolbox::{DeadbandDetector, DeadbandMode, Stevens};
+    ///
+    /// let stevens = Stevens::new(1.0, 0.5, 0.1);
+    /// let mut deadband_detector =
+    ///     DeadbandDetector::with_model(stevens, Vector3::new(4.0, 0.0, 0.0), DeadbandMode::Norm);
+    ///
+    /// assert!(deadband_detector.is_in_deadband(&Vector3::new(4.4, 0.0, 0.0)));
+    /// assert!(!deadband_detector.is_in_deadband(&Vector3::new(4.5, 0.0, 0.0)));
+    /// ```
+    pub fn with_model(model: M, initial_vals: VectorN<N, D>, mode: DeadbandMode) -> Self {
+        Self {
+            prev_vals: initial_vals,
+            model,
+            mode,
+            velocity_mapping: None,
+            stats: DeadbandStats::default(),
+            max_hold: None,
+            held: 0,
+            angle_threshold: None,
+            hysteresis: None,
+            transmitting: false,
+            min_deadband: N::zero(),
+            max_deadband: None,
+            axis_scales: None,
+        }
+    }
+
+    /// Checks if `vals` are in the deadband of the previously saved vals.
+    pub fn is_in_deadband(&mut self, vals: &VectorN<N, D>) -> bool {
+        !self.check(vals).is_transmit()
+    }
+
+    /// Checks `vals` against the deadband of the previously saved vals and
+    /// returns the decision including why they have to be transmitted.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::{DeadbandDecision, DeadbandDetector, TransmitReason};
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0));
+    /// deadband_detector.set_max_hold(Some(1));
+    ///
+    /// assert_eq!(
+    ///     deadband_detector.check(&Vector1::new(1.05)),
+    ///     DeadbandDecision::Hold { value: Vector1::new(1.0) },
+    /// );
+    /// assert_eq!(
+    ///     deadband_detector.check(&Vector1::new(1.05)),
+    ///     DeadbandDecision::Transmit {
+    ///         reason: TransmitReason::KeepAlive,
+    ///         value: Vector1::new(1.05),
+    ///     },
+    /// );
+    /// assert_eq!(
+    ///     deadband_detector.check(&Vector1::new(2.0)).reason(),
+    ///     Some(TransmitReason::Magnitude),
+    /// );
+    /// ```
+    pub fn check(&mut self, vals: &VectorN<N, D>) -> DeadbandDecision<N, D> {
+        self.update(vals, N::one())
+    }
+
+    /// Checks all `samples` in order and returns a decision for each of them.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let samples = [1.05, 1.2, 1.25, 1.5].iter().map(|&v| Vector1::new(v)).collect::<Vec<_>>();
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0));
+    /// let decisions = deadband_detector.process_batch(&samples);
+    /// let transmitted = decisions.iter().map(|d| d.is_transmit()).collect::<Vec<_>>();
+    /// assert_eq!(transmitted, vec![false, true, false, true]);
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0));
+    /// assert_eq!(deadband_detector.process_iter(&samples).collect::<Vec<_>>(), decisions);
+    /// ```
+    pub fn process_batch(&mut self, samples: &[VectorN<N, D>]) -> Vec<DeadbandDecision<N, D>> {
+        samples.iter().map(|vals| self.check(vals)).collect()
+    }
+
+    /// Lazily checks the `samples` in order, yielding a decision for each of
+    /// them.
+    pub fn process_iter<'a, I>(
+        &'a mut self,
+        samples: I,
+    ) -> impl Iterator<Item = DeadbandDecision<N, D>> + 'a
+    where
+        I: IntoIterator<Item = &'a VectorN<N, D>>,
+        I::IntoIter: 'a,
+    {
+        samples.into_iter().map(move |vals| self.check(vals))
+    }
+
+    /// Checks if `vals` are in the deadband of the previously saved vals while
+    /// moving with the velocity `vel`.
+    ///
+    /// The deadband is scaled by the factor the velocity mapping returns for
+    /// the norm of `vel`. Without a velocity mapping this is the same as
+    /// `is_in_deadband`.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector3;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector3::new(1.0, 0.0, 0.0));
+    /// deadband_detector.set_velocity_mapping(Some(Box::new(|vel| 1.0 + vel)));
+    ///
+    /// let vals = Vector3::new(1.15, 0.0, 0.0);
+    /// assert!(deadband_detector.is_in_deadband_at_velocity(&vals, &Vector3::new(1.0, 0.0, 0.0)));
+    /// assert!(!deadband_detector.is_in_deadband_at_velocity(&vals, &Vector3::zeros()));
+    /// ```
+    pub fn is_in_deadband_at_velocity(
+        &mut self,
+        vals: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+    ) -> bool {
+        !self.check_at_velocity(vals, vel).is_transmit()
+    }
+
+    /// Same as `check` but the deadband is scaled by the velocity mapping like
+    /// in `is_in_deadband_at_velocity`.
+    pub fn check_at_velocity(
+        &mut self,
+        vals: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+    ) -> DeadbandDecision<N, D> {
+        let scale = self
+            .velocity_mapping
+            .as_ref()
+            .map_or(N::one(), |mapping| (mapping.0)(vel.norm()));
+        self.update(vals, scale)
+    }
+
+    /// Returns the mapping from velocity to deadband scaling.
+    pub fn velocity_mapping(&self) -> Option<&VelocityMapping<N>> {
+        self.velocity_mapping.as_ref().map(|mapping| &mapping.0)
+    }
+
+    /// Sets the mapping from the norm of the velocity to the factor the
+    /// deadband gets scaled with, defaults to `None`.
+    ///
+    /// The mapping may capture state, e.g. a scale that is shared with the
+    /// application and changed at runtime.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::DeadbandDetector;
+    /// use std::sync::{
+    ///     atomic::{AtomicU32, Ordering},
+    ///     Arc,
+    /// };
+    ///
+    /// let scale = Arc::new(AtomicU32::new(1));
+    /// let mapping_scale = scale.clone();
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0));
+    /// deadband_detector.set_velocity_mapping(Some(Box::new(move |vel| {
+    ///     1.0 + vel * mapping_scale.load(Ordering::Relaxed) as f64
+    /// })));
+    ///
+    /// let (vals, vel) = (Vector1::new(1.15), Vector1::new(1.0));
+    /// assert!(deadband_detector.is_in_deadband_at_velocity(&vals, &vel));
+    /// scale.store(0, Ordering::Relaxed);
+    /// assert!(!deadband_detector.is_in_deadband_at_velocity(&vals, &vel));
+    /// ```
+    pub fn set_velocity_mapping(&mut self, velocity_mapping: Option<VelocityMapping<N>>) {
+        self.velocity_mapping = velocity_mapping.map(BoxedVelocityMapping);
+    }
+
+    /// Returns the JND model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Returns the JND model mutably to adjust its parameters.
+    pub fn model_mut(&mut self) -> &mut M {
+        &mut self.model
+    }
+
+    /// Returns the mode used to compare values.
+    pub fn mode(&self) -> DeadbandMode {
+        self.mode
+    }
+
+    /// Returns the statistics about the samples checked so far.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0f64));
+    /// for &val in &[1.05, 1.2, 1.25, 1.22] {
+    ///     deadband_detector.is_in_deadband(&Vector1::new(val));
+    /// }
+    ///
+    /// let stats = deadband_detector.stats();
+    /// assert_eq!(stats.samples(), 4);
+    /// assert_eq!(stats.transmitted(), 1);
+    /// assert!((stats.compression_ratio() - 0.75).abs() < 1e-12);
+    /// assert!((stats.max_error() - 0.05).abs() < 1e-12);
+    /// assert!((stats.mean_error() - 0.12 / 4.0).abs() < 1e-12);
+    /// ```
+    pub fn stats(&self) -> &DeadbandStats<N> {
+        &self.stats
+    }
+
+    /// Resets the statistics.
+    pub fn reset_stats(&mut self) {
+        self.stats = DeadbandStats::default();
+    }
+
+    /// Returns the maximum number of consecutive samples that are held.
+    pub fn max_hold(&self) -> Option<usize> {
+        self.max_hold
+    }
+
+    /// Sets the maximum number of consecutive samples that are held.
+    ///
+    /// After `max_hold` samples in a row were in the deadband the next sample
+    /// is reported to be outside of it, so that a receiver keeps getting
+    /// updates even if the signal does not change.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0));
+    /// deadband_detector.set_max_hold(Some(2));
+    ///
+    /// let vals = Vector1::new(1.0);
+    /// assert!(deadband_detector.is_in_deadband(&vals));
+    /// assert!(deadband_detector.is_in_deadband(&vals));
+    /// assert!(!deadband_detector.is_in_deadband(&vals));
+    /// assert!(deadband_detector.is_in_deadband(&vals));
+    /// ```
+    pub fn set_max_hold(&mut self, max_hold: Option<usize>) {
+        self.max_hold = max_hold;
+    }
+
+    /// Returns the maximum angle between two values in the deadband.
+    pub fn angle_threshold(&self) -> Option<N> {
+        self.angle_threshold
+    }
+
+    /// Sets the maximum angle in radians between the previous values and
+    /// values in the deadband.
+    ///
+    /// Values are outside the deadband if either the magnitude criterion or
+    /// the direction criterion is violated.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector2;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.2, Vector2::new(10.0, 0.0));
+    /// let rotated = Vector2::new(10.0 * 0.15f64.cos(), 10.0 * 0.15f64.sin());
+    /// assert!(deadband_detector.is_in_deadband(&rotated));
+    ///
+    /// deadband_detector.set_angle_threshold(Some(0.1));
+    /// assert!(!deadband_detector.is_in_deadband(&rotated));
+    /// ```
+    pub fn set_angle_threshold(&mut self, angle_threshold: Option<N>) {
+        self.angle_threshold = angle_threshold;
+    }
+
+    /// Returns the hysteresis factor.
+    pub fn hysteresis(&self) -> Option<N> {
+        self.hysteresis
+    }
+
+    /// Sets the hysteresis factor between 0 and 1.
+    ///
+    /// Once values left the deadband they keep being transmitted until they
+    /// change by less than `hysteresis` times the deadband. This avoids
+    /// flapping between transmitting and holding for signals that oscillate
+    /// right at the border of the deadband.
+    ///
+    /// The deadband is the threshold to start transmitting and `hysteresis`
+    /// times the deadband the threshold to stop again. A factor rather than a
+    /// second threshold keeps both thresholds in proportion while the
+    /// deadband follows the JND model, the velocity mapping and the limits.
+    /// With the default Weber model both thresholds can also be set directly
+    /// with `set_hysteresis_thresholds`.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(1.0));
+    /// deadband_detector.set_hysteresis(Some(0.5));
+    ///
+    /// assert!(!deadband_detector.is_in_deadband(&Vector1::new(1.2)));
+    /// // Within the deadband of 1.2, but not within half of it.
+    /// assert!(!deadband_detector.is_in_deadband(&Vector1::new(1.29)));
+    /// assert!(deadband_detector.is_in_deadband(&Vector1::new(1.3)));
+    /// assert!(deadband_detector.is_in_deadband(&Vector1::new(1.4)));
+    /// ```
+    pub fn set_hysteresis(&mut self, hysteresis: Option<N>) {
+        if let Some(hysteresis) = hysteresis {
+            assert!(
+                hysteresis >= N::zero() && hysteresis <= N::one(),
+                "hysteresis has to be between 0 and 1"
+            );
+        }
+        self.hysteresis = hysteresis;
+    }
+
+    /// Returns the absolute lower and the optional upper limit of the
+    /// deadband.
+    pub fn deadband_limits(&self) -> (N, Option<N>) {
+        (self.min_deadband, self.max_deadband)
+    }
+
+    /// Sets absolute limits for the deadband computed by the JND model.
+    ///
+    /// The lower limit keeps the deadband from collapsing for previous values
+    /// close to zero, which would otherwise transmit every bit of noise.
+    ///
+    /// ```rust
+    /// use nalgebra::Vector1;
+    /// use haptic_toolbox::DeadbandDetector;
+    ///
+    /// let mut deadband_detector = DeadbandDetector::new(0.1, Vector1::new(0.0));
+    /// assert!(!deadband_detector.is_in_deadband(&Vector1::new(0.001)));
+    ///
+    /// deadband_detector.set_deadband_limits(0.01, Some(1.0));
+    /// assert!(deadband_detector.is_in_deadband(&Vector1::new(0.005)));
+    ///
+    /// // Without the upper limit the deadband of 100.0 would be 10.0.
+    /// assert!(!deadband_detector.is_in_deadband(&Vector1::new(100.0)));
+    /// assert!(!deadband_detector.is_in_deadband(&Vector1::new(102.0)));
+    /// ```
+    pub fn set_deadband_limits(&mut self, min_deadband: N, max_deadband: Option<N>) {
+        assert!(
+            min_deadband >= N::zero(),
+            "cannot assign a negative deadband"
+        );
+        if let Some(max_deadband) = max_deadband {
+            assert!(
+                max_deadband >= min_deadband,
+                "the upper limit cannot be below the lower limit"
+            );
+        }
+        self.min_deadband = min_deadband;
+        self.max_deadband = max_deadband;
+    }
+
+    /// Returns the factors the deadband of each axis is scaled with.
+    pub fn axis_scales(&self) -> Option<&VectorN<N, D>> {
+        self.axis_scales.as_ref()
+    }
+
+    /// Sets the factors the deadband of each axis is scaled with, so that axes
+    /// with different dynamic ranges can have independent deadbands.
+    pub fn set_axis_scales(&mut self, axis_scales: Option<VectorN<N, D>>) {
+        if let Some(axis_scales) = &axis_scales {
+            assert!(
+                axis_scales
+                    .iter()
+                    .all(|&axis_scale| axis_scale >= N::zero()),
+                "cannot assign a negative axis scale"
+            );
+        }
+        self.axis_scales = axis_scales;
     }
 
     /// Sets the values the following ones should be compared to.
@@ -90,7 +706,98 @@ where
         self.prev_vals = vals.clone();
     }
 
-    fn set_deadband(&mut self) {
-        self.deadband = self.threshold * self.prev_vals.norm();
+    fn update(&mut self, vals: &VectorN<N, D>, scale: N) -> DeadbandDecision<N, D> {
+        let scale = match self.hysteresis {
+            Some(hysteresis) if self.transmitting => scale * hysteresis,
+            _ => scale,
+        };
+        let reason = if !self.contains(vals, scale) {
+            Some(TransmitReason::Magnitude)
+        } else if self.exceeds_angle(vals) {
+            Some(TransmitReason::Direction)
+        } else if self.max_hold.is_some_and(|max_hold| self.held >= max_hold) {
+            Some(TransmitReason::KeepAlive)
+        } else {
+            None
+        };
+
+        match reason {
+            Some(reason) => {
+                self.stats.record(N::zero(), true);
+                self.prev_vals = vals.clone();
+                self.held = 0;
+                self.transmitting = reason == TransmitReason::Magnitude;
+                DeadbandDecision::Transmit {
+                    reason,
+                    value: vals.clone(),
+                }
+            }
+            None => {
+                let error = (&self.prev_vals - vals).norm();
+                self.stats.record(error, false);
+                self.held += 1;
+                self.transmitting = false;
+                DeadbandDecision::Hold {
+                    value: self.prev_vals.clone(),
+                }
+            }
+        }
+    }
+
+    fn contains(&self, vals: &VectorN<N, D>, scale: N) -> bool {
+        let diff = &self.prev_vals - vals;
+        match (self.mode, &self.axis_scales) {
+            (DeadbandMode::Norm, None) => {
+                diff.norm() <= self.deadband(self.prev_vals.norm(), scale)
+            }
+            (DeadbandMode::Norm, Some(axis_scales)) => {
+                let deadband = self.deadband(self.prev_vals.norm(), scale);
+                let mut scaled_diff = N::zero();
+                for (&d, &axis_scale) in diff.iter().zip(axis_scales.iter()) {
+                    if axis_scale == N::zero() {
+                        if d != N::zero() {
+                            return false;
+                        }
+                    } else {
+                        scaled_diff += (d / axis_scale) * (d / axis_scale);
+                    }
+                }
+                scaled_diff.sqrt() <= deadband
+            }
+            (DeadbandMode::PerAxis, axis_scales) => self
+                .prev_vals
+                .iter()
+                .zip(diff.iter())
+                .enumerate()
+                .all(|(i, (&prev, &d))| {
+                    let axis_scale = axis_scales
+                        .as_ref()
+                        .map_or(N::one(), |axis_scales| axis_scales[i]);
+                    d.abs() <= self.deadband(prev.abs(), scale * axis_scale)
+                }),
+        }
+    }
+
+    fn deadband(&self, magnitude: N, scale: N) -> N {
+        let deadband = (self.model.deadband(magnitude) * scale).max(self.min_deadband);
+        match self.max_deadband {
+            Some(max_deadband) => deadband.min(max_deadband),
+            None => deadband,
+        }
+    }
+
+    fn exceeds_angle(&self, vals: &VectorN<N, D>) -> bool {
+        let angle_threshold = match self.angle_threshold {
+            Some(angle_threshold) => angle_threshold,
+            None => return false,
+        };
+        let norms = self.prev_vals.norm() * vals.norm();
+        if norms == N::zero() {
+            return false;
+        }
+        let cos = (self.prev_vals.dot(vals) / norms)
+            .max(-N::one())
+            .min(N::one());
+        cos.acos() > angle_threshold
     }
 }
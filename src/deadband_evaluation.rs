@@ -0,0 +1,130 @@
+//! Deadband Evaluation
+//!
+//! Offline analysis of deadband compression. A recorded trace is replayed
+//! through a `DeadbandDetector` and a `DeadbandReconstructor` for a sweep of
+//! thresholds and the achieved compression is compared to the introduced
+//! distortion, resulting in a rate-distortion table.
+use nalgebra::{
+    allocator::Allocator,
+    convert,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{DeadbandDetector, DeadbandReconstructor, ReconstructionStrategy};
+
+/// Result of replaying a trace with one threshold.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateDistortionPoint<N>
+where
+    N: RealField,
+{
+    /// Threshold of the deadband detector.
+    pub threshold: N,
+    /// Fraction of samples that did not need to be transmitted.
+    pub compression_ratio: N,
+    /// Root mean square error of the reconstruction.
+    pub rmse: N,
+    /// Largest error of the reconstruction.
+    pub peak_error: N,
+    /// Fraction of samples whose reconstruction error exceeds the just
+    /// noticeable difference of the original sample.
+    pub perceptual_distortion: N,
+}
+
+/// Replays recorded traces through deadband compression.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{DeadbandEvaluation, ReconstructionStrategy};
+///
+/// let trace = (0..1000)
+///     .map(|i| Vector1::new(1.0 + (i as f64 * 0.01).sin()))
+///     .collect::<Vec<_>>();
+/// let evaluation = DeadbandEvaluation::new(0.001, ReconstructionStrategy::ZeroOrderHold, 0.1);
+/// let table = evaluation.sweep(&trace, &[0.0, 0.05, 0.1, 0.2]);
+///
+/// assert_eq!(table[0].compression_ratio, 0.0);
+/// assert_eq!(table[0].rmse, 0.0);
+/// for points in table.windows(2) {
+///     assert!(points[1].compression_ratio >= points[0].compression_ratio);
+///     assert!(points[1].peak_error >= points[0].peak_error);
+/// }
+/// // Thresholds well below the JND never produce noticeable errors.
+/// assert_eq!(table[1].perceptual_distortion, 0.0);
+/// assert!(table[3].perceptual_distortion > 0.0);
+/// ```
+#[derive(Debug)]
+pub struct DeadbandEvaluation<N>
+where
+    N: RealField,
+{
+    dt: N,
+    strategy: ReconstructionStrategy,
+    jnd: N,
+}
+
+impl<N> DeadbandEvaluation<N>
+where
+    N: RealField,
+{
+    /// Creates a new `DeadbandEvaluation` for traces sampled every `dt`.
+    ///
+    /// `jnd` is the Weber fraction used to decide whether a reconstruction
+    /// error is noticeable.
+    pub fn new(dt: N, strategy: ReconstructionStrategy, jnd: N) -> Self {
+        Self { dt, strategy, jnd }
+    }
+
+    /// Replays `trace` with a single `threshold`.
+    ///
+    /// Sender and receiver start with zeros, so the first non zero sample is
+    /// always transmitted.
+    pub fn evaluate<D>(&self, trace: &[VectorN<N, D>], threshold: N) -> RateDistortionPoint<N>
+    where
+        D: Dim + DimName,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        let mut detector = DeadbandDetector::new(threshold, VectorN::zeros());
+        let mut reconstructor = DeadbandReconstructor::new(VectorN::zeros(), self.strategy);
+
+        let mut squared_error_sum = N::zero();
+        let mut peak_error = N::zero();
+        let mut noticeable = 0;
+        for vals in trace {
+            let received = if detector.is_in_deadband(vals) {
+                None
+            } else {
+                Some(vals)
+            };
+            let reconstructed = reconstructor.update(received, self.dt);
+            let error = (reconstructed - vals).norm();
+            squared_error_sum += error * error;
+            peak_error = peak_error.max(error);
+            if error > self.jnd * vals.norm() {
+                noticeable += 1;
+            }
+        }
+
+        let samples = convert::<_, N>(trace.len().max(1) as f64);
+        RateDistortionPoint {
+            threshold,
+            compression_ratio: detector.stats().compression_ratio(),
+            rmse: (squared_error_sum / samples).sqrt(),
+            peak_error,
+            perceptual_distortion: convert::<_, N>(noticeable as f64) / samples,
+        }
+    }
+
+    /// Replays `trace` for every threshold in `thresholds`.
+    pub fn sweep<D>(&self, trace: &[VectorN<N, D>], thresholds: &[N]) -> Vec<RateDistortionPoint<N>>
+    where
+        D: Dim + DimName,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        thresholds
+            .iter()
+            .map(|&threshold| self.evaluate(trace, threshold))
+            .collect()
+    }
+}
@@ -0,0 +1,196 @@
+//! Deadband Reconstruction
+//!
+//! The receiving side of deadband compression. Between two transmitted samples
+//! the signal has to be reconstructed, either by holding the last sample or by
+//! extrapolating it with the slope between the last two samples.
+//!
+//! Holding samples can inject energy into a bilateral loop, which is why the
+//! reconstruction can additionally be kept passive by a `TDPA`.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+use num_traits::Zero;
+
+use crate::TDPA;
+
+/// How the signal is reconstructed between two received samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReconstructionStrategy {
+    /// Holds the last received sample.
+    #[default]
+    ZeroOrderHold,
+    /// Extrapolates the last received sample with the slope between the last
+    /// two received samples.
+    FirstOrderPrediction,
+}
+
+/// Reconstructs a deadband compressed signal.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{DeadbandReconstructor, ReconstructionStrategy};
+///
+/// let mut reconstructor = DeadbandReconstructor::new(
+///     Vector1::new(0.0),
+///     ReconstructionStrategy::FirstOrderPrediction,
+/// );
+///
+/// // A ramp with slope 1.0 is transmitted every 4 samples.
+/// for i in 1..20 {
+///     let t = i as f64 * 0.25;
+///     let received = if i % 4 == 0 { Some(Vector1::new(t)) } else { None };
+///     let vals = reconstructor.update(received.as_ref(), 0.25);
+///     if i >= 4 {
+///         assert!((vals[0] - t).abs() < 1e-12);
+///     }
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DeadbandReconstructor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    strategy: ReconstructionStrategy,
+    vals: VectorN<N, D>,
+    slope: VectorN<N, D>,
+    elapsed: N,
+}
+
+impl<N, D> DeadbandReconstructor<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `DeadbandReconstructor`.
+    ///
+    /// `initial_vals` have to match the initial values of the transmitting
+    /// `DeadbandDetector`.
+    pub fn new(initial_vals: VectorN<N, D>, strategy: ReconstructionStrategy) -> Self {
+        Self {
+            strategy,
+            vals: initial_vals,
+            slope: Zero::zero(),
+            elapsed: N::zero(),
+        }
+    }
+
+    /// Advances the reconstruction by `dt` and returns the reconstructed
+    /// values.
+    ///
+    /// `received` are the values that were received in this step, if any.
+    pub fn update(&mut self, received: Option<&VectorN<N, D>>, dt: N) -> VectorN<N, D> {
+        self.elapsed += dt;
+        if let Some(vals) = received {
+            if self.elapsed > N::zero() {
+                self.slope = (vals - &self.vals) / self.elapsed;
+            }
+            self.vals = vals.clone();
+            self.elapsed = N::zero();
+            return vals.clone();
+        }
+
+        self.reconstruct()
+    }
+
+    /// Returns the reconstructed values without advancing the time.
+    pub fn reconstruct(&self) -> VectorN<N, D> {
+        match self.strategy {
+            ReconstructionStrategy::ZeroOrderHold => self.vals.clone(),
+            ReconstructionStrategy::FirstOrderPrediction => &self.vals + &self.slope * self.elapsed,
+        }
+    }
+
+    /// Returns the last received values.
+    pub fn last_received(&self) -> &VectorN<N, D> {
+        &self.vals
+    }
+
+    /// Returns the reconstruction strategy.
+    pub fn strategy(&self) -> ReconstructionStrategy {
+        self.strategy
+    }
+
+    /// Sets the reconstruction strategy.
+    pub fn set_strategy(&mut self, strategy: ReconstructionStrategy) {
+        self.strategy = strategy;
+    }
+}
+
+/// Reconstructs a deadband compressed force while keeping the port it is
+/// applied to passive.
+///
+/// The reconstructed force is passed through a `TDPA` that observes the energy
+/// flowing through the port and injects damping whenever the reconstruction
+/// would generate energy.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{PassiveDeadbandReconstructor, ReconstructionStrategy};
+///
+/// let mut reconstructor = PassiveDeadbandReconstructor::new(
+///     Vector1::new(0.0),
+///     ReconstructionStrategy::ZeroOrderHold,
+/// );
+///
+/// // Holding a force against the direction of motion would generate energy.
+/// let mut energy = 0.0;
+/// for i in 0..10 {
+///     let received = if i == 0 { Some(Vector1::new(-1.0)) } else { None };
+///     let vel = Vector1::new(0.5);
+///     let force = reconstructor.update(received.as_ref(), &vel, 0.001);
+///     energy += force.dot(&vel);
+///     assert!(energy >= -1e-12);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PassiveDeadbandReconstructor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    reconstructor: DeadbandReconstructor<N, D>,
+    tdpa: TDPA<N, D>,
+}
+
+impl<N, D> PassiveDeadbandReconstructor<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `PassiveDeadbandReconstructor`.
+    pub fn new(initial_vals: VectorN<N, D>, strategy: ReconstructionStrategy) -> Self {
+        Self {
+            reconstructor: DeadbandReconstructor::new(initial_vals, strategy),
+            tdpa: TDPA::default(),
+        }
+    }
+
+    /// Advances the reconstruction by `dt` and returns the passive
+    /// reconstructed force for the port moving with velocity `vel`.
+    pub fn update(
+        &mut self,
+        received: Option<&VectorN<N, D>>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let force = self.reconstructor.update(received, dt);
+        self.tdpa.calculate_force(vel, &force, dt)
+    }
+
+    /// Returns the underlying reconstructor.
+    pub fn reconstructor(&self) -> &DeadbandReconstructor<N, D> {
+        &self.reconstructor
+    }
+
+    /// Returns the TDPA that keeps the reconstruction passive.
+    pub fn tdpa(&self) -> &TDPA<N, D> {
+        &self.tdpa
+    }
+}
@@ -0,0 +1,214 @@
+//! Delay Line
+//!
+//! Delays values by a constant delay, by a random time-varying delay or by a
+//! delay profile recorded from a real network. The random delays are drawn
+//! from a seeded generator, so that a delay line behaves the same in every
+//! run. Values are released in the order they arrive, which differs from the
+//! order they were pushed in when the delay drops faster than time passes.
+use nalgebra::{convert, RealField};
+
+/// Small deterministic random number generator (SplitMix64).
+#[derive(Clone, Debug)]
+pub(crate) struct Random {
+    state: u64,
+}
+
+impl Random {
+    pub(crate) fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a uniformly distributed number in `[0, 1)`.
+    pub(crate) fn uniform<N: RealField>(&mut self) -> N {
+        convert((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64)
+    }
+}
+
+/// Delay of the values in a `DelayLine`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DelayProfile<N>
+where
+    N: RealField,
+{
+    /// Constant delay.
+    Constant(N),
+    /// Delay that is uniformly distributed between `min` and `max` and drawn
+    /// anew for every value from a generator seeded with `seed`.
+    Stochastic {
+        /// Minimal delay.
+        min: N,
+        /// Maximal delay.
+        max: N,
+        /// Seed of the random number generator.
+        seed: u64,
+    },
+    /// Delays recorded over time as pairs of time and delay, sorted by time.
+    /// A value pushed at some time gets the latest recorded delay before it,
+    /// or the first one.
+    Trace(Vec<(N, N)>),
+}
+
+#[derive(Clone, Debug)]
+struct Entry<N, T> {
+    release_time: N,
+    sequence: u64,
+    value: T,
+}
+
+/// Delays values by a `DelayProfile`.
+///
+/// ```rust
+/// use haptic_toolbox::{DelayLine, DelayProfile};
+///
+/// let mut line = DelayLine::new(DelayProfile::Trace(vec![(0.0, 0.03), (0.01, 0.01)]));
+/// line.push(1, 0.0);
+/// line.push(2, 0.01);
+///
+/// // The second value overtakes the first, since the delay dropped.
+/// assert_eq!(line.pop(0.02), Some(2));
+/// assert_eq!(line.pop(0.02), None);
+/// assert_eq!(line.pop(0.03), Some(1));
+///
+/// // Stochastic delays are reproducible with the same seed.
+/// let profile = DelayProfile::Stochastic { min: 0.01, max: 0.02, seed: 7 };
+/// let (mut a, mut b) = (DelayLine::new(profile.clone()), DelayLine::new(profile));
+/// a.push((), 0.0);
+/// b.push((), 0.0);
+/// assert_eq!(a.next_release_time(), b.next_release_time());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DelayLine<N, T>
+where
+    N: RealField,
+{
+    profile: DelayProfile<N>,
+    random: Random,
+    entries: Vec<Entry<N, T>>,
+    sequence: u64,
+}
+
+impl<N, T> DelayLine<N, T>
+where
+    N: RealField,
+{
+    /// Creates a new empty `DelayLine` with the delays of `profile`.
+    pub fn new(profile: DelayProfile<N>) -> Self {
+        let seed = match &profile {
+            DelayProfile::Constant(delay) => {
+                assert!(*delay >= N::zero(), "the delay must not be negative");
+                0
+            }
+            DelayProfile::Stochastic { min, max, seed } => {
+                assert!(
+                    *min >= N::zero() && min <= max,
+                    "the delays have to be in 0 <= min <= max"
+                );
+                *seed
+            }
+            DelayProfile::Trace(trace) => {
+                assert!(!trace.is_empty(), "the trace needs a delay");
+                assert!(
+                    trace.iter().all(|(_, delay)| *delay >= N::zero()),
+                    "the delays must not be negative"
+                );
+                assert!(
+                    trace.windows(2).all(|pair| pair[0].0 <= pair[1].0),
+                    "the trace has to be sorted by time"
+                );
+                0
+            }
+        };
+        Self {
+            profile,
+            random: Random::new(seed),
+            entries: Vec::new(),
+            sequence: 0,
+        }
+    }
+
+    /// Pushes `value` at `time` and returns its delay.
+    pub fn push(&mut self, value: T, time: N) -> N {
+        let delay = match &self.profile {
+            DelayProfile::Constant(delay) => *delay,
+            DelayProfile::Stochastic { min, max, .. } => {
+                *min + (*max - *min) * self.random.uniform()
+            }
+            DelayProfile::Trace(trace) => {
+                trace
+                    .iter()
+                    .take_while(|(trace_time, _)| *trace_time <= time)
+                    .last()
+                    .unwrap_or(&trace[0])
+                    .1
+            }
+        };
+        self.entries.push(Entry {
+            release_time: time + delay,
+            sequence: self.sequence,
+            value,
+        });
+        self.sequence += 1;
+        delay
+    }
+
+    /// Returns the value that arrived first until `time`.
+    pub fn pop(&mut self, time: N) -> Option<T> {
+        let mut next: Option<usize> = None;
+        for (i, entry) in self.entries.iter().enumerate() {
+            if entry.release_time > time {
+                continue;
+            }
+            let is_earlier = match next {
+                Some(next) => {
+                    let next = &self.entries[next];
+                    entry.release_time < next.release_time
+                        || entry.release_time == next.release_time && entry.sequence < next.sequence
+                }
+                None => true,
+            };
+            if is_earlier {
+                next = Some(i);
+            }
+        }
+        next.map(|i| self.entries.remove(i).value)
+    }
+
+    /// Returns the time the next value arrives.
+    pub fn next_release_time(&self) -> Option<N> {
+        self.entries.iter().map(|entry| entry.release_time).fold(
+            None,
+            |min, release_time| match min {
+                Some(min) if min <= release_time => Some(min),
+                _ => Some(release_time),
+            },
+        )
+    }
+
+    /// Removes all values.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    /// Returns the number of values that have not been popped yet.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if all values have been popped.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the delay profile.
+    pub fn profile(&self) -> &DelayProfile<N> {
+        &self.profile
+    }
+}
@@ -0,0 +1,144 @@
+//! Device Model
+//!
+//! Simulates a haptic device or a slave robot as a mass with viscous damping
+//! and optional Coulomb friction. The friction is applied as an impulse that
+//! is limited to stopping the device, so a device at rest sticks until the
+//! force exceeds the friction and the friction never reverses the motion.
+//! Controllers and stabilizers can be validated against the model before
+//! they run on hardware.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+/// Mass-damper model of a device with Coulomb friction.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::DeviceModel;
+///
+/// let mut device = DeviceModel::<f64, U1>::new(0.5, 2.0);
+/// device.set_friction(1.0);
+///
+/// // The friction holds the device against small forces.
+/// device.step(&Vector1::new(0.5), 0.001);
+/// assert_eq!(device.vel(), &Vector1::new(0.0));
+///
+/// // Larger forces accelerate it until the damping balances them.
+/// for _ in 0..10000 {
+///     device.step(&Vector1::new(3.0), 0.001);
+/// }
+/// assert!((device.vel()[0] - 1.0f64).abs() < 1e-6);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DeviceModel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    mass: N,
+    damping: N,
+    friction: N,
+    pos: VectorN<N, D>,
+    vel: VectorN<N, D>,
+}
+
+impl<N, D> DeviceModel<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `DeviceModel` with `mass` and `damping` at rest in the
+    /// origin.
+    pub fn new(mass: N, damping: N) -> Self {
+        assert!(mass > N::zero(), "the mass has to be positive");
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        Self {
+            mass,
+            damping,
+            friction: N::zero(),
+            pos: VectorN::zeros(),
+            vel: VectorN::zeros(),
+        }
+    }
+
+    /// Resets the device to rest in the origin.
+    pub fn reset(&mut self) {
+        self.pos = VectorN::zeros();
+        self.vel = VectorN::zeros();
+    }
+}
+
+impl<N, D> DeviceModel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Steps the device with the external `force` over `dt` and returns the
+    /// new position.
+    pub fn step(&mut self, force: &VectorN<N, D>, dt: N) -> &VectorN<N, D> {
+        let vel = &self.vel + (force - &self.vel * self.damping) * (dt / self.mass);
+        let friction_vel = self.friction * dt / self.mass;
+        let speed = vel.norm();
+        self.vel = if speed <= friction_vel {
+            vel * N::zero()
+        } else {
+            &vel * ((speed - friction_vel) / speed)
+        };
+        self.pos += &self.vel * dt;
+        &self.pos
+    }
+
+    /// Sets the position and the velocity of the device.
+    pub fn set_state(&mut self, pos: VectorN<N, D>, vel: VectorN<N, D>) {
+        self.pos = pos;
+        self.vel = vel;
+    }
+
+    /// Returns the position.
+    pub fn pos(&self) -> &VectorN<N, D> {
+        &self.pos
+    }
+
+    /// Returns the velocity.
+    pub fn vel(&self) -> &VectorN<N, D> {
+        &self.vel
+    }
+
+    /// Returns the mass.
+    pub fn mass(&self) -> N {
+        self.mass
+    }
+
+    /// Sets the mass.
+    pub fn set_mass(&mut self, mass: N) {
+        assert!(mass > N::zero(), "the mass has to be positive");
+        self.mass = mass;
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the viscous damping.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        self.damping = damping;
+    }
+
+    /// Returns the friction.
+    pub fn friction(&self) -> N {
+        self.friction
+    }
+
+    /// Sets the magnitude of the Coulomb friction force, defaults to `0`.
+    pub fn set_friction(&mut self, friction: N) {
+        assert!(friction >= N::zero(), "the friction must not be negative");
+        self.friction = friction;
+    }
+}
@@ -0,0 +1,92 @@
+//! Position Drift Compensation
+//!
+//! Admittance-type passivity controllers modify the commanded velocity, so the
+//! slave slowly drifts away from the master position. The drift compensator
+//! adds a velocity proportional to the position drift, but only as far as the
+//! energy that the passivity observer currently has available allows, so the
+//! compensation never violates passivity.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+/// Passivity-preserving position drift compensation.
+///
+/// The compensated velocity should be passed to the passivity controller, so
+/// that the energy of the compensation is observed.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{DriftCompensator, TDPA};
+///
+/// let mut tdpa = TDPA::<f64, U1>::default();
+/// let compensator = DriftCompensator::new(10.0);
+///
+/// // Moving against the environment force stores energy in the observer.
+/// tdpa.calculate_vel(&Vector1::new(1.0), &Vector1::new(2.0), 0.5);
+/// assert_eq!(tdpa.observer().energy(), 1.0);
+///
+/// // Correcting the drift would generate 2 J, but only 1 J is available.
+/// let vel = compensator.compensate(
+///     &Vector1::new(0.0),
+///     &Vector1::new(-2.0),
+///     &Vector1::new(0.1),
+///     tdpa.observer().energy(),
+///     1.0,
+/// );
+/// assert!((vel[0] - 0.5f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct DriftCompensator<N>
+where
+    N: RealField,
+{
+    gain: N,
+}
+
+impl<N> DriftCompensator<N>
+where
+    N: RealField,
+{
+    /// Creates a new `DriftCompensator` with the `gain` in 1/s from the
+    /// position drift to the compensation velocity.
+    pub fn new(gain: N) -> Self {
+        assert!(gain >= N::zero(), "the gain must not be negative");
+        Self { gain }
+    }
+
+    /// Returns `vel` with the compensation of the position `drift` added.
+    ///
+    /// `drift` is the reference minus the current position, `force` the force
+    /// at the port and `available_energy` the energy the passivity observer
+    /// has available, e.g. `TDPA::observer().energy()`.
+    pub fn compensate<D>(
+        &self,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        drift: &VectorN<N, D>,
+        available_energy: N,
+        dt: N,
+    ) -> VectorN<N, D>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        let compensation = drift * self.gain;
+        let energy = force.dot(&compensation) * dt;
+        let scale = if energy >= N::zero() {
+            N::one()
+        } else {
+            (available_energy.max(N::zero()) / -energy).min(N::one())
+        };
+        vel + compensation * scale
+    }
+
+    /// Returns the gain.
+    pub fn gain(&self) -> N {
+        self.gain
+    }
+
+    /// Sets the gain.
+    pub fn set_gain(&mut self, gain: N) {
+        assert!(gain >= N::zero(), "the gain must not be negative");
+        self.gain = gain;
+    }
+}
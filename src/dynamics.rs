@@ -0,0 +1,70 @@
+//! Dynamics Model
+//!
+//! The rigid-body dynamics of a multi-joint arm are
+//! `M(q) * qdd + c(q, qd) + g(q) = tau` with the mass matrix `M`, the
+//! Coriolis and centrifugal torques `c` and the gravity torques `g`. Model
+//! based controllers take a user-provided model through the `Dynamics`
+//! trait, e.g. from a robot description or a system identification.
+use nalgebra::{
+    allocator::Allocator, dimension::Dim, DefaultAllocator, MatrixN, RealField, VectorN,
+};
+
+/// Rigid-body dynamics of a system with `D` joints.
+///
+/// ```rust
+/// use nalgebra::{Matrix1, Vector1, U1};
+/// use haptic_toolbox::Dynamics;
+///
+/// /// Pendulum with a point mass at the end of a massless link, hanging down
+/// /// at zero.
+/// struct Pendulum {
+///     mass: f64,
+///     length: f64,
+/// }
+///
+/// impl Dynamics<f64, U1> for Pendulum {
+///     fn mass_matrix(&self, _pos: &Vector1<f64>) -> Matrix1<f64> {
+///         Matrix1::new(self.mass * self.length * self.length)
+///     }
+///
+///     fn coriolis(&self, _pos: &Vector1<f64>, _vel: &Vector1<f64>) -> Vector1<f64> {
+///         Vector1::zeros()
+///     }
+///
+///     fn gravity(&self, pos: &Vector1<f64>) -> Vector1<f64> {
+///         Vector1::new(self.mass * 9.81 * self.length * pos[0].sin())
+///     }
+/// }
+///
+/// let pendulum = Pendulum { mass: 2.0, length: 0.5 };
+/// let pos = Vector1::new(std::f64::consts::FRAC_PI_2);
+/// let torque = pendulum.inverse_dynamics(&pos, &Vector1::zeros(), &Vector1::new(1.0));
+/// assert!((torque[0] - (0.5 + 9.81)).abs() < 1e-12);
+/// ```
+pub trait Dynamics<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D> + Allocator<N, D, D>,
+{
+    /// Returns the mass matrix at position `pos`.
+    fn mass_matrix(&self, pos: &VectorN<N, D>) -> MatrixN<N, D>;
+
+    /// Returns the Coriolis and centrifugal torques at position `pos` with
+    /// velocity `vel`.
+    fn coriolis(&self, pos: &VectorN<N, D>, vel: &VectorN<N, D>) -> VectorN<N, D>;
+
+    /// Returns the gravity torques at position `pos`.
+    fn gravity(&self, pos: &VectorN<N, D>) -> VectorN<N, D>;
+
+    /// Returns the torques that accelerate the system with `acc` at position
+    /// `pos` with velocity `vel`.
+    fn inverse_dynamics(
+        &self,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        acc: &VectorN<N, D>,
+    ) -> VectorN<N, D> {
+        self.mass_matrix(pos) * acc + self.coriolis(pos, vel) + self.gravity(pos)
+    }
+}
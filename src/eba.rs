@@ -0,0 +1,147 @@
+//! Energy Bounding Algorithm
+//!
+//! The zero-order hold of a sampled haptic interface can generate energy,
+//! which makes stiff virtual walls unstable on devices with low update rates.
+//! The energy bounding algorithm keeps a reservoir of the energy that the
+//! physical damping of the device dissipated and bounds the force so that the
+//! energy output by the interface never exceeds it, as proposed by Kim and Ryu
+//! in "Stable Haptic Interaction Control using Energy Bounding Algorithm".
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+use num_traits::Zero;
+
+/// Energy bounding stabilizer for a haptic interface.
+///
+/// The energy output within the next sample is estimated from the
+/// displacement of the last sample.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::EBA;
+///
+/// let mut eba = EBA::<f64, U1>::new(1.0, 1.0);
+/// eba.calculate_force(&Vector1::new(0.0), &Vector1::new(0.0), 0.001);
+///
+/// // Moving 1 mm within a sample dissipates 1 mJ in the physical damping.
+/// let force = eba.calculate_force(&Vector1::new(0.001), &Vector1::new(2.0), 0.001);
+/// assert!((eba.energy() - 0.001f64).abs() < 1e-12);
+///
+/// // Pushing along the motion would output 2 mJ in the next sample.
+/// assert!((force[0] - 1.0f64).abs() < 1e-9);
+/// ```
+#[derive(Debug)]
+pub struct EBA<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    damping: N,
+    max_energy: N,
+    energy: N,
+    prev_pos: Option<VectorN<N, D>>,
+    prev_force: VectorN<N, D>,
+    displacement: VectorN<N, D>,
+}
+
+impl<N, D> EBA<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `EBA` for a device with the physical `damping` and the
+    /// maximum energy the reservoir can hold.
+    pub fn new(damping: N, max_energy: N) -> Self {
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        assert!(
+            max_energy >= N::zero(),
+            "the maximum energy must not be negative"
+        );
+        Self {
+            damping,
+            max_energy,
+            energy: N::zero(),
+            prev_pos: None,
+            prev_force: Zero::zero(),
+            displacement: Zero::zero(),
+        }
+    }
+
+    /// Resets the reservoir and the last position.
+    pub fn reset(&mut self) {
+        self.energy = N::zero();
+        self.prev_pos = None;
+        self.prev_force = Zero::zero();
+        self.displacement = Zero::zero();
+    }
+}
+
+impl<N, D> EBA<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Bounds the desired `force` rendered at position `pos` and returns it.
+    pub fn calculate_force(
+        &mut self,
+        pos: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        if let Some(prev_pos) = &self.prev_pos {
+            self.displacement = pos - prev_pos;
+            if dt > N::zero() {
+                self.energy += self.damping * self.displacement.dot(&self.displacement) / dt;
+            }
+            self.energy -= self.prev_force.dot(&self.displacement);
+            self.energy = self.energy.min(self.max_energy);
+        }
+        self.prev_pos = Some(pos.clone());
+
+        let output = force.dot(&self.displacement);
+        let available = self.energy.max(N::zero());
+        let displacement_norm_squared = self.displacement.dot(&self.displacement);
+        let force = if output > available && displacement_norm_squared > N::zero() {
+            force - &self.displacement * ((output - available) / displacement_norm_squared)
+        } else {
+            force.clone()
+        };
+        self.prev_force = force.clone();
+        force
+    }
+
+    /// Returns the energy in the reservoir.
+    pub fn energy(&self) -> N {
+        self.energy
+    }
+
+    /// Returns the physical damping of the device.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the physical damping of the device.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        self.damping = damping;
+    }
+
+    /// Returns the maximum energy the reservoir can hold.
+    pub fn max_energy(&self) -> N {
+        self.max_energy
+    }
+
+    /// Sets the maximum energy the reservoir can hold.
+    pub fn set_max_energy(&mut self, max_energy: N) {
+        assert!(
+            max_energy >= N::zero(),
+            "the maximum energy must not be negative"
+        );
+        self.max_energy = max_energy;
+    }
+}
@@ -0,0 +1,159 @@
+//! Energy Monitor
+//!
+//! Records the power and the energy flowing through a point of a pipeline,
+//! e.g. the device port, a channel port or a controller port. With one monitor
+//! per point the energy flows can be compared after a session and an energy
+//! leak can be localized to the stage between two monitors while tuning. Only
+//! the latest samples are kept in a ring buffer, so a monitor can stay attached
+//! during long sessions.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+
+/// Power and energy at a point of time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnergySample<N>
+where
+    N: RealField,
+{
+    /// Time since the monitor was created or cleared.
+    pub time: N,
+    /// Power `force · vel` of the sample.
+    pub power: N,
+    /// Energy that flowed since the monitor was created or cleared.
+    pub energy: N,
+}
+
+/// Records power and energy at a point of a pipeline.
+///
+/// The power `force · vel` is positive when energy flows into the monitored
+/// port, like for the `PassivityObserver`.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::EnergyMonitor;
+///
+/// let mut monitor = EnergyMonitor::new("master", 2);
+/// monitor.record(&Vector1::new(1.0), &Vector1::new(2.0), 0.5);
+/// monitor.record(&Vector1::new(-1.0), &Vector1::new(1.0), 0.5);
+/// monitor.record(&Vector1::new(2.0), &Vector1::new(1.0), 0.5);
+///
+/// // Only the latest two samples are kept, the energy covers all of them.
+/// assert_eq!(monitor.samples().count(), 2);
+/// assert_eq!(monitor.energy(), 1.5);
+///
+/// let mut csv = Vec::new();
+/// monitor.write_csv(&mut csv).unwrap();
+/// assert_eq!(
+///     String::from_utf8(csv).unwrap(),
+///     "label,time,power,energy\nmaster,1,-1,0.5\nmaster,1.5,2,1.5\n"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnergyMonitor<N>
+where
+    N: RealField,
+{
+    label: String,
+    capacity: usize,
+    samples: VecDeque<EnergySample<N>>,
+    time: N,
+    energy: N,
+}
+
+impl<N> EnergyMonitor<N>
+where
+    N: RealField,
+{
+    /// Creates a new `EnergyMonitor` named `label` that keeps the latest
+    /// `capacity` samples.
+    pub fn new(label: &str, capacity: usize) -> Self {
+        assert!(capacity > 0, "the capacity has to be positive");
+        Self {
+            label: label.to_owned(),
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+            time: N::zero(),
+            energy: N::zero(),
+        }
+    }
+
+    /// Records the `force` and `vel` of the next `dt` and returns the energy.
+    pub fn record<D>(&mut self, force: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> N
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        let power = force.dot(vel);
+        self.time += dt;
+        self.energy += power * dt;
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(EnergySample {
+            time: self.time,
+            power,
+            energy: self.energy,
+        });
+        self.energy
+    }
+
+    /// Returns the recorded samples from the oldest to the latest.
+    pub fn samples(&self) -> impl Iterator<Item = &EnergySample<N>> {
+        self.samples.iter()
+    }
+
+    /// Returns the latest sample.
+    pub fn latest(&self) -> Option<&EnergySample<N>> {
+        self.samples.back()
+    }
+
+    /// Returns the recorded samples from the oldest to the latest.
+    pub fn export(&self) -> Vec<EnergySample<N>> {
+        self.samples.iter().copied().collect()
+    }
+
+    /// Writes the recorded samples as CSV with a header line.
+    pub fn write_csv<W>(&self, mut writer: W) -> io::Result<()>
+    where
+        W: Write,
+    {
+        writeln!(writer, "label,time,power,energy")?;
+        for sample in &self.samples {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                self.label, sample.time, sample.power, sample.energy
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Removes all samples and resets the time and the energy.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+        self.time = N::zero();
+        self.energy = N::zero();
+    }
+
+    /// Returns the energy that flowed since the monitor was created or
+    /// cleared.
+    pub fn energy(&self) -> N {
+        self.energy
+    }
+
+    /// Returns the time since the monitor was created or cleared.
+    pub fn time(&self) -> N {
+        self.time
+    }
+
+    /// Returns the label.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Returns the number of samples that are kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
@@ -0,0 +1,168 @@
+//! Energy Tank
+//!
+//! An energy tank stores the energy that is dissipated by a system so that it
+//! can be spent again later on without the system becoming active. As long as
+//! all energy that is injected is drawn from the tank and the tank never falls
+//! below its lower bound the system stays passive.
+//!
+//! Variable stiffness or variable damping controllers can be made passive by
+//! passing their force through `EnergyTank::modulate`, which stores the energy
+//! the controller absorbs and scales down the force when the tank runs empty.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+#[derive(Clone, Debug)]
+pub struct EnergyTank<N>
+where
+    N: RealField,
+{
+    energy: N,
+    min_energy: N,
+    max_energy: Option<N>,
+}
+
+impl<N> EnergyTank<N>
+where
+    N: RealField,
+{
+    /// Creates a new `EnergyTank` with an initial `energy` that may never fall
+    /// below `min_energy`.
+    pub fn new(energy: N, min_energy: N) -> Self {
+        assert!(
+            energy >= min_energy,
+            "initial energy cannot be below the minimum energy"
+        );
+        Self {
+            energy,
+            min_energy,
+            max_energy: None,
+        }
+    }
+
+    /// Stores dissipated `energy` in the tank.
+    ///
+    /// Energy above the maximum energy is discarded.
+    pub fn store(&mut self, energy: N) {
+        assert!(energy >= N::zero(), "cannot store negative energy");
+        self.energy += energy;
+        if let Some(max_energy) = self.max_energy {
+            self.energy = self.energy.min(max_energy);
+        }
+    }
+
+    /// Extracts `energy` from the tank if enough energy is available.
+    ///
+    /// Returns `false` and leaves the tank untouched if the extraction would
+    /// make the tank fall below its minimum energy.
+    pub fn extract(&mut self, energy: N) -> bool {
+        assert!(energy >= N::zero(), "cannot extract negative energy");
+        if energy > self.available() {
+            false
+        } else {
+            self.energy -= energy;
+            true
+        }
+    }
+
+    /// Returns the fraction in [0, 1] of `energy` that can be extracted from
+    /// the tank.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::EnergyTank;
+    ///
+    /// let tank = EnergyTank::new(1.5, 0.5);
+    /// assert_eq!(tank.valve(0.5), 1.0);
+    /// assert_eq!(tank.valve(2.0), 0.5);
+    /// ```
+    pub fn valve(&self, energy: N) -> N {
+        let available = self.available();
+        if energy <= available {
+            N::one()
+        } else if available <= N::zero() {
+            N::zero()
+        } else {
+            available / energy
+        }
+    }
+
+    /// Makes `force` passive with respect to the velocity `vel` over the time
+    /// step `dt`.
+    ///
+    /// Energy absorbed by the force is stored in the tank. Energy injected by
+    /// the force is drawn from the tank and the force is scaled down by the
+    /// valve if the tank does not hold enough energy.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::EnergyTank;
+    /// use nalgebra::Vector2;
+    ///
+    /// let mut tank = EnergyTank::new(0.0, 0.0);
+    /// let vel = Vector2::new(1.0, 0.0);
+    ///
+    /// // Braking charges the tank.
+    /// let braking = tank.modulate(&Vector2::new(-2.0, 1.0), &vel, 0.1);
+    /// assert_eq!(braking, Vector2::new(-2.0, 1.0));
+    /// assert!((tank.energy() - 0.2f64).abs() < 1e-12);
+    ///
+    /// // Only half of the requested energy can be injected.
+    /// let pushing = tank.modulate(&Vector2::new(4.0, 0.0), &vel, 0.1);
+    /// assert!((pushing - Vector2::new(2.0, 0.0)).norm() < 1e-12);
+    /// assert!(tank.available().abs() < 1e-12);
+    /// ```
+    pub fn modulate<D>(
+        &mut self,
+        force: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        let energy = force.dot(vel) * dt;
+        if energy <= N::zero() {
+            self.store(-energy);
+            return force.clone();
+        }
+
+        let valve = self.valve(energy);
+        self.energy -= energy * valve;
+        if valve == N::one() {
+            force.clone()
+        } else {
+            force * valve
+        }
+    }
+
+    /// Returns the energy that can still be extracted from the tank.
+    pub fn available(&self) -> N {
+        self.energy - self.min_energy
+    }
+
+    /// Returns the energy currently stored in the tank.
+    pub fn energy(&self) -> N {
+        self.energy
+    }
+
+    /// Returns the minimum energy of the tank.
+    pub fn min_energy(&self) -> N {
+        self.min_energy
+    }
+
+    /// Returns the maximum energy of the tank.
+    pub fn max_energy(&self) -> Option<N> {
+        self.max_energy
+    }
+
+    /// Sets the maximum energy of the tank, which keeps a controller from
+    /// hoarding energy that it could release all at once later on.
+    pub fn set_max_energy(&mut self, max_energy: Option<N>) {
+        if let Some(max_energy) = max_energy {
+            assert!(
+                max_energy >= self.min_energy,
+                "the maximum energy cannot be below the minimum energy"
+            );
+            self.energy = self.energy.min(max_energy);
+        }
+        self.max_energy = max_energy;
+    }
+}
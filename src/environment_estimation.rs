@@ -0,0 +1,180 @@
+//! Environment Estimation
+//!
+//! Estimates the stiffness and the damping of the environment online from the
+//! force and the penetration measured at the slave with recursive least
+//! squares (RLS). The Kelvin–Voigt model `f = k x + b ẋ` is linear in its
+//! parameters, as is the Hunt–Crossley model `f = k x^n + b x^n ẋ` for a known
+//! exponent `n`. A forgetting factor below one tracks environments that
+//! change over time. The estimates feed the models of the `MMT` or adaptive
+//! controllers.
+use nalgebra::{convert, Matrix2, RealField, Vector2};
+
+/// Contact model whose parameters are estimated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContactModel<N>
+where
+    N: RealField,
+{
+    /// Linear spring and damper in parallel, `f = k x + b ẋ`.
+    KelvinVoigt,
+    /// Nonlinear spring and damper, `f = k x^n + b x^n ẋ`, with the
+    /// `exponent` `n`.
+    HuntCrossley {
+        /// Exponent of the penetration.
+        exponent: N,
+    },
+}
+
+/// Recursive least squares estimator of the environment.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{ContactModel, EnvironmentEstimator, EnvironmentModel, MMT};
+///
+/// let mut estimator = EnvironmentEstimator::new(ContactModel::KelvinVoigt, 1.0);
+/// for i in 1..100 {
+///     let x = 0.001 * i as f64;
+///     let v = 0.1 * (i as f64 * 0.1).cos();
+///     estimator.update(x, v, 2000.0 * x + 5.0 * v);
+/// }
+/// assert!((estimator.stiffness() - 2000.0).abs() < 0.1);
+/// assert!((estimator.damping() - 5.0).abs() < 0.01);
+///
+/// // The estimates update the model rendered at the master.
+/// let mut mmt = MMT::new(EnvironmentModel::free_space(), 0.01);
+/// mmt.update_model(EnvironmentModel::plane(
+///     Vector1::new(1.0),
+///     0.0,
+///     estimator.stiffness(),
+///     estimator.damping(),
+/// ));
+/// ```
+#[derive(Clone, Debug)]
+pub struct EnvironmentEstimator<N>
+where
+    N: RealField,
+{
+    model: ContactModel<N>,
+    forgetting_factor: N,
+    initial_covariance: N,
+    parameters: Vector2<N>,
+    covariance: Matrix2<N>,
+}
+
+impl<N> EnvironmentEstimator<N>
+where
+    N: RealField,
+{
+    /// Creates a new `EnvironmentEstimator` for `model` with the
+    /// `forgetting_factor` between `0` and `1`, where `1` never forgets.
+    pub fn new(model: ContactModel<N>, forgetting_factor: N) -> Self {
+        Self::assert_forgetting_factor(forgetting_factor);
+        let initial_covariance = convert(1e6);
+        Self {
+            model,
+            forgetting_factor,
+            initial_covariance,
+            parameters: Vector2::zeros(),
+            covariance: Matrix2::identity() * initial_covariance,
+        }
+    }
+
+    /// Updates the estimates with the measured `penetration`, its velocity
+    /// `penetration_vel` and the `force` of the environment. Samples without
+    /// contact are ignored.
+    pub fn update(&mut self, penetration: N, penetration_vel: N, force: N) {
+        if penetration <= N::zero() {
+            return;
+        }
+        let spring = match self.model {
+            ContactModel::KelvinVoigt => penetration,
+            ContactModel::HuntCrossley { exponent } => penetration.powf(exponent),
+        };
+        let damper = match self.model {
+            ContactModel::KelvinVoigt => penetration_vel,
+            ContactModel::HuntCrossley { .. } => spring * penetration_vel,
+        };
+        let regressor = Vector2::new(spring, damper);
+
+        let p_phi = self.covariance * regressor;
+        let gain = p_phi / (self.forgetting_factor + regressor.dot(&p_phi));
+        let error = force - regressor.dot(&self.parameters);
+        self.parameters += gain * error;
+        self.covariance = (self.covariance - gain * regressor.transpose() * self.covariance)
+            / self.forgetting_factor;
+    }
+
+    /// Returns the force the estimated model predicts.
+    pub fn predict(&self, penetration: N, penetration_vel: N) -> N {
+        if penetration <= N::zero() {
+            return N::zero();
+        }
+        match self.model {
+            ContactModel::KelvinVoigt => {
+                self.stiffness() * penetration + self.damping() * penetration_vel
+            }
+            ContactModel::HuntCrossley { exponent } => {
+                penetration.powf(exponent) * (self.stiffness() + self.damping() * penetration_vel)
+            }
+        }
+    }
+
+    /// Resets the estimates and the covariance.
+    pub fn reset(&mut self) {
+        self.parameters = Vector2::zeros();
+        self.covariance = Matrix2::identity() * self.initial_covariance;
+    }
+
+    /// Returns the estimated stiffness.
+    pub fn stiffness(&self) -> N {
+        self.parameters[0]
+    }
+
+    /// Returns the estimated damping.
+    pub fn damping(&self) -> N {
+        self.parameters[1]
+    }
+
+    /// Returns the covariance of the estimates.
+    pub fn covariance(&self) -> &Matrix2<N> {
+        &self.covariance
+    }
+
+    /// Returns the contact model.
+    pub fn model(&self) -> ContactModel<N> {
+        self.model
+    }
+
+    /// Returns the forgetting factor.
+    pub fn forgetting_factor(&self) -> N {
+        self.forgetting_factor
+    }
+
+    /// Sets the forgetting factor between `0` and `1`.
+    pub fn set_forgetting_factor(&mut self, forgetting_factor: N) {
+        Self::assert_forgetting_factor(forgetting_factor);
+        self.forgetting_factor = forgetting_factor;
+    }
+
+    /// Returns the initial covariance.
+    pub fn initial_covariance(&self) -> N {
+        self.initial_covariance
+    }
+
+    /// Sets the initial covariance of the estimates, which is applied on the
+    /// next reset, defaults to `1e6`.
+    pub fn set_initial_covariance(&mut self, initial_covariance: N) {
+        assert!(
+            initial_covariance > N::zero(),
+            "the initial covariance has to be positive"
+        );
+        self.initial_covariance = initial_covariance;
+    }
+
+    fn assert_forgetting_factor(forgetting_factor: N) {
+        assert!(
+            forgetting_factor > N::zero() && forgetting_factor <= N::one(),
+            "the forgetting factor has to be in (0, 1]"
+        );
+    }
+}
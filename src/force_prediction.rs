@@ -0,0 +1,125 @@
+//! Force Prediction
+//!
+//! The force measured at the slave is a response to where the master was one
+//! round trip ago. The predictor corrects the delayed force by the change the
+//! environment model predicts for the motion of the master since then, so
+//! that the predictive display feels the contact when the master makes it and
+//! not one round trip later. While no force has been received, the model
+//! alone is rendered.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+use std::collections::VecDeque;
+
+use crate::{EnvironmentModel, PositionMessage};
+
+#[derive(Clone, Debug)]
+struct TimedState<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    time: N,
+    state: PositionMessage<N, D>,
+}
+
+#[derive(Clone, Debug)]
+struct ReceivedForce<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    force: VectorN<N, D>,
+    state: PositionMessage<N, D>,
+}
+
+/// Extrapolates the force of the slave during the round trip.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{EnvironmentModel, ForcePredictor, PositionMessage};
+///
+/// let mut predictor = ForcePredictor::<f64, U1>::new(EnvironmentModel::plane(
+///     Vector1::new(-1.0),
+///     0.0,
+///     1000.0,
+///     0.0,
+/// ));
+/// let state = |pos| PositionMessage { pos: Vector1::new(pos), vel: Vector1::new(0.0) };
+///
+/// predictor.record(state(0.001), 0.0);
+/// predictor.record(state(0.003), 0.1);
+///
+/// // The slave measured 1.1 N at the master state sent at 0, the master moved
+/// // 2 mm deeper since.
+/// predictor.receive(Vector1::new(-1.1), 0.0);
+/// let force = predictor.predict(&state(0.003));
+/// assert!((force[0] + 3.1f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ForcePredictor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    model: EnvironmentModel<N, D>,
+    history: VecDeque<TimedState<N, D>>,
+    received: Option<ReceivedForce<N, D>>,
+}
+
+impl<N, D> ForcePredictor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `ForcePredictor` with the environment `model`.
+    pub fn new(model: EnvironmentModel<N, D>) -> Self {
+        Self {
+            model,
+            history: VecDeque::new(),
+            received: None,
+        }
+    }
+
+    /// Records the master `state` that is sent to the slave at `time`.
+    pub fn record(&mut self, state: PositionMessage<N, D>, time: N) {
+        self.history.push_back(TimedState { time, state });
+    }
+
+    /// Receives the `force` the slave measured in response to the master
+    /// state sent at `master_time`. Older states are forgotten.
+    pub fn receive(&mut self, force: VectorN<N, D>, master_time: N) {
+        while self.history.len() > 1 && self.history[1].time <= master_time {
+            self.history.pop_front();
+        }
+        let state = match self.history.front() {
+            Some(timed) if timed.time <= master_time => timed.state.clone(),
+            _ => return,
+        };
+        self.received = Some(ReceivedForce { force, state });
+    }
+
+    /// Predicts the force of the slave for the current master `state`.
+    pub fn predict(&self, state: &PositionMessage<N, D>) -> VectorN<N, D> {
+        let predicted = self.model.force(&state.pos, &state.vel);
+        match &self.received {
+            Some(received) => {
+                &received.force + predicted
+                    - self.model.force(&received.state.pos, &received.state.vel)
+            }
+            None => predicted,
+        }
+    }
+
+    /// Returns the environment model.
+    pub fn model(&self) -> &EnvironmentModel<N, D> {
+        &self.model
+    }
+
+    /// Sets the environment model, e.g. with the latest estimate.
+    pub fn set_model(&mut self, model: EnvironmentModel<N, D>) {
+        self.model = model;
+    }
+}
@@ -0,0 +1,220 @@
+//! Four-Channel Teleoperation
+//!
+//! Lawrence's four-channel architecture transmits the position and the force
+//! in both directions. With the blocks `C1` to `C6` and the local controllers
+//! `C_m` and `C_s` the commanded forces of master and slave are
+//!
+//! `force_m = C6 * force_h - C2 * force_e - C_m(pos_m, vel_m) - C4(pos_s, vel_s)`
+//!
+//! `force_s = C3 * force_h - C5 * force_e + C1(pos_m, vel_m) - C_s(pos_s, vel_s)`
+//!
+//! where `force_h` is the force of the operator on the master and `force_e`
+//! the force of the environment on the slave. The position blocks are `PD`
+//! controllers applied to position and velocity, the force blocks are scalar
+//! gains. The classic architectures are special cases, e.g. position-position
+//! with `C1 = -C4 = C_m = C_s` and zero force blocks. The system is
+//! transparent for `C1 = Z_s + C_s`, `C4 = -(Z_m + C_m)`, `C2 = 1 + C6` and
+//! `C3 = 1 + C5` with the impedances `Z_m` and `Z_s` of the devices.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+use crate::PD;
+
+/// State of a port that is exchanged between master and slave.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FourChannelMessage<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Position of the device.
+    pub pos: VectorN<N, D>,
+    /// Velocity of the device.
+    pub vel: VectorN<N, D>,
+    /// Measured external force, of the operator at the master and of the
+    /// environment at the slave.
+    pub force: VectorN<N, D>,
+}
+
+/// Four-channel bilateral controller.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{FourChannel, FourChannelMessage, PD};
+///
+/// // Position-position architecture.
+/// let mut four_channel = FourChannel::<f64, U1>::new(PD::new(100.0, 10.0), PD::new(100.0, 10.0));
+/// four_channel.set_c1(PD::new(100.0, 10.0));
+/// four_channel.set_c4(PD::new(-100.0, -10.0));
+///
+/// let master = FourChannelMessage {
+///     pos: Vector1::new(0.1),
+///     vel: Vector1::new(0.0),
+///     force: Vector1::new(0.0),
+/// };
+/// let slave = FourChannelMessage {
+///     pos: Vector1::new(0.0),
+///     vel: Vector1::new(0.0),
+///     force: Vector1::new(0.0),
+/// };
+///
+/// // Both sides are pulled towards each other.
+/// let force_m = four_channel.calculate_master_force(&master, &slave);
+/// let force_s = four_channel.calculate_slave_force(&slave, &master);
+/// assert!((force_m[0] + 10.0f64).abs() < 1e-12);
+/// assert!((force_s[0] - 10.0f64).abs() < 1e-12);
+/// ```
+pub struct FourChannel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    c_m: PD<N, D>,
+    c_s: PD<N, D>,
+    c1: Option<PD<N, D>>,
+    c4: Option<PD<N, D>>,
+    c2: N,
+    c3: N,
+    c5: N,
+    c6: N,
+}
+
+impl<N, D> FourChannel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `FourChannel` with the local controllers of master and
+    /// slave, whose channel and force blocks are all zero.
+    pub fn new(c_m: PD<N, D>, c_s: PD<N, D>) -> Self {
+        Self {
+            c_m,
+            c_s,
+            c1: None,
+            c4: None,
+            c2: N::zero(),
+            c3: N::zero(),
+            c5: N::zero(),
+            c6: N::zero(),
+        }
+    }
+
+    /// Calculates the force commanded to the master from the `master` state
+    /// and the latest received `slave` state.
+    pub fn calculate_master_force(
+        &self,
+        master: &FourChannelMessage<N, D>,
+        slave: &FourChannelMessage<N, D>,
+    ) -> VectorN<N, D> {
+        let mut force =
+            &master.force * self.c6 - &slave.force * self.c2 - Self::apply(&self.c_m, master);
+        if let Some(c4) = &self.c4 {
+            force -= Self::apply(c4, slave);
+        }
+        force
+    }
+
+    /// Calculates the force commanded to the slave from the `slave` state and
+    /// the latest received `master` state.
+    pub fn calculate_slave_force(
+        &self,
+        slave: &FourChannelMessage<N, D>,
+        master: &FourChannelMessage<N, D>,
+    ) -> VectorN<N, D> {
+        let mut force =
+            &master.force * self.c3 - &slave.force * self.c5 - Self::apply(&self.c_s, slave);
+        if let Some(c1) = &self.c1 {
+            force += Self::apply(c1, master);
+        }
+        force
+    }
+
+    fn apply(block: &PD<N, D>, state: &FourChannelMessage<N, D>) -> VectorN<N, D> {
+        let zero = state.pos.map(|_| N::zero());
+        block.calculate_force(&state.pos, &zero, &state.vel, &zero)
+    }
+
+    /// Returns the local controller of the master.
+    pub fn c_m(&self) -> &PD<N, D> {
+        &self.c_m
+    }
+
+    /// Sets the local controller of the master.
+    pub fn set_c_m(&mut self, c_m: PD<N, D>) {
+        self.c_m = c_m;
+    }
+
+    /// Returns the local controller of the slave.
+    pub fn c_s(&self) -> &PD<N, D> {
+        &self.c_s
+    }
+
+    /// Sets the local controller of the slave.
+    pub fn set_c_s(&mut self, c_s: PD<N, D>) {
+        self.c_s = c_s;
+    }
+
+    /// Returns the block from the master motion to the slave force.
+    pub fn c1(&self) -> Option<&PD<N, D>> {
+        self.c1.as_ref()
+    }
+
+    /// Sets the block from the master motion to the slave force.
+    pub fn set_c1(&mut self, c1: PD<N, D>) {
+        self.c1 = Some(c1);
+    }
+
+    /// Returns the block from the environment force to the master force.
+    pub fn c2(&self) -> N {
+        self.c2
+    }
+
+    /// Sets the block from the environment force to the master force,
+    /// defaults to `0`.
+    pub fn set_c2(&mut self, c2: N) {
+        self.c2 = c2;
+    }
+
+    /// Returns the block from the operator force to the slave force.
+    pub fn c3(&self) -> N {
+        self.c3
+    }
+
+    /// Sets the block from the operator force to the slave force, defaults to
+    /// `0`.
+    pub fn set_c3(&mut self, c3: N) {
+        self.c3 = c3;
+    }
+
+    /// Returns the block from the slave motion to the master force.
+    pub fn c4(&self) -> Option<&PD<N, D>> {
+        self.c4.as_ref()
+    }
+
+    /// Sets the block from the slave motion to the master force.
+    pub fn set_c4(&mut self, c4: PD<N, D>) {
+        self.c4 = Some(c4);
+    }
+
+    /// Returns the local force feedback of the slave.
+    pub fn c5(&self) -> N {
+        self.c5
+    }
+
+    /// Sets the local force feedback of the slave, defaults to `0`.
+    pub fn set_c5(&mut self, c5: N) {
+        self.c5 = c5;
+    }
+
+    /// Returns the local force feedback of the master.
+    pub fn c6(&self) -> N {
+        self.c6
+    }
+
+    /// Sets the local force feedback of the master, defaults to `0`.
+    pub fn set_c6(&mut self, c6: N) {
+        self.c6 = c6;
+    }
+}
@@ -0,0 +1,155 @@
+//! Controller Gains
+//!
+//! The gains of the `PD` and `PID` controllers are generic, so that the same
+//! controller can either be tuned with one scalar gain for all degrees of
+//! freedom, with one gain per degree of freedom or with a full gain matrix for
+//! coupled (MIMO) systems.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, MatrixN, RealField, VectorN,
+};
+
+/// Gain that maps an error onto a force.
+pub trait Gain<N, D>: Clone
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Multiplies the gain with `error`.
+    fn apply(&self, error: &VectorN<N, D>) -> VectorN<N, D>;
+
+    /// Returns the error that results in `force`, or `None` if there is no
+    /// such error.
+    ///
+    /// Axes with a zero gain are mapped onto a zero error.
+    fn apply_inverse(&self, force: &VectorN<N, D>) -> Option<VectorN<N, D>>;
+
+    /// Returns the gain of every axis.
+    fn diagonal(&self) -> VectorN<N, D>
+    where
+        D: DimName;
+}
+
+/// The same gain is used for all axes.
+impl<N, D> Gain<N, D> for N
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn apply(&self, error: &VectorN<N, D>) -> VectorN<N, D> {
+        error * *self
+    }
+
+    fn apply_inverse(&self, force: &VectorN<N, D>) -> Option<VectorN<N, D>> {
+        if *self == N::zero() {
+            None
+        } else {
+            Some(force / *self)
+        }
+    }
+
+    fn diagonal(&self) -> VectorN<N, D>
+    where
+        D: DimName,
+    {
+        VectorN::repeat(*self)
+    }
+}
+
+/// Every axis has its own gain.
+impl<N, D> Gain<N, D> for VectorN<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn apply(&self, error: &VectorN<N, D>) -> VectorN<N, D> {
+        self.component_mul(error)
+    }
+
+    fn apply_inverse(&self, force: &VectorN<N, D>) -> Option<VectorN<N, D>> {
+        Some(self.zip_map(force, |gain, force| {
+            if gain == N::zero() {
+                N::zero()
+            } else {
+                force / gain
+            }
+        }))
+    }
+
+    fn diagonal(&self) -> VectorN<N, D>
+    where
+        D: DimName,
+    {
+        self.clone()
+    }
+}
+
+/// Full gain matrix that couples the axes.
+///
+/// ```rust
+/// use haptic_toolbox::{MatrixGain, PD};
+/// use nalgebra::{Matrix2, Vector2, U2};
+///
+/// let k_p = MatrixGain::new(Matrix2::new(10.0, 5.0, 0.0, 10.0));
+/// let k_d = MatrixGain::new(Matrix2::zeros());
+/// let pd = PD::<f64, U2, _>::new(k_p, k_d);
+///
+/// let zeros = Vector2::zeros();
+/// let force = pd.calculate_force(&Vector2::new(0.0, 1.0), &zeros, &zeros, &zeros);
+/// assert_eq!(force, Vector2::new(5.0, 10.0));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatrixGain<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D, D>,
+{
+    matrix: MatrixN<N, D>,
+    inverse: Option<MatrixN<N, D>>,
+}
+
+impl<N, D> MatrixGain<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D, D>,
+{
+    /// Creates a new gain matrix.
+    pub fn new(matrix: MatrixN<N, D>) -> Self {
+        let inverse = matrix.clone().try_inverse();
+        Self { matrix, inverse }
+    }
+
+    /// Returns the gain matrix.
+    pub fn matrix(&self) -> &MatrixN<N, D> {
+        &self.matrix
+    }
+}
+
+impl<N, D> Gain<N, D> for MatrixGain<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D> + Allocator<N, D, D>,
+{
+    fn apply(&self, error: &VectorN<N, D>) -> VectorN<N, D> {
+        &self.matrix * error
+    }
+
+    /// Returns `None` if the gain matrix is singular.
+    fn apply_inverse(&self, force: &VectorN<N, D>) -> Option<VectorN<N, D>> {
+        self.inverse.as_ref().map(|inverse| inverse * force)
+    }
+
+    fn diagonal(&self) -> VectorN<N, D>
+    where
+        D: DimName,
+    {
+        self.matrix.diagonal()
+    }
+}
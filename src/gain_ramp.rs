@@ -0,0 +1,182 @@
+//! Soft-Start Gain Ramping
+//!
+//! Engaging a stiff coupling on a live device at full gain kicks the hand of
+//! the operator if there is an error at that moment. `GainRamp` ramps the gains
+//! of a controller from zero to their targets over a configurable period after
+//! `enable` was called.
+use nalgebra::{convert, ComplexField};
+use num_traits::{One, Zero};
+
+use crate::{Gains, Scheduled};
+
+/// Shape of the gain ramp.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RampProfile {
+    /// The gains grow linearly over the ramp period.
+    #[default]
+    Linear,
+    /// The gains approach their targets exponentially with a time constant of
+    /// a fifth of the ramp period and are set to their targets at the end of
+    /// the period.
+    Exponential,
+}
+
+/// Wraps a controller and ramps its gains up after it is enabled.
+///
+/// ```rust
+/// use haptic_toolbox::{GainRamp, Scheduled, PD};
+/// use nalgebra::U1;
+///
+/// let mut ramp = GainRamp::new(PD::<f64, U1>::new(100.0, 2.0), 0.5);
+/// assert_eq!(ramp.controller().gains(), (0.0, 0.0));
+///
+/// ramp.enable();
+/// ramp.update(0.25);
+/// assert_eq!(ramp.controller().gains(), (50.0, 1.0));
+///
+/// ramp.update(0.25);
+/// assert!(ramp.is_ramped_up());
+/// assert_eq!(ramp.controller().gains(), (100.0, 2.0));
+/// ```
+///
+/// The force of a ramped `PID` follows its gains as well.
+///
+/// ```rust
+/// use haptic_toolbox::{GainRamp, PID};
+/// use nalgebra::{Vector1, U1};
+///
+/// let mut ramp = GainRamp::new(PID::<f64, U1>::new(100.0, 10.0, 0.0), 0.5);
+/// ramp.enable();
+///
+/// let (pos_ref, zero) = (Vector1::new(0.1), Vector1::zeros());
+/// let mut force = Vector1::zeros();
+/// while !ramp.is_ramped_up() {
+///     force = ramp.update(0.01).calculate_force(&pos_ref, &zero, &zero, &zero, 0.0);
+/// }
+/// assert!((force[0] - 10.0f64).abs() < 1e-12);
+/// ```
+pub struct GainRamp<C>
+where
+    C: Scheduled,
+{
+    controller: C,
+    target_gains: C::Gains,
+    period: C::Scalar,
+    profile: RampProfile,
+    elapsed: Option<C::Scalar>,
+}
+
+impl<C> GainRamp<C>
+where
+    C: Scheduled,
+{
+    /// Creates a new disabled gain ramp with the current gains of the
+    /// controller as targets and the ramp `period` in seconds.
+    pub fn new(mut controller: C, period: C::Scalar) -> Self {
+        assert!(
+            period > C::Scalar::zero(),
+            "the ramp period has to be positive"
+        );
+        let target_gains = controller.gains();
+        controller.set_gains(target_gains.scale(C::Scalar::zero()));
+        Self {
+            controller,
+            target_gains,
+            period,
+            profile: RampProfile::default(),
+            elapsed: None,
+        }
+    }
+
+    /// Starts ramping up the gains from zero.
+    pub fn enable(&mut self) {
+        self.elapsed = Some(C::Scalar::zero());
+        self.apply_gains();
+    }
+
+    /// Sets the gains to zero.
+    pub fn disable(&mut self) {
+        self.elapsed = None;
+        self.apply_gains();
+    }
+
+    /// Returns `true` if the ramp was enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.elapsed.is_some()
+    }
+
+    /// Returns `true` if the gains reached their targets.
+    pub fn is_ramped_up(&self) -> bool {
+        self.elapsed.is_some_and(|elapsed| elapsed >= self.period)
+    }
+
+    /// Advances the ramp by `dt` and returns the controller.
+    pub fn update(&mut self, dt: C::Scalar) -> &mut C {
+        if let Some(elapsed) = &mut self.elapsed {
+            *elapsed += dt;
+        }
+        self.apply_gains();
+        &mut self.controller
+    }
+
+    /// Returns the factor the target gains are currently multiplied with.
+    pub fn factor(&self) -> C::Scalar {
+        let elapsed = match self.elapsed {
+            Some(elapsed) => elapsed,
+            None => return C::Scalar::zero(),
+        };
+        if elapsed >= self.period {
+            return C::Scalar::one();
+        }
+        match self.profile {
+            RampProfile::Linear => elapsed / self.period,
+            RampProfile::Exponential => {
+                let five: C::Scalar = convert(5.0);
+                C::Scalar::one() - (-five * elapsed / self.period).exp()
+            }
+        }
+    }
+
+    /// Returns the controller.
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    /// Returns the controller mutably.
+    ///
+    /// The gains of the controller are overwritten by the next update.
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+
+    /// Returns the target gains.
+    pub fn target_gains(&self) -> &C::Gains {
+        &self.target_gains
+    }
+
+    /// Sets the target gains.
+    pub fn set_target_gains(&mut self, target_gains: C::Gains) {
+        self.target_gains = target_gains;
+        self.apply_gains();
+    }
+
+    /// Returns the ramp profile.
+    pub fn profile(&self) -> RampProfile {
+        self.profile
+    }
+
+    /// Sets the ramp profile, defaults to `RampProfile::Linear`.
+    pub fn set_profile(&mut self, profile: RampProfile) {
+        self.profile = profile;
+    }
+
+    /// Returns the ramp period.
+    pub fn period(&self) -> C::Scalar {
+        self.period
+    }
+
+    fn apply_gains(&mut self) {
+        let gains = self.target_gains.scale(self.factor());
+        self.controller.set_gains(gains);
+    }
+}
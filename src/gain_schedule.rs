@@ -0,0 +1,344 @@
+//! Gain Scheduling
+//!
+//! The gains of a controller are interpolated from a lookup table keyed on a
+//! scheduling variable, e.g. the estimated stiffness of the environment or the
+//! penetration depth into a virtual wall. Between two entries of the table the
+//! gains are interpolated linearly, outside of the table the first or last
+//! entry is used. An optional transition time low-pass filters the gains so
+//! that jumps of the scheduling variable do not lead to jumps of the force.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, MatrixMN, RealField,
+};
+use num_traits::Zero;
+
+use crate::{Gain, MatrixGain, VirtualWall, PD, PID};
+
+/// Set of gains that can be interpolated.
+pub trait Gains<N>: Clone
+where
+    N: RealField,
+{
+    /// Interpolates linearly between `self` for `t = 0` and `other` for
+    /// `t = 1`.
+    fn interpolate(&self, other: &Self, t: N) -> Self;
+
+    /// Multiplies all gains with `factor`.
+    fn scale(&self, factor: N) -> Self;
+}
+
+impl Gains<f32> for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    fn scale(&self, factor: f32) -> Self {
+        self * factor
+    }
+}
+
+impl Gains<f64> for f64 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+
+    fn scale(&self, factor: f64) -> Self {
+        self * factor
+    }
+}
+
+impl<N, R, C> Gains<N> for MatrixMN<N, R, C>
+where
+    N: RealField,
+    R: Dim,
+    C: Dim,
+    DefaultAllocator: Allocator<N, R, C>,
+{
+    fn interpolate(&self, other: &Self, t: N) -> Self {
+        self + (other - self) * t
+    }
+
+    fn scale(&self, factor: N) -> Self {
+        self * factor
+    }
+}
+
+impl<N, D> Gains<N> for MatrixGain<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D, D>,
+{
+    fn interpolate(&self, other: &Self, t: N) -> Self {
+        MatrixGain::new(self.matrix().interpolate(other.matrix(), t))
+    }
+
+    fn scale(&self, factor: N) -> Self {
+        MatrixGain::new(self.matrix() * factor)
+    }
+}
+
+impl<N, A, B> Gains<N> for (A, B)
+where
+    N: RealField,
+    A: Gains<N>,
+    B: Gains<N>,
+{
+    fn interpolate(&self, other: &Self, t: N) -> Self {
+        (
+            self.0.interpolate(&other.0, t),
+            self.1.interpolate(&other.1, t),
+        )
+    }
+
+    fn scale(&self, factor: N) -> Self {
+        (self.0.scale(factor), self.1.scale(factor))
+    }
+}
+
+impl<N, A, B, C> Gains<N> for (A, B, C)
+where
+    N: RealField,
+    A: Gains<N>,
+    B: Gains<N>,
+    C: Gains<N>,
+{
+    fn interpolate(&self, other: &Self, t: N) -> Self {
+        (
+            self.0.interpolate(&other.0, t),
+            self.1.interpolate(&other.1, t),
+            self.2.interpolate(&other.2, t),
+        )
+    }
+
+    fn scale(&self, factor: N) -> Self {
+        (
+            self.0.scale(factor),
+            self.1.scale(factor),
+            self.2.scale(factor),
+        )
+    }
+}
+
+/// Controller whose gains can be scheduled.
+pub trait Scheduled {
+    type Scalar: RealField;
+    type Gains: Gains<Self::Scalar>;
+
+    /// Returns the current gains.
+    fn gains(&self) -> Self::Gains;
+
+    /// Sets the gains.
+    fn set_gains(&mut self, gains: Self::Gains);
+
+    /// Sets the gains without a jump of the output, defaults to `set_gains`.
+    fn set_gains_bumpless(&mut self, gains: Self::Gains) {
+        self.set_gains(gains);
+    }
+}
+
+/// The gains are `(k_p, k_d)`.
+impl<N, D, G> Scheduled for PD<N, D, G>
+where
+    N: RealField,
+    D: Dim,
+    G: Gain<N, D> + Gains<N>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    type Scalar = N;
+    type Gains = (G, G);
+
+    fn gains(&self) -> Self::Gains {
+        (self.k_p(), self.k_d())
+    }
+
+    fn set_gains(&mut self, (k_p, k_d): Self::Gains) {
+        self.set_k_p(k_p);
+        self.set_k_d(k_d);
+    }
+}
+
+/// The gains are `(k_p, k_i, k_d)`.
+impl<N, D, G> Scheduled for PID<N, D, G>
+where
+    N: RealField,
+    D: Dim + DimName,
+    G: Gain<N, D> + Gains<N>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    type Scalar = N;
+    type Gains = (G, G, G);
+
+    fn gains(&self) -> Self::Gains {
+        (self.k_p(), self.k_i(), self.k_d())
+    }
+
+    fn set_gains(&mut self, (k_p, k_i, k_d): Self::Gains) {
+        self.set_k_p(k_p);
+        self.set_k_i(k_i);
+        self.set_k_d(k_d);
+    }
+
+    fn set_gains_bumpless(&mut self, (k_p, k_i, k_d): Self::Gains) {
+        PID::set_gains_bumpless(self, k_p, k_i, k_d);
+    }
+}
+
+/// The gains are `(stiffness, damping)`.
+impl<N, D> Scheduled for VirtualWall<N, D>
+where
+    N: RealField + Gains<N>,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    type Scalar = N;
+    type Gains = (N, N);
+
+    fn gains(&self) -> Self::Gains {
+        (self.stiffness(), self.damping())
+    }
+
+    fn set_gains(&mut self, (stiffness, damping): Self::Gains) {
+        self.set_stiffness(stiffness);
+        self.set_damping(damping);
+    }
+}
+
+/// Wraps a controller and schedules its gains.
+///
+/// ```rust
+/// use haptic_toolbox::{GainSchedule, Scheduled, PD};
+/// use nalgebra::U1;
+///
+/// // Stiffer environments get a softer controller.
+/// let table = vec![(100.0, (50.0, 1.0)), (1000.0, (10.0, 2.0))];
+/// let mut schedule = GainSchedule::new(PD::<f64, U1>::new(50.0, 1.0), table);
+///
+/// schedule.update(550.0, 0.001);
+/// assert_eq!(schedule.controller().gains(), (30.0, 1.5));
+///
+/// schedule.update(5000.0, 0.001);
+/// assert_eq!(schedule.controller().gains(), (10.0, 2.0));
+/// ```
+pub struct GainSchedule<C>
+where
+    C: Scheduled,
+{
+    controller: C,
+    table: Vec<(C::Scalar, C::Gains)>,
+    transition_time: C::Scalar,
+    bumpless: bool,
+}
+
+impl<C> GainSchedule<C>
+where
+    C: Scheduled,
+{
+    /// Creates a new gain schedule from a table of scheduling variables and
+    /// gains, sorted by the scheduling variable.
+    pub fn new(controller: C, table: Vec<(C::Scalar, C::Gains)>) -> Self {
+        assert!(!table.is_empty(), "the gain table must not be empty");
+        assert!(
+            table.windows(2).all(|entries| entries[0].0 < entries[1].0),
+            "the gain table has to be sorted by the scheduling variable"
+        );
+        Self {
+            controller,
+            table,
+            transition_time: C::Scalar::zero(),
+            bumpless: false,
+        }
+    }
+
+    /// Returns the gains of the table for the scheduling variable.
+    pub fn scheduled_gains(&self, variable: C::Scalar) -> C::Gains {
+        let upper = self.table.iter().position(|(key, _)| variable < *key);
+        match upper {
+            Some(0) => self.table[0].1.clone(),
+            Some(upper) => {
+                let (key_a, gains_a) = &self.table[upper - 1];
+                let (key_b, gains_b) = &self.table[upper];
+                gains_a.interpolate(gains_b, (variable - *key_a) / (*key_b - *key_a))
+            }
+            None => self.table[self.table.len() - 1].1.clone(),
+        }
+    }
+
+    /// Updates the gains of the controller from the scheduling variable and
+    /// returns the controller.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{GainSchedule, Scheduled, PD};
+    /// use nalgebra::U1;
+    ///
+    /// let table = vec![(0.0, (10.0, 0.0)), (1.0, (20.0, 0.0))];
+    /// let mut schedule = GainSchedule::new(PD::<f64, U1>::new(10.0, 0.0), table);
+    /// schedule.set_transition_time(0.1);
+    ///
+    /// // The gains move smoothly towards the scheduled gains.
+    /// let mut prev_k_p = 10.0;
+    /// for _ in 0..100 {
+    ///     let k_p = schedule.update(1.0, 0.001).k_p();
+    ///     assert!(k_p > prev_k_p && k_p < 20.0);
+    ///     prev_k_p = k_p;
+    /// }
+    /// ```
+    pub fn update(&mut self, variable: C::Scalar, dt: C::Scalar) -> &mut C {
+        let scheduled_gains = self.scheduled_gains(variable);
+        let gains = if self.transition_time > C::Scalar::zero() {
+            let alpha = dt / (self.transition_time + dt);
+            self.controller.gains().interpolate(&scheduled_gains, alpha)
+        } else {
+            scheduled_gains
+        };
+        if self.bumpless {
+            self.controller.set_gains_bumpless(gains);
+        } else {
+            self.controller.set_gains(gains);
+        }
+        &mut self.controller
+    }
+
+    /// Returns the controller.
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    /// Returns the controller mutably.
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+
+    /// Returns the table of scheduling variables and gains.
+    pub fn table(&self) -> &[(C::Scalar, C::Gains)] {
+        &self.table
+    }
+
+    /// Returns the transition time.
+    pub fn transition_time(&self) -> C::Scalar {
+        self.transition_time
+    }
+
+    /// Sets the time constant used to low-pass filter the gains, defaults to
+    /// zero which applies the scheduled gains immediately.
+    pub fn set_transition_time(&mut self, transition_time: C::Scalar) {
+        assert!(
+            transition_time >= C::Scalar::zero(),
+            "the transition time must not be negative"
+        );
+        self.transition_time = transition_time;
+    }
+
+    /// Returns `true` if the gains are switched bumpless.
+    pub fn is_bumpless(&self) -> bool {
+        self.bumpless
+    }
+
+    /// Sets whether the gains are switched with `set_gains_bumpless`, which
+    /// keeps the force of a `PID` continuous by moving the change into its
+    /// integral component, defaults to `false`.
+    pub fn set_bumpless(&mut self, bumpless: bool) {
+        self.bumpless = bumpless;
+    }
+}
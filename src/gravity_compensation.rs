@@ -0,0 +1,158 @@
+//! Gravity Compensation
+//!
+//! Gravity pulls on the links of a device and on the tool it holds, which the
+//! operator would otherwise have to carry and which biases any controller.
+//! The compensation is a feedforward force or torque that cancels the weight
+//! and is added to the controller output, e.g. with `PD::set_feedforward`. A
+//! `Payload` compensates a tool at the end effector in Cartesian space, a
+//! `GravityCompensation` compensates the joints of an arm from its
+//! `Dynamics` model.
+use nalgebra::{
+    allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, UnitQuaternion, Vector3,
+    VectorN,
+};
+use std::marker::PhantomData;
+
+use crate::Dynamics;
+
+/// Tool mounted at the end effector.
+///
+/// ```rust
+/// use nalgebra::{UnitQuaternion, Vector3};
+/// use haptic_toolbox::Payload;
+///
+/// let payload = Payload {
+///     mass: 0.5,
+///     center_of_mass: Vector3::new(0.1, 0.0, 0.0),
+///     gravity: Vector3::new(0.0, 0.0, -9.81),
+/// };
+/// assert_eq!(payload.force(), Vector3::new(0.0, 0.0, 4.905));
+///
+/// // Held sideways the weight twists the end effector, held upright it does not.
+/// let torque = payload.torque(&UnitQuaternion::identity());
+/// assert!((torque - Vector3::new(0.0, -0.4905, 0.0)).norm() < 1e-12);
+/// let upright = UnitQuaternion::from_axis_angle(&Vector3::y_axis(), -std::f64::consts::FRAC_PI_2);
+/// assert!(payload.torque(&upright).norm() < 1e-12);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Payload<N>
+where
+    N: RealField,
+{
+    /// Mass of the tool.
+    pub mass: N,
+    /// Center of mass of the tool in the end effector frame.
+    pub center_of_mass: Vector3<N>,
+    /// Gravitational acceleration in the world frame.
+    pub gravity: Vector3<N>,
+}
+
+impl<N> Payload<N>
+where
+    N: RealField,
+{
+    /// Returns the force in the world frame that cancels the weight.
+    pub fn force(&self) -> Vector3<N> {
+        -&self.gravity * self.mass
+    }
+
+    /// Returns the torque in the world frame around the end effector that
+    /// cancels the weight at the end effector `orientation`.
+    pub fn torque(&self, orientation: &UnitQuaternion<N>) -> Vector3<N> {
+        (orientation * self.center_of_mass).cross(&self.force())
+    }
+}
+
+/// Joint-space gravity compensation of an arm.
+///
+/// ```rust
+/// use nalgebra::{Matrix1, Vector1, U1};
+/// use haptic_toolbox::{Dynamics, GravityCompensation, PD};
+///
+/// /// Link of 1 kg with its center of mass 0.2 m from the joint, hanging down
+/// /// at zero.
+/// struct Link;
+///
+/// impl Dynamics<f64, U1> for Link {
+///     fn mass_matrix(&self, _pos: &Vector1<f64>) -> Matrix1<f64> {
+///         Matrix1::new(0.04)
+///     }
+///
+///     fn coriolis(&self, _pos: &Vector1<f64>, _vel: &Vector1<f64>) -> Vector1<f64> {
+///         Vector1::zeros()
+///     }
+///
+///     fn gravity(&self, pos: &Vector1<f64>) -> Vector1<f64> {
+///         Vector1::new(1.0 * 9.81 * 0.2 * pos[0].sin())
+///     }
+/// }
+///
+/// let mut compensation = GravityCompensation::new(Link);
+/// compensation.set_scale(0.5);
+///
+/// let pos = Vector1::new(std::f64::consts::FRAC_PI_2);
+/// let mut pd = PD::<f64, U1>::new(10.0, 0.0);
+/// pd.set_feedforward(Some(compensation.torque(&pos)));
+/// let torque = pd.calculate_force(&pos, &pos, &Vector1::zeros(), &Vector1::zeros());
+/// assert!((torque[0] - 0.981f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct GravityCompensation<N, D, M>
+where
+    N: RealField,
+    D: Dim,
+    M: Dynamics<N, D>,
+    DefaultAllocator: Allocator<N, D> + Allocator<N, D, D>,
+{
+    dynamics: M,
+    scale: N,
+    _phantom: PhantomData<D>,
+}
+
+impl<N, D, M> GravityCompensation<N, D, M>
+where
+    N: RealField,
+    D: Dim,
+    M: Dynamics<N, D>,
+    DefaultAllocator: Allocator<N, D> + Allocator<N, D, D>,
+{
+    /// Creates a new `GravityCompensation` from the `dynamics` model of the
+    /// arm.
+    pub fn new(dynamics: M) -> Self {
+        Self {
+            dynamics,
+            scale: N::one(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the joint torques that cancel gravity at position `pos`.
+    pub fn torque(&self, pos: &VectorN<N, D>) -> VectorN<N, D> {
+        self.dynamics.gravity(pos) * self.scale
+    }
+
+    /// Returns the dynamics model.
+    pub fn dynamics(&self) -> &M {
+        &self.dynamics
+    }
+
+    /// Returns the dynamics model mutably.
+    pub fn dynamics_mut(&mut self) -> &mut M {
+        &mut self.dynamics
+    }
+
+    /// Returns the scale.
+    pub fn scale(&self) -> N {
+        self.scale
+    }
+
+    /// Sets the fraction of gravity that is compensated, which leaves some
+    /// weight when the model is uncertain, defaults to `1`.
+    pub fn set_scale(&mut self, scale: N) {
+        assert!(
+            scale >= N::zero() && scale <= N::one(),
+            "the scale has to be in [0, 1]"
+        );
+        self.scale = scale;
+    }
+}
@@ -0,0 +1,260 @@
+//! Impedance Controller
+//!
+//! Renders a virtual mass-spring-damper with a mass, damping and stiffness
+//! per axis between a reference and the device, so that the motion error
+//! responds to external forces like `M * a + B * v + K * x = -F`. Without
+//! inertia shaping the virtual mass is rendered on top of the device
+//! inertia. With inertia shaping the measured external force is fed back, so
+//! that the device feels like the virtual mass instead of its own.
+//!
+//! Increasing the stiffness or the mass while the spring is compressed or the
+//! mass is moving injects energy. With an `EnergyTank` the energy the damping
+//! dissipates is stored, and parameter increases are only rendered as far as
+//! the tank can pay for them.
+use nalgebra::{
+    allocator::Allocator, convert, dimension::Dim, DefaultAllocator, RealField, VectorN,
+};
+
+use crate::EnergyTank;
+
+/// Virtual mass-spring-damper controller.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{EnergyTank, Impedance};
+///
+/// let mut impedance =
+///     Impedance::<f64, U1>::new(Vector1::new(0.0), Vector1::new(10.0), Vector1::new(100.0));
+/// let (pos_ref, zeros) = (Vector1::new(0.01), Vector1::zeros());
+/// let force = impedance.calculate_force(&pos_ref, &zeros, &zeros, &zeros, &zeros, 0.001);
+/// assert!((force[0] - 1.0f64).abs() < 1e-12);
+///
+/// // An empty tank cannot pay for stiffening the compressed spring.
+/// impedance.set_energy_tank(Some(EnergyTank::new(0.0, 0.0)));
+/// impedance.set_stiffness(Vector1::new(200.0));
+/// impedance.calculate_force(&pos_ref, &zeros, &zeros, &zeros, &zeros, 0.001);
+/// assert_eq!(impedance.rendered_stiffness(), &Vector1::new(100.0));
+///
+/// // A tank holding more than the additional spring energy of 5 mJ can.
+/// impedance.set_energy_tank(Some(EnergyTank::new(0.006, 0.0)));
+/// let force = impedance.calculate_force(&pos_ref, &zeros, &zeros, &zeros, &zeros, 0.001);
+/// assert!((force[0] - 2.0f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Impedance<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    mass: VectorN<N, D>,
+    damping: VectorN<N, D>,
+    stiffness: VectorN<N, D>,
+    rendered_mass: VectorN<N, D>,
+    rendered_stiffness: VectorN<N, D>,
+    device_mass: Option<VectorN<N, D>>,
+    energy_tank: Option<EnergyTank<N>>,
+    prev_vel_error: Option<VectorN<N, D>>,
+    prev_vel_ref: Option<VectorN<N, D>>,
+}
+
+impl<N, D> Impedance<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `Impedance` controller with a `mass`, `damping` and
+    /// `stiffness` per axis.
+    pub fn new(mass: VectorN<N, D>, damping: VectorN<N, D>, stiffness: VectorN<N, D>) -> Self {
+        assert_non_negative(&mass, "the mass must not be negative");
+        assert_non_negative(&damping, "the damping must not be negative");
+        assert_non_negative(&stiffness, "the stiffness must not be negative");
+        Self {
+            rendered_mass: mass.clone(),
+            rendered_stiffness: stiffness.clone(),
+            mass,
+            damping,
+            stiffness,
+            device_mass: None,
+            energy_tank: None,
+            prev_vel_error: None,
+            prev_vel_ref: None,
+        }
+    }
+
+    /// Calculates the force for the device at position `pos` with velocity
+    /// `vel` following the reference position and velocity.
+    ///
+    /// The `external_force` the operator or the environment applies to the
+    /// device is only used for inertia shaping. The accelerations are zero
+    /// for the first call and for a time step that is not positive.
+    pub fn calculate_force(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        external_force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let pos_error = pos_ref - pos;
+        let vel_error = vel_ref - vel;
+        self.render_parameters(&pos_error, &vel_error);
+
+        let spring_damper = self.rendered_stiffness.component_mul(&pos_error)
+            + self.damping.component_mul(&vel_error);
+        let force = match &self.device_mass {
+            Some(device_mass) => {
+                assert!(
+                    self.rendered_mass.iter().all(|mass| *mass > N::zero()),
+                    "inertia shaping needs a positive mass"
+                );
+                let acc_ref = match &self.prev_vel_ref {
+                    Some(prev_vel_ref) if dt > N::zero() => (vel_ref - prev_vel_ref) / dt,
+                    _ => vel_ref * N::zero(),
+                };
+                device_mass
+                    .component_mul(&(spring_damper + external_force))
+                    .component_div(&self.rendered_mass)
+                    - external_force
+                    + device_mass.component_mul(&acc_ref)
+            }
+            None => {
+                let acc_error = match &self.prev_vel_error {
+                    Some(prev_vel_error) if dt > N::zero() => (&vel_error - prev_vel_error) / dt,
+                    _ => &vel_error * N::zero(),
+                };
+                spring_damper + self.rendered_mass.component_mul(&acc_error)
+            }
+        };
+
+        if let Some(energy_tank) = &mut self.energy_tank {
+            let dissipated = self.damping.dot(&vel_error.component_mul(&vel_error)) * dt;
+            energy_tank.store(dissipated);
+        }
+        self.prev_vel_error = Some(vel_error);
+        self.prev_vel_ref = Some(vel_ref.clone());
+        force
+    }
+
+    fn render_parameters(&mut self, pos_error: &VectorN<N, D>, vel_error: &VectorN<N, D>) {
+        let energy_tank = match &mut self.energy_tank {
+            Some(energy_tank) => energy_tank,
+            None => {
+                self.rendered_stiffness = self.stiffness.clone();
+                self.rendered_mass = self.mass.clone();
+                return;
+            }
+        };
+
+        // The energy of the spring is K * x^2 / 2 and of the mass M * v^2 / 2.
+        let half: N = convert(0.5);
+        let stiffness_change = &self.stiffness - &self.rendered_stiffness;
+        let mass_change = &self.mass - &self.rendered_mass;
+        let energy = (stiffness_change.dot(&pos_error.component_mul(pos_error))
+            + mass_change.dot(&vel_error.component_mul(vel_error)))
+            * half;
+        let valve = if energy <= N::zero() {
+            energy_tank.store(-energy);
+            N::one()
+        } else {
+            let valve = energy_tank.valve(energy);
+            energy_tank.extract(energy * valve);
+            valve
+        };
+        if valve == N::one() {
+            self.rendered_stiffness = self.stiffness.clone();
+            self.rendered_mass = self.mass.clone();
+        } else {
+            self.rendered_stiffness += stiffness_change * valve;
+            self.rendered_mass += mass_change * valve;
+        }
+    }
+
+    /// Resets the acceleration estimates.
+    pub fn reset(&mut self) {
+        self.prev_vel_error = None;
+        self.prev_vel_ref = None;
+    }
+
+    /// Returns the mass.
+    pub fn mass(&self) -> &VectorN<N, D> {
+        &self.mass
+    }
+
+    /// Sets the mass per axis.
+    pub fn set_mass(&mut self, mass: VectorN<N, D>) {
+        assert_non_negative(&mass, "the mass must not be negative");
+        self.mass = mass;
+    }
+
+    /// Returns the mass that is rendered, which lags behind the mass while
+    /// the energy tank cannot pay for an increase.
+    pub fn rendered_mass(&self) -> &VectorN<N, D> {
+        &self.rendered_mass
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> &VectorN<N, D> {
+        &self.damping
+    }
+
+    /// Sets the damping per axis.
+    pub fn set_damping(&mut self, damping: VectorN<N, D>) {
+        assert_non_negative(&damping, "the damping must not be negative");
+        self.damping = damping;
+    }
+
+    /// Returns the stiffness.
+    pub fn stiffness(&self) -> &VectorN<N, D> {
+        &self.stiffness
+    }
+
+    /// Sets the stiffness per axis.
+    pub fn set_stiffness(&mut self, stiffness: VectorN<N, D>) {
+        assert_non_negative(&stiffness, "the stiffness must not be negative");
+        self.stiffness = stiffness;
+    }
+
+    /// Returns the stiffness that is rendered, which lags behind the
+    /// stiffness while the energy tank cannot pay for an increase.
+    pub fn rendered_stiffness(&self) -> &VectorN<N, D> {
+        &self.rendered_stiffness
+    }
+
+    /// Returns the device mass used for inertia shaping.
+    pub fn inertia_shaping(&self) -> Option<&VectorN<N, D>> {
+        self.device_mass.as_ref()
+    }
+
+    /// Sets the mass of the device per axis to shape its inertia into the
+    /// virtual mass, defaults to `None` which renders the virtual mass on top
+    /// of the device inertia.
+    pub fn set_inertia_shaping(&mut self, device_mass: Option<VectorN<N, D>>) {
+        if let Some(device_mass) = &device_mass {
+            assert_non_negative(device_mass, "the device mass must not be negative");
+        }
+        self.device_mass = device_mass;
+    }
+
+    /// Returns the energy tank paying for parameter increases.
+    pub fn energy_tank(&self) -> Option<&EnergyTank<N>> {
+        self.energy_tank.as_ref()
+    }
+
+    /// Sets an energy tank that pays for increases of the stiffness and the
+    /// mass, defaults to `None` which renders every change immediately.
+    pub fn set_energy_tank(&mut self, energy_tank: Option<EnergyTank<N>>) {
+        self.energy_tank = energy_tank;
+    }
+}
+
+fn assert_non_negative<N, D>(vals: &VectorN<N, D>, msg: &str)
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    assert!(vals.iter().all(|val| *val >= N::zero()), "{}", msg);
+}
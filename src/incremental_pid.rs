@@ -0,0 +1,253 @@
+//! Incremental PID Controller
+//!
+//! The velocity form of the PID controller calculates force increments
+//! instead of absolute forces:
+//!
+//! `Δu = k_p (e_k - e_(k-1)) + k_i e_k dt + k_d (ė_k - ė_(k-1))`
+//!
+//! Since there is no explicit integral state, an offset of the actuator is
+//! compensated automatically and clamping the accumulated force is enough to
+//! avoid windup.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+use num_traits::Zero;
+use std::time::Duration;
+
+use crate::{seconds, Clock, Gain, OutputLimits, Sign};
+
+/// Velocity form of the PID controller.
+///
+/// ```rust
+/// use haptic_toolbox::{IncrementalPID, PID};
+/// use nalgebra::{Vector1, U1};
+///
+/// let mut incremental_pid = IncrementalPID::<f64, U1>::new(2.0, 0.5, 0.1);
+/// let mut pid = PID::<f64, U1>::new(2.0, 0.5, 0.1);
+///
+/// for i in 0..100 {
+///     let t = i as f64 * 0.001;
+///     let (pos_ref, pos) = (Vector1::new(t.sin()), Vector1::new(0.5 * t.cos()));
+///     let (vel_ref, vel) = (Vector1::new(t.cos()), Vector1::new(-0.5 * t.sin()));
+///
+///     let force = incremental_pid.calculate_force(&pos_ref, &pos, &vel_ref, &vel, 0.001);
+///     let expected_force = pid.calculate_force(&pos_ref, &pos, &vel_ref, &vel, 0.001);
+///     assert!((force - expected_force).norm() < 1e-9);
+/// }
+/// ```
+pub struct IncrementalPID<N, D, G = N>
+where
+    N: RealField,
+    D: Dim,
+    G: Gain<N, D>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    k_p: G,
+    k_i: G,
+    k_d: G,
+    force_sign: Sign,
+    output_limits: Option<OutputLimits<N, D>>,
+    saturated: bool,
+    clock: Clock,
+
+    prev_error: VectorN<N, D>,
+    prev_d_error: VectorN<N, D>,
+    force: VectorN<N, D>,
+}
+
+impl<N, D, G> IncrementalPID<N, D, G>
+where
+    N: RealField,
+    D: Dim + DimName,
+    G: Gain<N, D>,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new incremental PID controller.
+    pub fn new(k_p: G, k_i: G, k_d: G) -> Self {
+        Self {
+            k_p,
+            k_i,
+            k_d,
+            force_sign: Sign::default(),
+            output_limits: None,
+            saturated: false,
+            clock: Clock::new(),
+            prev_error: Zero::zero(),
+            prev_d_error: Zero::zero(),
+            force: Zero::zero(),
+        }
+    }
+
+    /// Calculates the force increment for tracking reference position and
+    /// velocity.
+    ///
+    /// The increment is not accumulated, so this can be used to drive an
+    /// actuator that takes force increments directly.
+    pub fn calculate_increment(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let error = pos_ref - pos;
+        let d_error = vel_ref - vel;
+        let increment = self.k_p.apply(&(&error - &self.prev_error))
+            + self.k_i.apply(&(&error * dt))
+            + self.k_d.apply(&(&d_error - &self.prev_d_error));
+        self.prev_error = error;
+        self.prev_d_error = d_error;
+        self.force_sign.apply(increment)
+    }
+
+    /// Calculates the force increment and returns the accumulated force.
+    ///
+    /// The accumulated force is clamped to the output limits, which also
+    /// keeps it from winding up.
+    pub fn calculate_force(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let increment = self.calculate_increment(pos_ref, pos, vel_ref, vel, dt);
+        self.force += increment;
+        if let Some(output_limits) = &self.output_limits {
+            let (force, saturated) = output_limits.apply(&self.force);
+            self.force = force;
+            self.saturated = saturated;
+        }
+        self.force.clone()
+    }
+
+    /// Calculates the force like `calculate_force` with the time step given
+    /// as a `Duration`.
+    pub fn calculate_force_duration(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: Duration,
+    ) -> VectorN<N, D> {
+        self.calculate_force(pos_ref, pos, vel_ref, vel, seconds(dt))
+    }
+
+    /// Calculates the force like `calculate_force` with the time since the
+    /// last call measured by the internal clock.
+    ///
+    /// The first call uses a time step of zero.
+    pub fn calculate_force_clocked(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+    ) -> VectorN<N, D> {
+        let dt = self.clock.tick_seconds();
+        self.calculate_force(pos_ref, pos, vel_ref, vel, dt)
+    }
+
+    /// Returns the accumulated force.
+    pub fn force(&self) -> &VectorN<N, D> {
+        &self.force
+    }
+
+    /// Sets the accumulated force, e.g. to take over from another controller
+    /// without a jump.
+    pub fn set_force(&mut self, force: VectorN<N, D>) {
+        self.force = force;
+    }
+
+    /// Resets the internal state as if the controller was just created.
+    pub fn reset(&mut self) {
+        self.prev_error = Zero::zero();
+        self.prev_d_error = Zero::zero();
+        self.force = Zero::zero();
+        self.saturated = false;
+        self.clock.reset();
+    }
+
+    /// Returns the output limits.
+    pub fn output_limits(&self) -> Option<&OutputLimits<N, D>> {
+        self.output_limits.as_ref()
+    }
+
+    /// Sets the limits the accumulated force is clamped to.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{IncrementalPID, OutputLimits};
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = IncrementalPID::<f64, U1>::new(0.0, 10.0, 0.0);
+    /// pid.set_output_limits(Some(OutputLimits::symmetric(Vector1::new(1.0))));
+    ///
+    /// let zero = Vector1::zeros();
+    /// for _ in 0..100 {
+    ///     pid.calculate_force(&Vector1::new(1.0), &zero, &zero, &zero, 0.01);
+    /// }
+    /// assert!(pid.is_saturated());
+    ///
+    /// // The force leaves the saturation as soon as the error changes sign.
+    /// let force = pid.calculate_force(&Vector1::new(-1.0), &zero, &zero, &zero, 0.01);
+    /// assert!(force[0] < 1.0);
+    /// ```
+    pub fn set_output_limits(&mut self, output_limits: Option<OutputLimits<N, D>>) {
+        self.output_limits = output_limits;
+        self.saturated = false;
+    }
+
+    /// Returns `true` if the accumulated force had to be clamped.
+    pub fn is_saturated(&self) -> bool {
+        self.saturated
+    }
+
+    /// Returns k p.
+    pub fn k_p(&self) -> G {
+        self.k_p.clone()
+    }
+
+    /// Returns k i.
+    pub fn k_i(&self) -> G {
+        self.k_i.clone()
+    }
+
+    /// Returns k d.
+    pub fn k_d(&self) -> G {
+        self.k_d.clone()
+    }
+
+    /// Sets k p.
+    ///
+    /// Changing the gains never makes the force jump, since only the
+    /// increments are affected.
+    pub fn set_k_p(&mut self, k_p: G) {
+        self.k_p = k_p;
+    }
+
+    /// Sets k i.
+    pub fn set_k_i(&mut self, k_i: G) {
+        self.k_i = k_i;
+    }
+
+    /// Sets k d.
+    pub fn set_k_d(&mut self, k_d: G) {
+        self.k_d = k_d;
+    }
+
+    /// Returns the sign convention of the returned force.
+    pub fn force_sign(&self) -> Sign {
+        self.force_sign
+    }
+
+    /// Sets the sign convention of the returned force, defaults to
+    /// `Sign::Positive`.
+    pub fn set_force_sign(&mut self, force_sign: Sign) {
+        self.force_sign = force_sign;
+    }
+}
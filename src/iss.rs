@@ -3,12 +3,32 @@
 //! Similar to other passivity apporaches, but less conservative. While most
 //! passivity approaches do not allow a system to generate energy the ISS
 //! approach allows to generate energy that is bounded by a constant. [[1]](https://ieeexplore.ieee.org/abstract/document/7139013)
+//!
+//! The generated energy can be accounted with `observe` and compared against an
+//! explicit energy budget. If the slope of the environment is not known
+//! beforehand, `mu_max` can be adapted online with a `MuMaxEstimator`.
 use nalgebra::{
     allocator::Allocator,
+    convert,
     dimension::{Dim, DimName},
     DefaultAllocator, RealField, VectorN,
 };
 use num_traits::Zero;
+use std::time::Duration;
+
+use crate::{seconds, PassivityObserver, Sign};
+
+/// State of the generated energy relative to the energy budget.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnergyAlarm {
+    /// The generated energy is below the warning level.
+    #[default]
+    Normal,
+    /// The generated energy approaches the energy budget.
+    Warning,
+    /// The generated energy exceeds the energy budget.
+    Exceeded,
+}
 
 pub struct ISS<N, D>
 where
@@ -18,7 +38,13 @@ where
 {
     tau: N,
     mu_max: N,
+    force_sign: Sign,
     prev_force: VectorN<N, D>,
+    correction: VectorN<N, D>,
+    prev_dt: N,
+    observer: PassivityObserver<N>,
+    energy_budget: Option<N>,
+    warning_level: N,
 }
 
 impl<N, D> ISS<N, D>
@@ -35,15 +61,70 @@ where
         Self {
             tau,
             mu_max,
+            force_sign: Sign::default(),
             prev_force: Zero::zero(),
+            correction: Zero::zero(),
+            prev_dt: N::zero(),
+            observer: PassivityObserver::new(),
+            energy_budget: None,
+            warning_level: convert(0.8),
         }
     }
 
     /// Calculate the ISS force.
     pub fn calculate_force(&mut self, force: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
-        let iss_force = force + (force - &self.prev_force) * self.tau / dt;
+        let correction = (force - &self.prev_force) * self.tau / dt;
+        let iss_force = force + &correction;
+        self.correction = self.force_sign.apply(correction);
         self.prev_force = force.clone();
-        iss_force
+        self.prev_dt = dt;
+        self.force_sign.apply(iss_force)
+    }
+
+    /// Calculates the ISS force with the time step given as a `Duration`.
+    pub fn calculate_force_duration(
+        &mut self,
+        force: &VectorN<N, D>,
+        dt: Duration,
+    ) -> VectorN<N, D> {
+        self.calculate_force(force, seconds(dt))
+    }
+
+    /// Returns the correction that was added to the force by the last call to
+    /// `calculate_force`.
+    ///
+    /// The correction has the sign convention of the returned force, so it is
+    /// the difference between the returned force and the force with the sign
+    /// convention applied.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::{Sign, ISS};
+    ///
+    /// let mut iss = ISS::<f64, U1>::new(0.1, 100.0);
+    /// iss.calculate_force(&Vector1::new(1.0), 0.01);
+    ///
+    /// let force = Vector1::new(2.0);
+    /// let passive_force = iss.calculate_force(&force, 0.01);
+    /// assert_eq!(iss.last_correction(), &(passive_force - force));
+    ///
+    /// // The port moving along the correction dissipates its energy.
+    /// let vel = Vector1::new(0.5);
+    /// assert!((iss.last_dissipated_energy(&vel) - 0.05f64).abs() < 1e-12);
+    ///
+    /// iss.set_force_sign(Sign::Negative);
+    /// let force = Vector1::new(3.0);
+    /// let passive_force = iss.calculate_force(&force, 0.01);
+    /// assert_eq!(iss.last_correction(), &(passive_force + force));
+    /// ```
+    pub fn last_correction(&self) -> &VectorN<N, D> {
+        &self.correction
+    }
+
+    /// Returns the energy that is dissipated by the last correction over its
+    /// time step at the port moving with velocity `vel`.
+    pub fn last_dissipated_energy(&self, vel: &VectorN<N, D>) -> N {
+        self.correction.dot(vel) * self.prev_dt
     }
 
     /// Calculates the ISS velocity.
@@ -56,6 +137,90 @@ where
         vel - (force - &self.prev_force) / dt / self.mu_max
     }
 
+    /// Accounts the energy of the applied `force` at the port moving with
+    /// velocity `vel` and returns the alarm state.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::{EnergyAlarm, ISS};
+    ///
+    /// let mut iss = ISS::<f64, U1>::new(0.1, 100.0);
+    /// iss.set_energy_budget(Some(1.0));
+    ///
+    /// let (force, vel) = (Vector1::new(-1.0), Vector1::new(1.0));
+    /// assert_eq!(iss.observe(&force, &vel, 0.5), EnergyAlarm::Normal);
+    /// assert_eq!(iss.observe(&force, &vel, 0.4), EnergyAlarm::Warning);
+    /// assert_eq!(iss.observe(&force, &vel, 0.2), EnergyAlarm::Exceeded);
+    /// assert!((iss.generated_energy() - 1.1).abs() < 1e-12);
+    /// ```
+    pub fn observe(&mut self, force: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> EnergyAlarm {
+        self.observer.observe(force, vel, dt);
+        self.energy_alarm()
+    }
+
+    /// Returns the energy that was generated at the port.
+    pub fn generated_energy(&self) -> N {
+        (-self.observer.energy()).max(N::zero())
+    }
+
+    /// Returns the alarm state of the generated energy.
+    pub fn energy_alarm(&self) -> EnergyAlarm {
+        let energy_budget = match self.energy_budget {
+            Some(energy_budget) => energy_budget,
+            None => return EnergyAlarm::Normal,
+        };
+        let generated_energy = self.generated_energy();
+        if generated_energy > energy_budget {
+            EnergyAlarm::Exceeded
+        } else if generated_energy >= energy_budget * self.warning_level {
+            EnergyAlarm::Warning
+        } else {
+            EnergyAlarm::Normal
+        }
+    }
+
+    /// Returns the passivity observer that accounts the energy at the port.
+    pub fn observer(&self) -> &PassivityObserver<N> {
+        &self.observer
+    }
+
+    /// Resets the accounted energy.
+    pub fn reset_energy(&mut self) {
+        self.observer.reset();
+    }
+
+    /// Returns the energy budget.
+    pub fn energy_budget(&self) -> Option<N> {
+        self.energy_budget
+    }
+
+    /// Sets the energy the port may generate, defaults to `None` for no
+    /// alarms.
+    pub fn set_energy_budget(&mut self, energy_budget: Option<N>) {
+        if let Some(energy_budget) = energy_budget {
+            assert!(
+                energy_budget >= N::zero(),
+                "the energy budget must not be negative"
+            );
+        }
+        self.energy_budget = energy_budget;
+    }
+
+    /// Returns the warning level.
+    pub fn warning_level(&self) -> N {
+        self.warning_level
+    }
+
+    /// Sets the fraction of the energy budget above which
+    /// `EnergyAlarm::Warning` is raised, defaults to 0.8.
+    pub fn set_warning_level(&mut self, warning_level: N) {
+        assert!(
+            warning_level >= N::zero() && warning_level <= N::one(),
+            "the warning level has to be within [0, 1]"
+        );
+        self.warning_level = warning_level;
+    }
+
     /// Returns tau.
     pub fn tau(&self) -> N {
         self.tau
@@ -75,4 +240,183 @@ where
     pub fn set_mu_max(&mut self, mu_max: N) {
         self.mu_max = mu_max;
     }
+
+    /// Returns the sign convention of the returned force.
+    pub fn force_sign(&self) -> Sign {
+        self.force_sign
+    }
+
+    /// Sets the sign convention of the returned force, defaults to
+    /// `Sign::Positive`.
+    pub fn set_force_sign(&mut self, force_sign: Sign) {
+        self.force_sign = force_sign;
+    }
+}
+
+/// Online estimator for `mu_max` of the ISS approach.
+///
+/// The slope of the environment is estimated from consecutive samples as the
+/// ratio of the force change to the position change, which bounds f'(x) from
+/// above. The peak slope decays slowly, so that `mu_max` follows a softer
+/// environment. `mu_max` is the peak slope times a safety margin within safe
+/// limits. It increases immediately, but only decreases once it is off by more
+/// than the hysteresis, which avoids chattering.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{MuMaxEstimator, ISS};
+///
+/// let mut estimator = MuMaxEstimator::<f64, U1>::new(10.0, 1e4);
+/// let mut iss = ISS::<f64, U1>::new(0.1, estimator.mu_max());
+///
+/// // Pushing into an environment with a stiffness of 1000 N/m.
+/// for i in 0..10 {
+///     let pos = Vector1::new(i as f64 * 0.001);
+///     let force = pos * 1000.0;
+///     iss.set_mu_max(estimator.update(&pos, &force, 0.001));
+/// }
+/// assert!((iss.mu_max() - 1500.0).abs() < 1e-6);
+/// ```
+#[derive(Debug)]
+pub struct MuMaxEstimator<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    min_mu_max: N,
+    max_mu_max: N,
+    margin: N,
+    hysteresis: N,
+    decay_rate: N,
+    min_displacement: N,
+    slope: N,
+    mu_max: N,
+    prev: Option<(VectorN<N, D>, VectorN<N, D>)>,
+}
+
+impl<N, D> MuMaxEstimator<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `MuMaxEstimator` that keeps `mu_max` within
+    /// `min_mu_max` and `max_mu_max`, starting at `min_mu_max`.
+    pub fn new(min_mu_max: N, max_mu_max: N) -> Self {
+        assert!(
+            min_mu_max > N::zero() && min_mu_max <= max_mu_max,
+            "the bounds of mu max have to be positive and ordered"
+        );
+        Self {
+            min_mu_max,
+            max_mu_max,
+            margin: convert(1.5),
+            hysteresis: convert(0.2),
+            decay_rate: convert(0.1),
+            min_displacement: convert(1e-6),
+            slope: N::zero(),
+            mu_max: min_mu_max,
+            prev: None,
+        }
+    }
+
+    /// Updates the estimate with the observed position and force and returns
+    /// the adapted `mu_max`.
+    pub fn update(&mut self, pos: &VectorN<N, D>, force: &VectorN<N, D>, dt: N) -> N {
+        self.slope *= (-self.decay_rate * dt).exp();
+        if let Some((prev_pos, prev_force)) = &self.prev {
+            let displacement = (pos - prev_pos).norm();
+            if displacement > self.min_displacement {
+                let slope = (force - prev_force).norm() / displacement;
+                self.slope = self.slope.max(slope);
+            }
+        }
+        self.prev = Some((pos.clone(), force.clone()));
+
+        let target = (self.slope * self.margin)
+            .max(self.min_mu_max)
+            .min(self.max_mu_max);
+        if target > self.mu_max || target < self.mu_max * (N::one() - self.hysteresis) {
+            self.mu_max = target;
+        }
+        self.mu_max
+    }
+
+    /// Returns the adapted `mu_max`.
+    pub fn mu_max(&self) -> N {
+        self.mu_max
+    }
+
+    /// Returns the peak slope of the environment.
+    pub fn slope(&self) -> N {
+        self.slope
+    }
+
+    /// Returns the bounds of mu max.
+    pub fn bounds(&self) -> (N, N) {
+        (self.min_mu_max, self.max_mu_max)
+    }
+
+    /// Sets the bounds of mu max.
+    pub fn set_bounds(&mut self, min_mu_max: N, max_mu_max: N) {
+        assert!(
+            min_mu_max > N::zero() && min_mu_max <= max_mu_max,
+            "the bounds of mu max have to be positive and ordered"
+        );
+        self.min_mu_max = min_mu_max;
+        self.max_mu_max = max_mu_max;
+        self.mu_max = self.mu_max.max(min_mu_max).min(max_mu_max);
+    }
+
+    /// Returns the safety margin.
+    pub fn margin(&self) -> N {
+        self.margin
+    }
+
+    /// Sets the factor the peak slope is multiplied with, defaults to 1.5.
+    pub fn set_margin(&mut self, margin: N) {
+        assert!(margin >= N::one(), "the margin must not be less than one");
+        self.margin = margin;
+    }
+
+    /// Returns the hysteresis.
+    pub fn hysteresis(&self) -> N {
+        self.hysteresis
+    }
+
+    /// Sets the fraction `mu_max` has to be too large before it is decreased,
+    /// defaults to 0.2.
+    pub fn set_hysteresis(&mut self, hysteresis: N) {
+        assert!(
+            hysteresis >= N::zero() && hysteresis < N::one(),
+            "the hysteresis has to be within [0, 1)"
+        );
+        self.hysteresis = hysteresis;
+    }
+
+    /// Returns the decay rate.
+    pub fn decay_rate(&self) -> N {
+        self.decay_rate
+    }
+
+    /// Sets the rate in 1/s the peak slope decays with, defaults to 0.1.
+    pub fn set_decay_rate(&mut self, decay_rate: N) {
+        assert!(
+            decay_rate >= N::zero(),
+            "the decay rate must not be negative"
+        );
+        self.decay_rate = decay_rate;
+    }
+
+    /// Returns the minimum displacement.
+    pub fn min_displacement(&self) -> N {
+        self.min_displacement
+    }
+
+    /// Sets the displacement between samples below which the slope is not
+    /// estimated to avoid amplifying noise, defaults to 1e-6.
+    pub fn set_min_displacement(&mut self, min_displacement: N) {
+        self.min_displacement = min_displacement;
+    }
 }
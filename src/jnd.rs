@@ -0,0 +1,146 @@
+//! Just Noticeable Difference Models
+//!
+//! Psychophysical models that describe how much a stimulus has to change
+//! before the change can be perceived. They are used to compute the size of
+//! the deadband around a previously transmitted value.
+use nalgebra::RealField;
+
+/// Model for the just noticeable difference (JND) of a stimulus.
+pub trait JndModel<N>
+where
+    N: RealField,
+{
+    /// Returns the smallest noticeable change of a stimulus with `magnitude`.
+    fn deadband(&self, magnitude: N) -> N;
+}
+
+impl<N> JndModel<N> for Box<dyn JndModel<N>>
+where
+    N: RealField,
+{
+    fn deadband(&self, magnitude: N) -> N {
+        (**self).deadband(magnitude)
+    }
+}
+
+/// Weber's law, the JND is proportional to the magnitude of the stimulus.
+/// [[1]](https://en.wikipedia.org/wiki/Weber%E2%80%93Fechner_law)
+#[derive(Clone, Copy, Debug)]
+pub struct Weber<N>
+where
+    N: RealField,
+{
+    fraction: N,
+}
+
+impl<N> Weber<N>
+where
+    N: RealField,
+{
+    /// Creates a new Weber model with the Weber `fraction`.
+    pub fn new(fraction: N) -> Self {
+        assert!(fraction >= N::zero(), "cannot assign a negative fraction");
+        Self { fraction }
+    }
+
+    /// Returns the Weber fraction.
+    pub fn fraction(&self) -> N {
+        self.fraction
+    }
+
+    /// Sets the Weber fraction.
+    pub fn set_fraction(&mut self, fraction: N) {
+        assert!(fraction >= N::zero(), "cannot assign a negative fraction");
+        self.fraction = fraction;
+    }
+}
+
+impl<N> JndModel<N> for Weber<N>
+where
+    N: RealField,
+{
+    fn deadband(&self, magnitude: N) -> N {
+        self.fraction * magnitude
+    }
+}
+
+/// Stevens' power law, the perceived intensity is `k * magnitude^exponent`.
+/// [[1]](https://en.wikipedia.org/wiki/Stevens%27s_power_law)
+///
+/// The JND is the change of the stimulus that changes the perceived intensity
+/// by `perceived_jnd`.
+///
+/// ```rust
+/// use haptic_toolbox::{JndModel, Stevens};
+///
+/// let stevens = Stevens::new(1.0, 0.5, 0.1);
+/// let deadband = stevens.deadband(4.0);
+/// assert!(((4.0f64 + deadband).sqrt() - 4.0f64.sqrt() - 0.1).abs() < 1e-12);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Stevens<N>
+where
+    N: RealField,
+{
+    k: N,
+    exponent: N,
+    perceived_jnd: N,
+}
+
+impl<N> Stevens<N>
+where
+    N: RealField,
+{
+    /// Creates a new Stevens model.
+    pub fn new(k: N, exponent: N, perceived_jnd: N) -> Self {
+        assert!(k > N::zero(), "k has to be positive");
+        assert!(exponent > N::zero(), "the exponent has to be positive");
+        Self {
+            k,
+            exponent,
+            perceived_jnd,
+        }
+    }
+
+    /// Returns the scaling constant k.
+    pub fn k(&self) -> N {
+        self.k
+    }
+
+    /// Returns the exponent.
+    pub fn exponent(&self) -> N {
+        self.exponent
+    }
+
+    /// Returns the change in perceived intensity that is just noticeable.
+    pub fn perceived_jnd(&self) -> N {
+        self.perceived_jnd
+    }
+
+    /// Sets the scaling constant k.
+    pub fn set_k(&mut self, k: N) {
+        assert!(k > N::zero(), "k has to be positive");
+        self.k = k;
+    }
+
+    /// Sets the exponent.
+    pub fn set_exponent(&mut self, exponent: N) {
+        assert!(exponent > N::zero(), "the exponent has to be positive");
+        self.exponent = exponent;
+    }
+
+    /// Sets the change in perceived intensity that is just noticeable.
+    pub fn set_perceived_jnd(&mut self, perceived_jnd: N) {
+        self.perceived_jnd = perceived_jnd;
+    }
+}
+
+impl<N> JndModel<N> for Stevens<N>
+where
+    N: RealField,
+{
+    fn deadband(&self, magnitude: N) -> N {
+        let perceived = magnitude.powf(self.exponent) + self.perceived_jnd / self.k;
+        perceived.powf(N::one() / self.exponent) - magnitude
+    }
+}
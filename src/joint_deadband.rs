@@ -0,0 +1,163 @@
+//! Joint Force and Velocity Deadband
+//!
+//! Perceptual coding for teleoperation applies deadbands to the velocity sent
+//! from master to slave and to the force sent back. Instead of deciding for
+//! each signal on its own, the joint detector combines the relative changes of
+//! both signals into one weighted perceptual distance and transmits the pair
+//! once that distance exceeds 1.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+/// Deadband detector for a pair of force and velocity.
+///
+/// ```rust
+/// use nalgebra::Vector3;
+/// use haptic_toolbox::JointDeadbandDetector;
+///
+/// let mut deadband_detector = JointDeadbandDetector::new(
+///     0.1,
+///     0.1,
+///     Vector3::new(1.0, 0.0, 0.0),
+///     Vector3::new(1.0, 0.0, 0.0),
+/// );
+///
+/// // Each signal on its own is within its deadband, but together they are not.
+/// let force = Vector3::new(1.08, 0.0, 0.0);
+/// let vel = Vector3::new(1.08, 0.0, 0.0);
+/// assert!(deadband_detector.is_in_deadband(&force, &Vector3::new(1.0, 0.0, 0.0)));
+/// assert!(!deadband_detector.is_in_deadband(&force, &vel));
+/// ```
+#[derive(Debug)]
+pub struct JointDeadbandDetector<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    prev_force: VectorN<N, D>,
+    prev_vel: VectorN<N, D>,
+    force_threshold: N,
+    vel_threshold: N,
+    force_weight: N,
+    vel_weight: N,
+}
+
+impl<N, D> JointDeadbandDetector<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `JointDeadbandDetector` with relative thresholds for force
+    /// and velocity.
+    pub fn new(
+        force_threshold: N,
+        vel_threshold: N,
+        initial_force: VectorN<N, D>,
+        initial_vel: VectorN<N, D>,
+    ) -> Self {
+        assert!(
+            force_threshold >= N::zero() && vel_threshold >= N::zero(),
+            "cannot assign a negative threshold"
+        );
+        Self {
+            prev_force: initial_force,
+            prev_vel: initial_vel,
+            force_threshold,
+            vel_threshold,
+            force_weight: N::one(),
+            vel_weight: N::one(),
+        }
+    }
+
+    /// Checks if the pair of `force` and `vel` is in the deadband of the
+    /// previously saved pair.
+    pub fn is_in_deadband(&mut self, force: &VectorN<N, D>, vel: &VectorN<N, D>) -> bool {
+        let in_deadband = self.perceptual_distance(force, vel) <= N::one();
+        if !in_deadband {
+            self.prev_force = force.clone();
+            self.prev_vel = vel.clone();
+        }
+        in_deadband
+    }
+
+    /// Returns the weighted perceptual distance of the pair to the previously
+    /// saved pair, values above 1 are noticeable.
+    pub fn perceptual_distance(&self, force: &VectorN<N, D>, vel: &VectorN<N, D>) -> N {
+        let force_change = relative_change(&self.prev_force, force, self.force_threshold);
+        let vel_change = relative_change(&self.prev_vel, vel, self.vel_threshold);
+        (self.force_weight * force_change * force_change
+            + self.vel_weight * vel_change * vel_change)
+            .sqrt()
+    }
+
+    /// Returns the weights of the force and velocity change.
+    pub fn weights(&self) -> (N, N) {
+        (self.force_weight, self.vel_weight)
+    }
+
+    /// Sets the weights of the force and velocity change.
+    pub fn set_weights(&mut self, force_weight: N, vel_weight: N) {
+        self.force_weight = force_weight;
+        self.vel_weight = vel_weight;
+    }
+
+    /// Returns the relative force threshold.
+    pub fn force_threshold(&self) -> N {
+        self.force_threshold
+    }
+
+    /// Returns the relative velocity threshold.
+    pub fn vel_threshold(&self) -> N {
+        self.vel_threshold
+    }
+
+    /// Sets the relative force threshold.
+    pub fn set_force_threshold(&mut self, force_threshold: N) {
+        assert!(
+            force_threshold >= N::zero(),
+            "cannot assign a negative threshold"
+        );
+        self.force_threshold = force_threshold;
+    }
+
+    /// Sets the relative velocity threshold.
+    pub fn set_vel_threshold(&mut self, vel_threshold: N) {
+        assert!(
+            vel_threshold >= N::zero(),
+            "cannot assign a negative threshold"
+        );
+        self.vel_threshold = vel_threshold;
+    }
+
+    /// Returns the last transmitted force.
+    pub fn prev_force(&self) -> &VectorN<N, D> {
+        &self.prev_force
+    }
+
+    /// Returns the last transmitted velocity.
+    pub fn prev_vel(&self) -> &VectorN<N, D> {
+        &self.prev_vel
+    }
+}
+
+/// Change from `prev` to `vals` relative to the deadband around `prev`.
+fn relative_change<N, D>(prev: &VectorN<N, D>, vals: &VectorN<N, D>, threshold: N) -> N
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    let diff = (prev - vals).norm();
+    let deadband = threshold * prev.norm();
+    if diff == N::zero() {
+        N::zero()
+    } else if deadband == N::zero() {
+        N::max_value()
+    } else {
+        diff / deadband
+    }
+}
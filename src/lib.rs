@@ -1,13 +1,291 @@
+#[cfg(feature = "nalgebra")]
+mod admittance;
+#[cfg(feature = "nalgebra")]
+mod autotune;
+#[cfg(feature = "nalgebra")]
+mod bilateral;
+#[cfg(feature = "nalgebra")]
+mod cascade;
+#[cfg(feature = "nalgebra")]
+mod clock;
+#[cfg(feature = "nalgebra")]
+mod computed_torque;
+#[cfg(feature = "nalgebra")]
+mod congestion;
+#[cfg(feature = "nalgebra")]
+mod coordination;
+#[cfg(feature = "nalgebra")]
 mod deadband;
+#[cfg(feature = "nalgebra")]
+mod deadband_evaluation;
+#[cfg(feature = "nalgebra")]
+mod deadband_reconstructor;
+#[cfg(feature = "nalgebra")]
+mod delay_line;
+#[cfg(feature = "nalgebra")]
+mod device_model;
+#[cfg(feature = "nalgebra")]
+mod drift_compensation;
+#[cfg(feature = "nalgebra")]
+mod dynamics;
+#[cfg(feature = "nalgebra")]
+mod eba;
+#[cfg(feature = "nalgebra")]
+mod energy_monitor;
+#[cfg(feature = "nalgebra")]
+mod energy_tank;
+#[cfg(feature = "nalgebra")]
+mod environment_estimation;
+#[cfg(feature = "nalgebra")]
+mod force_prediction;
+#[cfg(feature = "nalgebra")]
+mod four_channel;
+#[cfg(feature = "nalgebra")]
+mod gain;
+#[cfg(feature = "nalgebra")]
+mod gain_ramp;
+#[cfg(feature = "nalgebra")]
+mod gain_schedule;
+#[cfg(feature = "nalgebra")]
+mod gravity_compensation;
+#[cfg(feature = "nalgebra")]
+mod impedance;
+#[cfg(feature = "nalgebra")]
+mod incremental_pid;
+#[cfg(feature = "nalgebra")]
 mod iss;
+#[cfg(feature = "nalgebra")]
+mod jnd;
+#[cfg(feature = "nalgebra")]
+mod joint_deadband;
+#[cfg(feature = "nalgebra")]
+mod lqr;
+#[cfg(feature = "nalgebra")]
+mod mmt;
+#[cfg(feature = "nalgebra")]
+mod mpc;
+#[cfg(feature = "nalgebra")]
+mod multi_channel_deadband;
+#[cfg(feature = "nalgebra")]
+mod multirate;
+#[cfg(feature = "nalgebra")]
+mod orientation_pd;
+#[cfg(feature = "nalgebra")]
+mod output_limits;
+#[cfg(feature = "nalgebra")]
+mod passive_deadband;
+#[cfg(feature = "nalgebra")]
+mod passivity_observer;
+#[cfg(feature = "nalgebra")]
+mod passivity_verification;
+#[cfg(feature = "nalgebra")]
 mod pd;
+#[cfg(feature = "nalgebra")]
 mod pid;
+#[cfg(feature = "nalgebra")]
+mod pipeline;
+#[cfg(feature = "nalgebra")]
+mod predictive_deadband;
+#[cfg(feature = "nalgebra")]
+mod pspm;
+#[cfg(feature = "nalgebra")]
+mod rotation_deadband;
+// The scalar controllers are also built for the tests, which compare them
+// against their `nalgebra` counterparts.
+#[cfg(any(feature = "scalar-only", test))]
+#[cfg_attr(not(feature = "scalar-only"), allow(dead_code))]
+mod scalar;
+#[cfg(feature = "nalgebra")]
+mod scattering;
+#[cfg(feature = "nalgebra")]
+mod session;
+#[cfg(feature = "nalgebra")]
+mod sfa;
+#[cfg(feature = "nalgebra")]
+mod shared_control;
+#[cfg(feature = "nalgebra")]
+mod sign;
+#[cfg(feature = "nalgebra")]
+mod simulation;
+#[cfg(feature = "nalgebra")]
+mod slew_limiter;
+#[cfg(feature = "nalgebra")]
+mod sliding_mode;
+#[cfg(feature = "nalgebra")]
+mod smith_predictor;
+#[cfg(feature = "nalgebra")]
+mod stabilizer;
+#[cfg(feature = "nalgebra")]
+mod tactile_deadband;
+#[cfg(feature = "nalgebra")]
 mod tdpa;
+#[cfg(feature = "nalgebra")]
+mod tdpa_two_port;
+#[cfg(feature = "nalgebra")]
+mod trilateral;
+#[cfg(feature = "nalgebra")]
+mod two_port;
+#[cfg(feature = "nalgebra")]
+mod virtual_coupling;
+#[cfg(feature = "nalgebra")]
+mod virtual_fixture;
+#[cfg(feature = "nalgebra")]
+mod virtual_wall;
+#[cfg(feature = "nalgebra")]
 mod wave;
+#[cfg(feature = "nalgebra")]
+mod wave_impedance;
+#[cfg(feature = "nalgebra")]
+mod wave_predictor;
+#[cfg(feature = "nalgebra")]
+mod z_width;
 
-pub use deadband::DeadbandDetector;
-pub use iss::ISS;
+#[cfg(feature = "nalgebra")]
+pub use admittance::Admittance;
+#[cfg(feature = "nalgebra")]
+pub use autotune::{Plant, RelayAutotuner, TuningRule};
+#[cfg(feature = "nalgebra")]
+pub use bilateral::{ForceMessage, PositionForce, PositionMessage, PositionPosition};
+#[cfg(feature = "nalgebra")]
+pub use cascade::{Cascade, CascadeLoop};
+#[cfg(feature = "nalgebra")]
+pub use clock::{seconds, Clock};
+#[cfg(feature = "nalgebra")]
+pub use computed_torque::{ComputedTorque, TrackingLoop};
+#[cfg(feature = "nalgebra")]
+pub use congestion::{CongestionAdaptiveThreshold, NetworkFeedback};
+#[cfg(feature = "nalgebra")]
+pub use coordination::{Coordination, PairForces};
+#[cfg(feature = "nalgebra")]
+pub use deadband::{
+    DeadbandDecision, DeadbandDetector, DeadbandMode, DeadbandStats, TransmitReason,
+    VelocityMapping,
+};
+#[cfg(feature = "nalgebra")]
+pub use deadband_evaluation::{DeadbandEvaluation, RateDistortionPoint};
+#[cfg(feature = "nalgebra")]
+pub use deadband_reconstructor::{
+    DeadbandReconstructor, PassiveDeadbandReconstructor, ReconstructionStrategy,
+};
+#[cfg(feature = "nalgebra")]
+pub use delay_line::{DelayLine, DelayProfile};
+#[cfg(feature = "nalgebra")]
+pub use device_model::DeviceModel;
+#[cfg(feature = "nalgebra")]
+pub use drift_compensation::DriftCompensator;
+#[cfg(feature = "nalgebra")]
+pub use dynamics::Dynamics;
+#[cfg(feature = "nalgebra")]
+pub use eba::EBA;
+#[cfg(feature = "nalgebra")]
+pub use energy_monitor::{EnergyMonitor, EnergySample};
+#[cfg(feature = "nalgebra")]
+pub use energy_tank::EnergyTank;
+#[cfg(feature = "nalgebra")]
+pub use environment_estimation::{ContactModel, EnvironmentEstimator};
+#[cfg(feature = "nalgebra")]
+pub use force_prediction::ForcePredictor;
+#[cfg(feature = "nalgebra")]
+pub use four_channel::{FourChannel, FourChannelMessage};
+#[cfg(feature = "nalgebra")]
+pub use gain::{Gain, MatrixGain};
+#[cfg(feature = "nalgebra")]
+pub use gain_ramp::{GainRamp, RampProfile};
+#[cfg(feature = "nalgebra")]
+pub use gain_schedule::{GainSchedule, Gains, Scheduled};
+#[cfg(feature = "nalgebra")]
+pub use gravity_compensation::{GravityCompensation, Payload};
+#[cfg(feature = "nalgebra")]
+pub use impedance::Impedance;
+#[cfg(feature = "nalgebra")]
+pub use incremental_pid::IncrementalPID;
+#[cfg(feature = "nalgebra")]
+pub use iss::{EnergyAlarm, MuMaxEstimator, ISS};
+#[cfg(feature = "nalgebra")]
+pub use jnd::{JndModel, Stevens, Weber};
+#[cfg(feature = "nalgebra")]
+pub use joint_deadband::JointDeadbandDetector;
+#[cfg(feature = "nalgebra")]
+pub use lqr::LQR;
+#[cfg(feature = "nalgebra")]
+pub use mmt::{EnvironmentModel, MMT};
+#[cfg(feature = "nalgebra")]
+pub use mpc::MPC;
+#[cfg(feature = "nalgebra")]
+pub use multi_channel_deadband::{ChannelBitmap, MultiChannelDeadband};
+#[cfg(feature = "nalgebra")]
+pub use multirate::{LocalModel, MultirateBridge};
+#[cfg(feature = "nalgebra")]
+pub use orientation_pd::OrientationPD;
+#[cfg(feature = "nalgebra")]
+pub use output_limits::OutputLimits;
+#[cfg(feature = "nalgebra")]
+pub use passive_deadband::PassiveDeadbandTransmitter;
+#[cfg(feature = "nalgebra")]
+pub use passivity_observer::PassivityObserver;
+#[cfg(feature = "nalgebra")]
+pub use passivity_verification::{
+    PassivityReport, PassivityVerification, PassivityViolation, PortTrace,
+};
+#[cfg(feature = "nalgebra")]
 pub use pd::PD;
-pub use pid::PID;
-pub use tdpa::TDPA;
-pub use wave::WAVE;
+#[cfg(feature = "nalgebra")]
+pub use pid::{AntiWindup, DerivativeFilter, Discretization, PID};
+#[cfg(feature = "nalgebra")]
+pub use pipeline::{ControllerStage, Pipeline, SignalProcessor};
+#[cfg(feature = "nalgebra")]
+pub use predictive_deadband::{PredictiveDeadbandDecoder, PredictiveDeadbandEncoder};
+#[cfg(feature = "nalgebra")]
+pub use pspm::PSPM;
+#[cfg(feature = "nalgebra")]
+pub use rotation_deadband::RotationDeadbandDetector;
+#[cfg(feature = "scalar-only")]
+pub use scalar::{ScalarPd, ScalarPid};
+#[cfg(feature = "nalgebra")]
+pub use scattering::ScatteringTransformation;
+#[cfg(feature = "nalgebra")]
+pub use session::{MasterNode, SlaveNode, TeleopSession, TeleopSessionBuilder, Transport};
+#[cfg(feature = "nalgebra")]
+pub use sfa::SFA;
+#[cfg(feature = "nalgebra")]
+pub use shared_control::{Arbitration, SharedControl};
+#[cfg(feature = "nalgebra")]
+pub use sign::Sign;
+#[cfg(feature = "nalgebra")]
+pub use simulation::{Channel, Simulation, SimulationStep};
+#[cfg(feature = "nalgebra")]
+pub use slew_limiter::SlewLimiter;
+#[cfg(feature = "nalgebra")]
+pub use sliding_mode::SlidingMode;
+#[cfg(feature = "nalgebra")]
+pub use smith_predictor::SmithPredictor;
+#[cfg(feature = "nalgebra")]
+pub use stabilizer::{HybridStabilizer, Stabilizer};
+#[cfg(feature = "nalgebra")]
+pub use tactile_deadband::{
+    TactileArrayDeadband, TactileArrayReconstructor, TactileGrouping, TactileUpdate,
+};
+#[cfg(feature = "nalgebra")]
+pub use tdpa::{PassivityControllerType, TDPA};
+#[cfg(feature = "nalgebra")]
+pub use tdpa_two_port::{EnergyMessage, TdpaPort, TdpaTwoPort, TimedEnergyMessage};
+#[cfg(feature = "nalgebra")]
+pub use trilateral::{Trilateral, TrilateralPort};
+#[cfg(feature = "nalgebra")]
+pub use two_port::{DelayChannel, MechanicalImpedance, TwoPort, TwoPortChain, WaveChannel};
+#[cfg(feature = "nalgebra")]
+pub use virtual_coupling::VirtualCoupling;
+#[cfg(feature = "nalgebra")]
+pub use virtual_fixture::{FixtureSet, PathFixture, SphereFixture, VirtualFixture};
+#[cfg(feature = "nalgebra")]
+pub use virtual_wall::VirtualWall;
+#[cfg(feature = "nalgebra")]
+pub use wave::{
+    WaveDecoder, WaveEncoder, WaveEnergyAudit, WaveLossPolicy, WavePacket, WaveVariables, WAVE,
+};
+#[cfg(feature = "nalgebra")]
+pub use wave_impedance::AdaptiveWaveImpedance;
+#[cfg(feature = "nalgebra")]
+pub use wave_predictor::{WavePrediction, WavePredictor};
+#[cfg(feature = "nalgebra")]
+pub use z_width::{ZWidthAnalyzer, ZWidthPoint};
@@ -0,0 +1,127 @@
+//! LQR Controller
+//!
+//! A linear-quadratic regulator (LQR) is the state feedback `u = K * (x_ref -
+//! x)` that minimizes the cost `sum(x' * Q * x + u' * R * u)` of a discrete
+//! linear system `x(k + 1) = A * x(k) + B * u(k)`. The gain follows from the
+//! solution of the discrete algebraic Riccati equation, which is solved by
+//! iterating the Riccati difference equation until it converges.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, MatrixMN, MatrixN, RealField, VectorN,
+};
+
+const MAX_ITERATIONS: usize = 100_000;
+
+/// Discrete linear-quadratic regulator with `S` states and `I` inputs.
+///
+/// ```rust
+/// use nalgebra::{Matrix1, Matrix2, Vector1, Vector2, U1, U2};
+/// use haptic_toolbox::LQR;
+///
+/// // Mass of 1 kg sampled with 10 ms.
+/// let dt = 0.01;
+/// let a = Matrix2::new(1.0, dt, 0.0, 1.0);
+/// let b = Vector2::new(0.5 * dt * dt, dt);
+/// let lqr = LQR::<f64, U2, U1>::new(&a, &b, &Matrix2::identity(), &Matrix1::new(0.01));
+///
+/// let (mut state, state_ref) = (Vector2::new(0.1, 0.0), Vector2::zeros());
+/// for _ in 0..1000 {
+///     let force = lqr.calculate_force(&state, &state_ref);
+///     state = a * state + b * force;
+/// }
+/// assert!(state.norm() < 1e-4);
+/// ```
+#[derive(Clone, Debug)]
+pub struct LQR<N, S, I>
+where
+    N: RealField,
+    S: Dim,
+    I: Dim,
+    DefaultAllocator: Allocator<N, S, S> + Allocator<N, I, S>,
+{
+    gain: MatrixMN<N, I, S>,
+    riccati_solution: MatrixN<N, S>,
+}
+
+impl<N, S, I> LQR<N, S, I>
+where
+    N: RealField,
+    S: Dim + DimName,
+    I: Dim + DimName,
+    DefaultAllocator: Allocator<N, S, S>
+        + Allocator<N, S, I>
+        + Allocator<N, I, S>
+        + Allocator<N, I, I>
+        + Allocator<N, S>
+        + Allocator<N, I>,
+{
+    /// Creates a new `LQR` for the system matrices `a` and `b` with the state
+    /// weights `q` and the input weights `r`.
+    ///
+    /// `q` has to be positive semi-definite, `r` positive definite and the
+    /// system has to be stabilizable.
+    pub fn new(
+        a: &MatrixN<N, S>,
+        b: &MatrixMN<N, S, I>,
+        q: &MatrixN<N, S>,
+        r: &MatrixN<N, I>,
+    ) -> Self {
+        let tolerance = N::default_epsilon().sqrt();
+        let mut p = q.clone();
+        for _ in 0..MAX_ITERATIONS {
+            let gain = Self::solve_gain(a, b, r, &p);
+            let next = q + a.transpose() * &p * (a - b * &gain);
+            let change = (&next - &p).norm();
+            p = next;
+            if change <= tolerance * (N::one() + p.norm()) {
+                return Self {
+                    gain: Self::solve_gain(a, b, r, &p),
+                    riccati_solution: p,
+                };
+            }
+        }
+        panic!("the Riccati equation did not converge");
+    }
+
+    fn solve_gain(
+        a: &MatrixN<N, S>,
+        b: &MatrixMN<N, S, I>,
+        r: &MatrixN<N, I>,
+        p: &MatrixN<N, S>,
+    ) -> MatrixMN<N, I, S> {
+        let b_t_p = b.transpose() * p;
+        (r + &b_t_p * b)
+            .cholesky()
+            .expect("the input weights have to be positive definite")
+            .solve(&(b_t_p * a))
+    }
+}
+
+impl<N, S, I> LQR<N, S, I>
+where
+    N: RealField,
+    S: Dim,
+    I: Dim,
+    DefaultAllocator: Allocator<N, S, S> + Allocator<N, I, S> + Allocator<N, S> + Allocator<N, I>,
+{
+    /// Calculates the force for driving `state` to `state_ref`.
+    pub fn calculate_force(
+        &self,
+        state: &VectorN<N, S>,
+        state_ref: &VectorN<N, S>,
+    ) -> VectorN<N, I> {
+        &self.gain * (state_ref - state)
+    }
+
+    /// Returns the feedback gain `K`.
+    pub fn gain(&self) -> &MatrixMN<N, I, S> {
+        &self.gain
+    }
+
+    /// Returns the solution `P` of the Riccati equation, with which
+    /// `x' * P * x` is the cost to go from state `x`.
+    pub fn riccati_solution(&self) -> &MatrixN<N, S> {
+        &self.riccati_solution
+    }
+}
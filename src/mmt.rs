@@ -0,0 +1,231 @@
+//! Model-Mediated Teleoperation (MMT)
+//!
+//! Instead of the delayed force the master renders a local model of the
+//! environment at the full rate. The slave estimates the model, e.g. a plane
+//! with its stiffness and damping, and only sends it when it changes. The
+//! master is stable for any delay as long as the model is passive, since no
+//! force is closed over the channel. A new model is blended in over a blend
+//! time, so that updates do not cause force jumps.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+/// Plane with a stiffness and a damping, which models the contact with the
+/// environment.
+///
+/// The force only pushes back while the device penetrates the plane
+/// `normal * x = offset`, a model without stiffness and damping is free
+/// space.
+#[derive(Clone, Debug, PartialEq)]
+pub struct EnvironmentModel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Normal of the plane that points into free space.
+    pub normal: VectorN<N, D>,
+    /// Offset of the plane along the normal.
+    pub offset: N,
+    /// Stiffness of the environment.
+    pub stiffness: N,
+    /// Damping of the environment.
+    pub damping: N,
+}
+
+impl<N, D> EnvironmentModel<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a model of free space.
+    pub fn free_space() -> Self {
+        Self {
+            normal: VectorN::zeros(),
+            offset: N::zero(),
+            stiffness: N::zero(),
+            damping: N::zero(),
+        }
+    }
+}
+
+impl<N, D> EnvironmentModel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new plane `normal * x = offset`, the `normal` gets
+    /// normalized and cannot be zero.
+    pub fn plane(normal: VectorN<N, D>, offset: N, stiffness: N, damping: N) -> Self {
+        Self {
+            normal: normal
+                .try_normalize(N::default_epsilon())
+                .expect("the normal cannot be zero"),
+            offset,
+            stiffness,
+            damping,
+        }
+    }
+
+    /// Returns whether the model is free space.
+    pub fn is_free_space(&self) -> bool {
+        self.stiffness == N::zero() && self.damping == N::zero()
+    }
+
+    /// Calculates the force of the model at position `pos` with velocity
+    /// `vel`.
+    pub fn force(&self, pos: &VectorN<N, D>, vel: &VectorN<N, D>) -> VectorN<N, D> {
+        let penetration = self.offset - self.normal.dot(pos);
+        if self.is_free_space() || penetration <= N::zero() {
+            return pos.map(|_| N::zero());
+        }
+        let magnitude = self.stiffness * penetration - self.damping * self.normal.dot(vel);
+        &self.normal * magnitude.max(N::zero())
+    }
+
+    /// Interpolates the parameters towards `other` by `alpha` between `0`
+    /// and `1`. The plane of free space is taken from the other model.
+    pub fn interpolate(&self, other: &Self, alpha: N) -> Self {
+        let (normal, offset) = if self.is_free_space() {
+            (other.normal.clone(), other.offset)
+        } else if other.is_free_space() {
+            (self.normal.clone(), self.offset)
+        } else {
+            let normal = self.normal.lerp(&other.normal, alpha);
+            match normal.try_normalize(N::default_epsilon()) {
+                Some(normal) => (normal, lerp(self.offset, other.offset, alpha)),
+                None => (other.normal.clone(), other.offset),
+            }
+        };
+        Self {
+            normal,
+            offset,
+            stiffness: lerp(self.stiffness, other.stiffness, alpha),
+            damping: lerp(self.damping, other.damping, alpha),
+        }
+    }
+}
+
+fn lerp<N: RealField>(from: N, to: N, alpha: N) -> N {
+    from + (to - from) * alpha
+}
+
+/// Renders a local environment model at the master.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{EnvironmentModel, MMT};
+///
+/// let mut mmt = MMT::new(EnvironmentModel::free_space(), 0.01);
+/// let (pos, vel) = (Vector1::new(-0.01), Vector1::new(0.0));
+/// assert_eq!(mmt.calculate_force(&pos, &vel, 0.001), Vector1::new(0.0));
+///
+/// // The slave detects a wall at 0, which is blended in without a jump.
+/// mmt.update_model(EnvironmentModel::plane(Vector1::new(1.0), 0.0, 1000.0, 0.0));
+/// let force = mmt.calculate_force(&pos, &vel, 0.001);
+/// assert!(force[0] > 0.0 && force[0] < 10.0);
+/// for _ in 0..10 {
+///     mmt.calculate_force(&pos, &vel, 0.001);
+/// }
+/// assert!(!mmt.is_blending());
+/// assert!((mmt.calculate_force(&pos, &vel, 0.001)[0] - 10.0f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MMT<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    model: EnvironmentModel<N, D>,
+    previous: Option<EnvironmentModel<N, D>>,
+    blend: N,
+    blend_time: N,
+}
+
+impl<N, D> MMT<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `MMT` rendering `model`, which blends in updates over
+    /// `blend_time` in seconds.
+    pub fn new(model: EnvironmentModel<N, D>, blend_time: N) -> Self {
+        assert!(
+            blend_time >= N::zero(),
+            "the blend time must not be negative"
+        );
+        Self {
+            model,
+            previous: None,
+            blend: N::one(),
+            blend_time,
+        }
+    }
+
+    /// Updates the model, which is blended in from the rendered model.
+    pub fn update_model(&mut self, model: EnvironmentModel<N, D>) {
+        self.previous = Some(self.rendered_model());
+        self.model = model;
+        self.blend = N::zero();
+    }
+
+    /// Calculates the force rendered at position `pos` with velocity `vel`.
+    pub fn calculate_force(
+        &mut self,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        if self.previous.is_some() {
+            self.blend = if self.blend_time > N::zero() {
+                (self.blend + dt / self.blend_time).min(N::one())
+            } else {
+                N::one()
+            };
+        }
+        let force = self.rendered_model().force(pos, vel);
+        if self.blend >= N::one() {
+            self.previous = None;
+        }
+        force
+    }
+
+    /// Returns the model that is rendered, which is interpolated while an
+    /// update is blended in.
+    pub fn rendered_model(&self) -> EnvironmentModel<N, D> {
+        match &self.previous {
+            Some(previous) => previous.interpolate(&self.model, self.blend),
+            None => self.model.clone(),
+        }
+    }
+
+    /// Returns the latest model.
+    pub fn model(&self) -> &EnvironmentModel<N, D> {
+        &self.model
+    }
+
+    /// Returns whether an update is still blended in.
+    pub fn is_blending(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    /// Returns the blend time.
+    pub fn blend_time(&self) -> N {
+        self.blend_time
+    }
+
+    /// Sets the time in seconds an update is blended in over.
+    pub fn set_blend_time(&mut self, blend_time: N) {
+        assert!(
+            blend_time >= N::zero(),
+            "the blend time must not be negative"
+        );
+        self.blend_time = blend_time;
+    }
+}
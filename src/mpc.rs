@@ -0,0 +1,285 @@
+//! Model Predictive Controller
+//!
+//! A model predictive controller (MPC) predicts the states of a discrete
+//! linear system `x(k + 1) = A * x(k) + B * u(k)` over a short horizon and
+//! chooses the forces that minimize `sum(e' * Q * e + u' * R * u)` of the
+//! tracking error `e` subject to limits of the forces and the states, e.g.
+//! the velocity of an admittance-type slave. Only the first of the forces is
+//! applied and the problem is solved anew in every step.
+//!
+//! The problem is condensed into a dense quadratic program in the forces.
+//! Its unconstrained solution is exact, and the limits are enforced by a
+//! fixed number of sweeps of Hildreth's coordinate descent on the dual
+//! problem, warm-started with the previous multipliers. All matrices are
+//! factorized once, so a step only costs a few matrix-vector products and
+//! fits a 1 kHz loop for systems with a few states and a horizon of a few
+//! dozen steps. The force limits always hold, the state limits hold up to
+//! the accuracy the sweeps reach.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName, Dynamic},
+    linalg::Cholesky,
+    DMatrix, DVector, DefaultAllocator, MatrixMN, MatrixN, RealField, VectorN,
+};
+
+use crate::OutputLimits;
+
+/// Model predictive controller for linear systems with `S` states and `I`
+/// inputs.
+///
+/// ```rust
+/// use nalgebra::{Matrix1, Matrix2, Vector1, Vector2, U1, U2};
+/// use haptic_toolbox::{OutputLimits, MPC};
+///
+/// // Mass of 1 kg sampled with 10 ms.
+/// let dt = 0.01;
+/// let a = Matrix2::new(1.0, dt, 0.0, 1.0);
+/// let b = Vector2::new(0.5 * dt * dt, dt);
+/// let q = Matrix2::new(100.0, 0.0, 0.0, 1.0);
+/// let mut mpc = MPC::<f64, U2, U1>::new(&a, &b, &q, &Matrix1::new(0.001), 20);
+/// mpc.set_force_limits(Some(OutputLimits::symmetric(Vector1::new(2.0))));
+/// mpc.set_state_limits(Some(OutputLimits::symmetric(Vector2::new(f64::INFINITY, 0.1))));
+///
+/// let (mut state, state_ref) = (Vector2::zeros(), Vector2::new(0.2, 0.0));
+/// for _ in 0..500 {
+///     let force = mpc.calculate_force(&state, &state_ref);
+///     assert!(force[0].abs() <= 2.0);
+///     state = a * state + b * force;
+///     assert!(state[1] < 0.101);
+/// }
+/// assert!((state - state_ref).norm() < 1e-3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MPC<N, S, I>
+where
+    N: RealField,
+    S: Dim,
+    I: Dim,
+    DefaultAllocator: Allocator<N, S> + Allocator<N, I>,
+{
+    horizon: usize,
+    phi: DMatrix<N>,
+    gamma: DMatrix<N>,
+    gradient_map: DMatrix<N>,
+    factor: Cholesky<N, Dynamic>,
+    constraints: DMatrix<N>,
+    dual_map: DMatrix<N>,
+    dual_hessian: DMatrix<N>,
+    multipliers: DVector<N>,
+    force_limits: Option<OutputLimits<N, I>>,
+    state_limits: Option<OutputLimits<N, S>>,
+    iterations: usize,
+}
+
+impl<N, S, I> MPC<N, S, I>
+where
+    N: RealField,
+    S: Dim + DimName,
+    I: Dim + DimName,
+    DefaultAllocator: Allocator<N, S, S>
+        + Allocator<N, S, I>
+        + Allocator<N, I, I>
+        + Allocator<N, S>
+        + Allocator<N, I>,
+{
+    /// Creates a new `MPC` for the system matrices `a` and `b` with the state
+    /// weights `q`, the input weights `r` and a prediction `horizon` in
+    /// steps.
+    ///
+    /// `q` has to be positive semi-definite and `r` positive definite.
+    pub fn new(
+        a: &MatrixN<N, S>,
+        b: &MatrixMN<N, S, I>,
+        q: &MatrixN<N, S>,
+        r: &MatrixN<N, I>,
+        horizon: usize,
+    ) -> Self {
+        assert!(horizon > 0, "the horizon needs a step");
+        let (states, inputs) = (S::dim(), I::dim());
+
+        // x(k + 1) = phi(k) * x(0) + sum(gamma(k, j) * u(j))
+        let mut phi = DMatrix::zeros(horizon * states, states);
+        let mut gamma = DMatrix::zeros(horizon * states, horizon * inputs);
+        let mut power = a.clone();
+        let mut power_b = vec![b.clone()];
+        for k in 0..horizon {
+            phi.slice_mut((k * states, 0), (states, states))
+                .copy_from(&power);
+            for j in 0..=k {
+                gamma
+                    .slice_mut((k * states, j * inputs), (states, inputs))
+                    .copy_from(&power_b[k - j]);
+            }
+            power_b.push(a * &power_b[k]);
+            power = a * power;
+        }
+
+        let mut weighted_gamma = gamma.clone();
+        let mut hessian = DMatrix::zeros(horizon * inputs, horizon * inputs);
+        for k in 0..horizon {
+            let weighted = q * gamma.rows(k * states, states);
+            weighted_gamma
+                .rows_mut(k * states, states)
+                .copy_from(&weighted);
+            hessian
+                .slice_mut((k * inputs, k * inputs), (inputs, inputs))
+                .copy_from(r);
+        }
+        let gradient_map = weighted_gamma.transpose();
+        hessian += &gradient_map * &gamma;
+
+        let mut mpc = Self {
+            horizon,
+            phi,
+            gamma,
+            gradient_map,
+            factor: hessian
+                .cholesky()
+                .expect("the input weights have to be positive definite"),
+            constraints: DMatrix::zeros(0, horizon * inputs),
+            dual_map: DMatrix::zeros(horizon * inputs, 0),
+            dual_hessian: DMatrix::zeros(0, 0),
+            multipliers: DVector::zeros(0),
+            force_limits: None,
+            state_limits: None,
+            iterations: 50,
+        };
+        mpc.update_constraints();
+        mpc
+    }
+
+    fn update_constraints(&mut self) {
+        let inputs = self.horizon * I::dim();
+        let mut rows = Vec::new();
+        if self.force_limits.is_some() {
+            rows.push(DMatrix::identity(inputs, inputs));
+        }
+        if self.state_limits.is_some() {
+            rows.push(self.gamma.clone());
+        }
+        let count = rows.iter().map(|rows| rows.nrows()).sum();
+        let mut constraints = DMatrix::zeros(count, inputs);
+        let mut offset = 0;
+        for rows in rows {
+            constraints.rows_mut(offset, rows.nrows()).copy_from(&rows);
+            offset += rows.nrows();
+        }
+
+        self.dual_map = self.factor.solve(&constraints.transpose());
+        self.dual_hessian = &constraints * &self.dual_map;
+        self.multipliers = DVector::zeros(count);
+        self.constraints = constraints;
+    }
+
+    /// Calculates the force for driving `state` to `state_ref`.
+    pub fn calculate_force(
+        &mut self,
+        state: &VectorN<N, S>,
+        state_ref: &VectorN<N, S>,
+    ) -> VectorN<N, I> {
+        let states = S::dim();
+        let free = &self.phi * DVector::from_column_slice(state.as_slice());
+        let mut error = free.clone();
+        for k in 0..self.horizon {
+            for i in 0..states {
+                error[k * states + i] -= state_ref[i];
+            }
+        }
+        let unconstrained = -self.factor.solve(&(&self.gradient_map * error));
+
+        let (lower, upper) = self.bounds(&free);
+        let mut constrained =
+            &self.constraints * &unconstrained - &self.dual_hessian * &self.multipliers;
+        for _ in 0..self.iterations {
+            for i in 0..self.multipliers.len() {
+                let curvature = self.dual_hessian[(i, i)];
+                if curvature <= N::zero() {
+                    continue;
+                }
+                let multiplier = self.multipliers[i];
+                let upper_active = multiplier + (constrained[i] - upper[i]) / curvature;
+                let lower_active = multiplier + (constrained[i] - lower[i]) / curvature;
+                let next = if upper_active > N::zero() {
+                    upper_active
+                } else if lower_active < N::zero() {
+                    lower_active
+                } else {
+                    N::zero()
+                };
+                if next != multiplier {
+                    constrained -= self.dual_hessian.column(i) * (next - multiplier);
+                    self.multipliers[i] = next;
+                }
+            }
+        }
+
+        let forces = unconstrained - &self.dual_map * &self.multipliers;
+        let force = VectorN::<N, I>::from_fn(|i, _| forces[i]);
+        match &self.force_limits {
+            Some(force_limits) => force_limits.apply(&force).0,
+            None => force,
+        }
+    }
+
+    fn bounds(&self, free: &DVector<N>) -> (DVector<N>, DVector<N>) {
+        let (states, inputs) = (S::dim(), I::dim());
+        let mut lower = Vec::with_capacity(self.constraints.nrows());
+        let mut upper = Vec::with_capacity(self.constraints.nrows());
+        if let Some(force_limits) = &self.force_limits {
+            for _ in 0..self.horizon {
+                for i in 0..inputs {
+                    lower.push(force_limits.min()[i]);
+                    upper.push(force_limits.max()[i]);
+                }
+            }
+        }
+        if let Some(state_limits) = &self.state_limits {
+            for k in 0..self.horizon {
+                for i in 0..states {
+                    lower.push(state_limits.min()[i] - free[k * states + i]);
+                    upper.push(state_limits.max()[i] - free[k * states + i]);
+                }
+            }
+        }
+        (DVector::from_vec(lower), DVector::from_vec(upper))
+    }
+
+    /// Returns the force limits.
+    pub fn force_limits(&self) -> Option<&OutputLimits<N, I>> {
+        self.force_limits.as_ref()
+    }
+
+    /// Sets the limits of the forces over the horizon, defaults to `None`.
+    pub fn set_force_limits(&mut self, force_limits: Option<OutputLimits<N, I>>) {
+        self.force_limits = force_limits;
+        self.update_constraints();
+    }
+
+    /// Returns the state limits.
+    pub fn state_limits(&self) -> Option<&OutputLimits<N, S>> {
+        self.state_limits.as_ref()
+    }
+
+    /// Sets the limits of the predicted states, e.g. of the velocities with
+    /// infinite limits for the positions, defaults to `None`.
+    pub fn set_state_limits(&mut self, state_limits: Option<OutputLimits<N, S>>) {
+        self.state_limits = state_limits;
+        self.update_constraints();
+    }
+
+    /// Returns the number of sweeps per step.
+    pub fn iterations(&self) -> usize {
+        self.iterations
+    }
+
+    /// Sets the number of sweeps over the limits per step, defaults to `50`.
+    pub fn set_iterations(&mut self, iterations: usize) {
+        assert!(iterations > 0, "the iterations cannot be zero");
+        self.iterations = iterations;
+    }
+
+    /// Returns the prediction horizon in steps.
+    pub fn horizon(&self) -> usize {
+        self.horizon
+    }
+}
@@ -0,0 +1,189 @@
+//! Multi-Channel Deadband
+//!
+//! Manages the deadband detectors of several channels that share one link,
+//! e.g. two arms and a gripper. Every tick all channels are checked at once
+//! and the result is packed into a bitmap that can be sent in front of the
+//! payload of the channels that changed.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{DeadbandDetector, JndModel, Weber};
+
+/// Bitmap with one bit per channel, set if the channel has to be transmitted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChannelBitmap {
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl ChannelBitmap {
+    /// Creates a new bitmap for `len` channels with no bit set.
+    pub fn new(len: usize) -> Self {
+        Self {
+            bytes: vec![0; len.div_ceil(8)],
+            len,
+        }
+    }
+
+    /// Returns `true` if the bit of `channel` is set.
+    pub fn is_set(&self, channel: usize) -> bool {
+        assert!(channel < self.len, "channel out of range");
+        self.bytes[channel / 8] & (1 << (channel % 8)) != 0
+    }
+
+    /// Sets the bit of `channel`.
+    pub fn set(&mut self, channel: usize) {
+        assert!(channel < self.len, "channel out of range");
+        self.bytes[channel / 8] |= 1 << (channel % 8);
+    }
+
+    /// Returns the number of channels.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the bitmap has no channels.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the number of set bits.
+    pub fn count(&self) -> usize {
+        self.bytes
+            .iter()
+            .map(|byte| byte.count_ones() as usize)
+            .sum()
+    }
+
+    /// Returns the packed bitmap, channel `i` is bit `i % 8` of byte `i / 8`.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+}
+
+/// Container for the named deadband detectors of several channels.
+///
+/// Heterogeneous JND models can be mixed by using `Box<dyn JndModel<N>>` as
+/// model.
+///
+/// ```rust
+/// use nalgebra::Vector3;
+/// use haptic_toolbox::{DeadbandDetector, MultiChannelDeadband};
+///
+/// let mut multi_channel = MultiChannelDeadband::new();
+/// multi_channel.add_channel("left", DeadbandDetector::new(0.1, Vector3::repeat(1.0)));
+/// multi_channel.add_channel("right", DeadbandDetector::new(0.1, Vector3::repeat(1.0)));
+/// multi_channel.add_channel("gripper", DeadbandDetector::new(0.01, Vector3::repeat(1.0)));
+///
+/// let bitmap = multi_channel.check(&[
+///     Vector3::repeat(1.05),
+///     Vector3::repeat(1.5),
+///     Vector3::repeat(1.05),
+/// ]);
+/// assert!(!bitmap.is_set(0));
+/// assert!(bitmap.is_set(1));
+/// assert!(bitmap.is_set(2));
+/// assert_eq!(bitmap.as_bytes(), &[0b110]);
+/// assert_eq!(multi_channel.channel_index("gripper"), Some(2));
+/// ```
+#[derive(Debug)]
+pub struct MultiChannelDeadband<N, D, M = Weber<N>>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    M: JndModel<N>,
+{
+    names: Vec<String>,
+    detectors: Vec<DeadbandDetector<N, D, M>>,
+}
+
+impl<N, D, M> Default for MultiChannelDeadband<N, D, M>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    M: JndModel<N>,
+{
+    fn default() -> Self {
+        Self {
+            names: Vec::new(),
+            detectors: Vec::new(),
+        }
+    }
+}
+
+impl<N, D, M> MultiChannelDeadband<N, D, M>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+    M: JndModel<N>,
+{
+    /// Creates a new `MultiChannelDeadband` without channels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a channel and returns its index.
+    pub fn add_channel(&mut self, name: &str, detector: DeadbandDetector<N, D, M>) -> usize {
+        assert!(
+            self.channel_index(name).is_none(),
+            "channel names have to be unique"
+        );
+        self.names.push(name.to_owned());
+        self.detectors.push(detector);
+        self.detectors.len() - 1
+    }
+
+    /// Checks the `samples` of all channels, given in the order the channels
+    /// were added, and returns which of them have to be transmitted.
+    pub fn check(&mut self, samples: &[VectorN<N, D>]) -> ChannelBitmap {
+        assert_eq!(
+            samples.len(),
+            self.detectors.len(),
+            "one sample per channel required"
+        );
+        let mut bitmap = ChannelBitmap::new(self.detectors.len());
+        for (channel, (detector, vals)) in self.detectors.iter_mut().zip(samples).enumerate() {
+            if !detector.is_in_deadband(vals) {
+                bitmap.set(channel);
+            }
+        }
+        bitmap
+    }
+
+    /// Returns the index of the channel with `name`.
+    pub fn channel_index(&self, name: &str) -> Option<usize> {
+        self.names.iter().position(|n| n == name)
+    }
+
+    /// Returns the detector of the channel with `name`.
+    pub fn channel(&self, name: &str) -> Option<&DeadbandDetector<N, D, M>> {
+        self.channel_index(name).map(|i| &self.detectors[i])
+    }
+
+    /// Returns the detector of the channel with `name` mutably.
+    pub fn channel_mut(&mut self, name: &str) -> Option<&mut DeadbandDetector<N, D, M>> {
+        self.channel_index(name)
+            .map(move |i| &mut self.detectors[i])
+    }
+
+    /// Returns the names of the channels in order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Returns the number of channels.
+    pub fn len(&self) -> usize {
+        self.detectors.len()
+    }
+
+    /// Returns `true` if there are no channels.
+    pub fn is_empty(&self) -> bool {
+        self.detectors.is_empty()
+    }
+}
@@ -0,0 +1,168 @@
+//! Multirate Bridge
+//!
+//! Haptic devices run at about 1 kHz, while remote or simulated environments
+//! often only update at 100 Hz or slower. The bridge renders a local
+//! representation of the environment at the device rate between the slow
+//! updates: the force at an anchor position and the stiffness around it. A
+//! new representation is interpolated in over the measured update interval,
+//! so that the force does not step with the slow rate. The energy the
+//! interpolation outputs on top of the new representation is observed and
+//! dissipated with a damping, so the bridge adds no energy to the loop.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+use crate::PassivityObserver;
+
+/// Local representation of the environment around an anchor position.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LocalModel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Position the environment calculated the force at.
+    pub anchor: VectorN<N, D>,
+    /// Force of the environment at the anchor.
+    pub force: VectorN<N, D>,
+    /// Stiffness of the environment around the anchor.
+    pub stiffness: N,
+}
+
+impl<N, D> LocalModel<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns the force at position `pos`.
+    pub fn force_at(&self, pos: &VectorN<N, D>) -> VectorN<N, D> {
+        &self.force - (pos - &self.anchor) * self.stiffness
+    }
+}
+
+/// Renders a slowly updated environment at the device rate.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{LocalModel, MultirateBridge};
+///
+/// let model = |anchor: f64, force: f64| LocalModel {
+///     anchor: Vector1::new(anchor),
+///     force: Vector1::new(force),
+///     stiffness: 100.0,
+/// };
+/// let mut bridge = MultirateBridge::new(model(0.0, 0.0));
+/// bridge.update(model(0.0, 0.0), 0.0);
+///
+/// // Between the updates the stiffness responds at the device rate.
+/// let (pos, vel) = (Vector1::new(0.01), Vector1::new(0.0));
+/// assert_eq!(bridge.calculate_force(&pos, &vel, 0.001), Vector1::new(-1.0));
+///
+/// // The next update 10 ms later is interpolated in over 10 ms.
+/// bridge.update(model(0.01, -2.0), 0.01);
+/// let force = bridge.calculate_force(&pos, &vel, 0.001);
+/// assert!((force[0] + 1.1f64).abs() < 1e-12);
+/// for _ in 0..9 {
+///     bridge.calculate_force(&pos, &vel, 0.001);
+/// }
+/// assert!((bridge.calculate_force(&pos, &vel, 0.001)[0] + 2.0f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultirateBridge<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    model: LocalModel<N, D>,
+    previous: Option<LocalModel<N, D>>,
+    blend: N,
+    interval: N,
+    last_update_time: Option<N>,
+    observer: PassivityObserver<N>,
+}
+
+impl<N, D> MultirateBridge<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `MultirateBridge` rendering `model`.
+    pub fn new(model: LocalModel<N, D>) -> Self {
+        Self {
+            model,
+            previous: None,
+            blend: N::one(),
+            interval: N::zero(),
+            last_update_time: None,
+            observer: PassivityObserver::new(),
+        }
+    }
+
+    /// Updates the local model with the `model` calculated by the environment
+    /// at `time`. It is interpolated in over the interval since the previous
+    /// update.
+    pub fn update(&mut self, model: LocalModel<N, D>, time: N) {
+        self.interval = match self.last_update_time {
+            Some(last_update_time) if time > last_update_time => time - last_update_time,
+            _ => N::zero(),
+        };
+        self.last_update_time = Some(time);
+        if self.interval > N::zero() {
+            self.previous = Some(self.model.clone());
+            self.blend = N::zero();
+            self.observer.reset();
+        } else {
+            self.previous = None;
+            self.blend = N::one();
+        }
+        self.model = model;
+    }
+
+    /// Calculates the force displayed at position `pos` with velocity `vel`.
+    pub fn calculate_force(
+        &mut self,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let target = self.model.force_at(pos);
+        let previous = match &self.previous {
+            Some(previous) => previous.force_at(pos),
+            None => return target,
+        };
+
+        self.blend = (self.blend + dt / self.interval).min(N::one());
+        let mut force = &previous * (N::one() - self.blend) + &target * self.blend;
+
+        // Observes the energy the blending outputs on top of the target.
+        let energy = self.observer.observe(&(&target - &force), vel, dt);
+        let flow_energy = vel.dot(vel) * dt;
+        if energy < N::zero() && flow_energy > N::zero() {
+            let alpha = -energy / flow_energy;
+            force -= vel * alpha;
+            self.observer.dissipate(alpha * flow_energy);
+        }
+
+        if self.blend >= N::one() {
+            self.previous = None;
+        }
+        force
+    }
+
+    /// Returns the latest local model.
+    pub fn model(&self) -> &LocalModel<N, D> {
+        &self.model
+    }
+
+    /// Returns whether an update is still interpolated in.
+    pub fn is_interpolating(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    /// Returns the measured interval between the latest two updates.
+    pub fn interval(&self) -> N {
+        self.interval
+    }
+}
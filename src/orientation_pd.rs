@@ -0,0 +1,111 @@
+//! Orientation PD Controller
+//!
+//! PD controller on SO(3) for the orientation channel of 6-DoF devices. The
+//! proportional component uses the geodesic orientation error, i.e. the
+//! rotation vector of the shortest rotation from the current to the reference
+//! orientation, which avoids the wrap-around issues of Euler angles.
+use nalgebra::{RealField, Rotation3, UnitQuaternion, Vector3, U3};
+use std::marker::PhantomData;
+
+use crate::Gain;
+
+/// PD controller that calculates a torque for tracking a reference
+/// orientation.
+///
+/// All angular velocities and torques are expressed in the world frame.
+///
+/// ```rust
+/// use haptic_toolbox::OrientationPD;
+/// use nalgebra::{UnitQuaternion, Vector3};
+///
+/// let pd = OrientationPD::<f64>::new(2.0, 0.1);
+///
+/// // The torque always turns the short way around.
+/// let rot_ref = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 3.0);
+/// let rot = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), -3.0);
+/// let zeros = Vector3::zeros();
+/// let torque = pd.calculate_torque(&rot_ref, &rot, &zeros, &zeros);
+///
+/// let err = 2.0 * std::f64::consts::PI - 6.0;
+/// assert!((torque - Vector3::new(0.0, 0.0, -2.0 * err)).norm() < 1e-9);
+/// ```
+pub struct OrientationPD<N, G = N>
+where
+    N: RealField,
+    G: Gain<N, U3>,
+{
+    k_p: G,
+    k_d: G,
+    _phantom: PhantomData<N>,
+}
+
+impl<N, G> OrientationPD<N, G>
+where
+    N: RealField,
+    G: Gain<N, U3>,
+{
+    /// Creates a new orientation PD controller.
+    pub fn new(k_p: G, k_d: G) -> Self {
+        Self {
+            k_p,
+            k_d,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Calculates the torque for tracking reference orientation and angular
+    /// velocity.
+    pub fn calculate_torque(
+        &self,
+        rot_ref: &UnitQuaternion<N>,
+        rot: &UnitQuaternion<N>,
+        ang_vel_ref: &Vector3<N>,
+        ang_vel: &Vector3<N>,
+    ) -> Vector3<N> {
+        self.k_p.apply(&Self::orientation_error(rot_ref, rot))
+            + self.k_d.apply(&(ang_vel_ref - ang_vel))
+    }
+
+    /// Calculates the torque for tracking reference orientation and angular
+    /// velocity given as rotation matrices.
+    pub fn calculate_torque_rotation(
+        &self,
+        rot_ref: &Rotation3<N>,
+        rot: &Rotation3<N>,
+        ang_vel_ref: &Vector3<N>,
+        ang_vel: &Vector3<N>,
+    ) -> Vector3<N> {
+        self.calculate_torque(
+            &UnitQuaternion::from_rotation_matrix(rot_ref),
+            &UnitQuaternion::from_rotation_matrix(rot),
+            ang_vel_ref,
+            ang_vel,
+        )
+    }
+
+    /// Returns the rotation vector of the shortest rotation from `rot` to
+    /// `rot_ref`, whose norm is the geodesic angle in [0, π].
+    pub fn orientation_error(rot_ref: &UnitQuaternion<N>, rot: &UnitQuaternion<N>) -> Vector3<N> {
+        (rot_ref * rot.inverse()).scaled_axis()
+    }
+
+    /// Returns k p.
+    pub fn k_p(&self) -> G {
+        self.k_p.clone()
+    }
+
+    /// Returns k d.
+    pub fn k_d(&self) -> G {
+        self.k_d.clone()
+    }
+
+    /// Sets k p.
+    pub fn set_k_p(&mut self, k_p: G) {
+        self.k_p = k_p;
+    }
+
+    /// Sets k d.
+    pub fn set_k_d(&mut self, k_d: G) {
+        self.k_d = k_d;
+    }
+}
@@ -0,0 +1,62 @@
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+/// Per-axis lower and upper limits of the force a controller may output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OutputLimits<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    min: VectorN<N, D>,
+    max: VectorN<N, D>,
+}
+
+impl<N, D> OutputLimits<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates new `OutputLimits`.
+    pub fn new(min: VectorN<N, D>, max: VectorN<N, D>) -> Self {
+        assert!(
+            min.iter().zip(max.iter()).all(|(min, max)| min <= max),
+            "the lower limit cannot be above the upper limit"
+        );
+        Self { min, max }
+    }
+
+    /// Creates new `OutputLimits` that are symmetric around zero.
+    pub fn symmetric(max: VectorN<N, D>) -> Self {
+        Self::new(-&max, max)
+    }
+
+    /// Returns the lower limits.
+    pub fn min(&self) -> &VectorN<N, D> {
+        &self.min
+    }
+
+    /// Returns the upper limits.
+    pub fn max(&self) -> &VectorN<N, D> {
+        &self.max
+    }
+
+    /// Clamps every axis of `force` to its limits.
+    ///
+    /// Returns the clamped force and whether any axis had to be clamped.
+    pub fn apply(&self, force: &VectorN<N, D>) -> (VectorN<N, D>, bool) {
+        let mut saturated = false;
+        let mut clamped = force.clone();
+        for ((f, &min), &max) in clamped.iter_mut().zip(self.min.iter()).zip(self.max.iter()) {
+            if *f < min {
+                *f = min;
+                saturated = true;
+            } else if *f > max {
+                *f = max;
+                saturated = true;
+            }
+        }
+        (clamped, saturated)
+    }
+}
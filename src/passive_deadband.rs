@@ -0,0 +1,103 @@
+//! Passive Deadband Transmission
+//!
+//! Deadband compression combined with an energy tank. The receiver holds the
+//! last transmitted sample, which can inject energy into the loop. The
+//! transmitter tracks the energy difference between the original and the
+//! reconstructed signal and transmits additional samples whenever the
+//! reconstruction would otherwise become active.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{DeadbandDetector, EnergyTank};
+
+/// Deadband transmitter that keeps the hold-last-sample reconstruction passive.
+///
+/// ```rust
+/// use nalgebra::Vector3;
+/// use haptic_toolbox::{DeadbandDetector, EnergyTank, PassiveDeadbandTransmitter};
+///
+/// let initial_force = Vector3::new(1.0, 0.0, 0.0);
+/// let mut deadband_detector = DeadbandDetector::new(0.1, initial_force);
+/// let mut transmitter =
+///     PassiveDeadbandTransmitter::new(0.1, initial_force, EnergyTank::new(0.0, 0.0));
+///
+/// // The force is within the deadband, but holding the previous force would
+/// // generate energy at the receiver.
+/// let force = Vector3::new(1.05, 0.0, 0.0);
+/// let vel = Vector3::new(1.0, 0.0, 0.0);
+/// assert!(deadband_detector.is_in_deadband(&force));
+/// assert!(transmitter.should_transmit(&force, &vel, 0.001));
+/// assert_eq!(transmitter.transmitted(), &force);
+///
+/// // Holding a larger force than the actual one only dissipates energy.
+/// let force = Vector3::new(1.0, 0.0, 0.0);
+/// assert!(!transmitter.should_transmit(&force, &vel, 0.001));
+/// ```
+#[derive(Debug)]
+pub struct PassiveDeadbandTransmitter<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    deadband_detector: DeadbandDetector<N, D>,
+    energy_tank: EnergyTank<N>,
+    transmitted: VectorN<N, D>,
+}
+
+impl<N, D> PassiveDeadbandTransmitter<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `PassiveDeadbandTransmitter`.
+    ///
+    /// `initial_vals` have to be known by the receiver as well.
+    pub fn new(threshold: N, initial_vals: VectorN<N, D>, energy_tank: EnergyTank<N>) -> Self {
+        Self {
+            deadband_detector: DeadbandDetector::new(threshold, initial_vals.clone()),
+            energy_tank,
+            transmitted: initial_vals,
+        }
+    }
+
+    /// Checks if `force` needs to be transmitted given the velocity `vel` of
+    /// the port it is applied to.
+    pub fn should_transmit(&mut self, force: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> bool {
+        if !self.deadband_detector.is_in_deadband(force) {
+            self.transmitted = force.clone();
+            return true;
+        }
+
+        let energy = (&self.transmitted - force).dot(vel) * dt;
+        if energy >= N::zero() {
+            self.energy_tank.store(energy);
+            false
+        } else if self.energy_tank.extract(-energy) {
+            false
+        } else {
+            self.deadband_detector.set_prev_vals(force);
+            self.transmitted = force.clone();
+            true
+        }
+    }
+
+    /// Returns the last transmitted values, which are held by the receiver.
+    pub fn transmitted(&self) -> &VectorN<N, D> {
+        &self.transmitted
+    }
+
+    /// Returns the energy tank.
+    pub fn energy_tank(&self) -> &EnergyTank<N> {
+        &self.energy_tank
+    }
+
+    /// Returns the deadband detector.
+    pub fn deadband_detector(&self) -> &DeadbandDetector<N, D> {
+        &self.deadband_detector
+    }
+}
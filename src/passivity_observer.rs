@@ -0,0 +1,82 @@
+//! Passivity Observer
+//!
+//! Integrates the energy flowing into a port from the force and velocity at
+//! that port. As long as the observed energy stays non-negative the port has
+//! not generated any energy, i.e. it behaved passively. The observer is shared
+//! by the stabilizers and can be used on its own for analysis.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+/// Energy bookkeeping of a single port.
+///
+/// ```rust
+/// use nalgebra::Vector2;
+/// use haptic_toolbox::PassivityObserver;
+///
+/// let mut observer = PassivityObserver::new();
+///
+/// observer.observe(&Vector2::new(1.0, 0.0), &Vector2::new(2.0, 1.0), 0.5);
+/// assert_eq!(observer.energy(), 1.0);
+///
+/// observer.observe(&Vector2::new(-3.0, 0.0), &Vector2::new(2.0, 1.0), 0.5);
+/// assert_eq!(observer.energy(), -2.0);
+/// assert!(observer.is_active());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PassivityObserver<N>
+where
+    N: RealField,
+{
+    energy: N,
+}
+
+impl<N> PassivityObserver<N>
+where
+    N: RealField,
+{
+    /// Creates a new `PassivityObserver` without any observed energy.
+    pub fn new() -> Self {
+        Self { energy: N::zero() }
+    }
+
+    /// Adds the energy `force * vel * dt` that flowed into the port and
+    /// returns the observed energy.
+    pub fn observe<D>(&mut self, force: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> N
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        self.energy += force.dot(vel) * dt;
+        self.energy
+    }
+
+    /// Adds `energy` that was dissipated by a passivity controller at the port
+    /// and returns the observed energy.
+    pub fn dissipate(&mut self, energy: N) -> N {
+        self.energy += energy;
+        self.energy
+    }
+
+    /// Adds `energy` that was transferred to the port from another one, e.g.
+    /// over a network, and returns the observed energy.
+    ///
+    /// Negative energy is transferred from the port to another one.
+    pub fn transfer(&mut self, energy: N) -> N {
+        self.energy += energy;
+        self.energy
+    }
+
+    /// Returns the observed energy.
+    pub fn energy(&self) -> N {
+        self.energy
+    }
+
+    /// Returns `true` if the port generated energy.
+    pub fn is_active(&self) -> bool {
+        self.energy < N::zero()
+    }
+
+    /// Resets the observed energy to zero.
+    pub fn reset(&mut self) {
+        self.energy = N::zero();
+    }
+}
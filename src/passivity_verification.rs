@@ -0,0 +1,198 @@
+//! Passivity Verification
+//!
+//! Offline analysis of recorded force and velocity traces at the ports of a
+//! network, e.g. master and slave port of a teleoperation system with a
+//! stabilizer. The network is passive if the energy that flowed into it never
+//! becomes negative, so the intervals in which it output more energy than it
+//! absorbed are reported together with the generated energy. This helps to
+//! validate a stabilizer configuration before it is deployed.
+use nalgebra::{
+    allocator::Allocator, convert, dimension::Dim, DefaultAllocator, RealField, VectorN,
+};
+
+/// Recorded force and velocity at a port, sampled every `dt`.
+///
+/// The power `force · vel` is positive when the port absorbs energy.
+#[derive(Clone, Copy, Debug)]
+pub struct PortTrace<'a, N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Forces at the port.
+    pub force: &'a [VectorN<N, D>],
+    /// Velocities at the port.
+    pub vel: &'a [VectorN<N, D>],
+}
+
+/// Interval in which the network was active.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PassivityViolation<N>
+where
+    N: RealField,
+{
+    /// Index of the first active sample.
+    pub start: usize,
+    /// Index after the last active sample.
+    pub end: usize,
+    /// Largest energy the network output beyond what it absorbed within the
+    /// interval.
+    pub worst_activity: N,
+}
+
+impl<N> PassivityViolation<N>
+where
+    N: RealField,
+{
+    /// Returns the duration of the interval for traces sampled every `dt`.
+    pub fn duration(&self, dt: N) -> N {
+        convert::<_, N>((self.end - self.start) as f64) * dt
+    }
+}
+
+/// Result of verifying the passivity of recorded traces.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PassivityReport<N>
+where
+    N: RealField,
+{
+    /// Intervals in which the network was active.
+    pub violations: Vec<PassivityViolation<N>>,
+    /// Energy that flowed into every port until the end of the traces.
+    pub port_energies: Vec<N>,
+    /// Energy that flowed into the network until the end of the traces.
+    pub energy: N,
+    /// Energy of all samples in which the network output power.
+    pub generated_energy: N,
+    /// Largest energy the network output beyond what it absorbed.
+    pub worst_activity: N,
+}
+
+impl<N> PassivityReport<N>
+where
+    N: RealField,
+{
+    /// Returns whether the network was passive throughout the traces.
+    pub fn is_passive(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Verifies the passivity of recorded traces.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{PassivityVerification, PortTrace};
+///
+/// let dt = 0.001;
+/// let vel = (0..1000)
+///     .map(|i| Vector1::new((i as f64 * 0.01).sin()))
+///     .collect::<Vec<_>>();
+///
+/// // A damper is passive.
+/// let force = vel.iter().map(|vel| vel * 2.0).collect::<Vec<_>>();
+/// let verification = PassivityVerification::new(dt);
+/// let report = verification.verify(&[PortTrace { force: &force, vel: &vel }]);
+/// assert!(report.is_passive());
+/// assert_eq!(report.generated_energy, 0.0);
+///
+/// // A negative damper is active as soon as it moves.
+/// let force = vel.iter().map(|vel| vel * -2.0).collect::<Vec<_>>();
+/// let report = verification.verify(&[PortTrace { force: &force, vel: &vel }]);
+/// assert_eq!(report.violations.len(), 1);
+/// assert_eq!(report.violations[0].start, 1);
+/// assert!((report.worst_activity + report.energy).abs() < 1e-12);
+/// ```
+#[derive(Debug)]
+pub struct PassivityVerification<N>
+where
+    N: RealField,
+{
+    dt: N,
+    initial_energy: N,
+}
+
+impl<N> PassivityVerification<N>
+where
+    N: RealField,
+{
+    /// Creates a new `PassivityVerification` for traces sampled every `dt`.
+    pub fn new(dt: N) -> Self {
+        assert!(dt > N::zero(), "the sample time has to be positive");
+        Self {
+            dt,
+            initial_energy: N::zero(),
+        }
+    }
+
+    /// Verifies the passivity of the network with the traces of all its
+    /// `ports`.
+    ///
+    /// Traces of different lengths are verified up to the shortest one.
+    pub fn verify<D>(&self, ports: &[PortTrace<N, D>]) -> PassivityReport<N>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        let samples = ports
+            .iter()
+            .map(|port| port.force.len().min(port.vel.len()))
+            .min()
+            .unwrap_or(0);
+
+        let mut port_energies = vec![N::zero(); ports.len()];
+        let mut energy = self.initial_energy;
+        let mut generated_energy = N::zero();
+        let mut worst_activity = N::zero();
+        let mut violations = Vec::new();
+        let mut violation: Option<PassivityViolation<N>> = None;
+        for i in 0..samples {
+            let mut power = N::zero();
+            for (port, port_energy) in ports.iter().zip(port_energies.iter_mut()) {
+                let port_power = port.force[i].dot(&port.vel[i]);
+                *port_energy += port_power * self.dt;
+                power += port_power;
+            }
+            energy += power * self.dt;
+            generated_energy += (-power).max(N::zero()) * self.dt;
+
+            if energy < N::zero() {
+                let violation = violation.get_or_insert(PassivityViolation {
+                    start: i,
+                    end: i + 1,
+                    worst_activity: N::zero(),
+                });
+                violation.end = i + 1;
+                violation.worst_activity = violation.worst_activity.max(-energy);
+                worst_activity = worst_activity.max(-energy);
+            } else if let Some(violation) = violation.take() {
+                violations.push(violation);
+            }
+        }
+        violations.extend(violation);
+
+        PassivityReport {
+            violations,
+            port_energies,
+            energy,
+            generated_energy,
+            worst_activity,
+        }
+    }
+
+    /// Returns the energy stored in the network before the traces start.
+    pub fn initial_energy(&self) -> N {
+        self.initial_energy
+    }
+
+    /// Sets the energy stored in the network before the traces start, e.g.
+    /// the initial energy of an energy tank, defaults to `0`.
+    pub fn set_initial_energy(&mut self, initial_energy: N) {
+        assert!(
+            initial_energy >= N::zero(),
+            "the initial energy must not be negative"
+        );
+        self.initial_energy = initial_energy;
+    }
+}
@@ -5,33 +5,61 @@
 use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
 use std::marker::PhantomData;
 
-pub struct PD<N, D>
+use crate::{Gain, OutputLimits, Sign};
+
+pub struct PD<N, D, G = N>
 where
     N: RealField,
     D: Dim,
+    G: Gain<N, D>,
     DefaultAllocator: Allocator<N, D>,
 {
-    k_p: N,
-    k_d: N,
+    k_p: G,
+    k_d: G,
+    force_sign: Sign,
+    output_limits: Option<OutputLimits<N, D>>,
+    feedforward: Option<VectorN<N, D>>,
+    feedforward_gain: Option<G>,
     _phantom: PhantomData<D>,
 }
 
-impl<N, D> PD<N, D>
+impl<N, D, G> PD<N, D, G>
 where
     N: RealField,
     D: Dim,
+    G: Gain<N, D>,
     DefaultAllocator: Allocator<N, D>,
 {
-    // Creates a new PD controller.
-    pub fn new(k_p: N, k_d: N) -> Self {
+    /// Creates a new PD controller.
+    ///
+    /// The gains are either scalars shared by all axes, vectors with one gain
+    /// per axis or `MatrixGain`s that couple the axes.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PD;
+    /// use nalgebra::{Vector2, U2};
+    ///
+    /// let pd = PD::<f64, U2, _>::new(Vector2::new(10.0, 40.0), Vector2::new(1.0, 0.0));
+    ///
+    /// let zeros = Vector2::zeros();
+    /// let force = pd.calculate_force(&Vector2::repeat(0.5), &zeros, &Vector2::repeat(1.0), &zeros);
+    /// assert_eq!(force, Vector2::new(6.0, 20.0));
+    /// ```
+    pub fn new(k_p: G, k_d: G) -> Self {
         Self {
             k_p,
             k_d,
+            force_sign: Sign::default(),
+            output_limits: None,
+            feedforward: None,
+            feedforward_gain: None,
             _phantom: PhantomData,
         }
     }
 
     /// Calculates the force for tracking reference position and velocity.
+    ///
+    /// The force is clamped to the output limits if there are any.
     pub fn calculate_force(
         &self,
         pos_ref: &VectorN<N, D>,
@@ -39,26 +67,129 @@ where
         vel_ref: &VectorN<N, D>,
         vel: &VectorN<N, D>,
     ) -> VectorN<N, D> {
-        (pos_ref - pos) * self.k_p + (vel_ref - vel) * self.k_d
+        self.calculate_force_saturated(pos_ref, pos, vel_ref, vel).0
+    }
+
+    /// Calculates the force like `calculate_force` and returns it together
+    /// with `true` if it had to be clamped to the output limits.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{OutputLimits, PD};
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pd = PD::<f64, U1>::new(10.0, 0.0);
+    /// pd.set_output_limits(Some(OutputLimits::symmetric(Vector1::new(1.0))));
+    ///
+    /// let (pos_ref, zero) = (Vector1::new(0.5), Vector1::zeros());
+    /// let (force, saturated) = pd.calculate_force_saturated(&pos_ref, &zero, &zero, &zero);
+    /// assert_eq!(force, Vector1::new(1.0));
+    /// assert!(saturated);
+    /// ```
+    pub fn calculate_force_saturated(
+        &self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+    ) -> (VectorN<N, D>, bool) {
+        let force = self.k_p.apply(&(pos_ref - pos)) + self.k_d.apply(&(vel_ref - vel));
+        let mut force = self.force_sign.apply(force);
+        if let Some(feedforward_force) = self.feedforward_force() {
+            force += feedforward_force;
+        }
+        match &self.output_limits {
+            Some(output_limits) => output_limits.apply(&force),
+            None => (force, false),
+        }
     }
 
     /// Returns k p.
-    pub fn k_p(&self) -> N {
-        self.k_p
+    pub fn k_p(&self) -> G {
+        self.k_p.clone()
     }
 
     /// Returns k d.
-    pub fn k_d(&self) -> N {
-        self.k_d
+    pub fn k_d(&self) -> G {
+        self.k_d.clone()
     }
 
     /// Sets k p.
-    pub fn set_k_p(&mut self, k_p: N) {
+    pub fn set_k_p(&mut self, k_p: G) {
         self.k_p = k_p
     }
 
     /// Sets k d.
-    pub fn set_k_d(&mut self, k_d: N) {
+    pub fn set_k_d(&mut self, k_d: G) {
         self.k_d = k_d
     }
+
+    /// Returns the sign convention of the returned force.
+    pub fn force_sign(&self) -> Sign {
+        self.force_sign
+    }
+
+    /// Sets the sign convention of the returned force, defaults to
+    /// `Sign::Positive`.
+    pub fn set_force_sign(&mut self, force_sign: Sign) {
+        self.force_sign = force_sign
+    }
+
+    /// Returns the feedforward input.
+    pub fn feedforward(&self) -> Option<&VectorN<N, D>> {
+        self.feedforward.as_ref()
+    }
+
+    /// Sets the feedforward input that is added to every returned force after
+    /// multiplying it with the feedforward gain, e.g. the desired acceleration
+    /// or a known gravity or friction force.
+    ///
+    /// The feedforward force is not affected by the sign convention, but is
+    /// clamped to the output limits together with the feedback force.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PD;
+    /// use nalgebra::{Vector2, U2};
+    ///
+    /// let mut pd = PD::<f64, U2>::new(10.0, 0.0);
+    /// // Compensates gravity along the second axis.
+    /// pd.set_feedforward(Some(Vector2::new(0.0, 9.81)));
+    ///
+    /// let zeros = Vector2::zeros();
+    /// let force = pd.calculate_force(&Vector2::new(0.1, 0.0), &zeros, &zeros, &zeros);
+    /// assert_eq!(force, Vector2::new(1.0, 9.81));
+    /// ```
+    pub fn set_feedforward(&mut self, feedforward: Option<VectorN<N, D>>) {
+        self.feedforward = feedforward;
+    }
+
+    /// Returns the feedforward gain.
+    pub fn feedforward_gain(&self) -> Option<&G> {
+        self.feedforward_gain.as_ref()
+    }
+
+    /// Sets the gain the feedforward input is multiplied with, e.g. a mass
+    /// estimate if the feedforward input is the desired acceleration,
+    /// defaults to `None` which adds the feedforward input as is.
+    pub fn set_feedforward_gain(&mut self, feedforward_gain: Option<G>) {
+        self.feedforward_gain = feedforward_gain;
+    }
+
+    fn feedforward_force(&self) -> Option<VectorN<N, D>> {
+        self.feedforward
+            .as_ref()
+            .map(|feedforward| match &self.feedforward_gain {
+                Some(feedforward_gain) => feedforward_gain.apply(feedforward),
+                None => feedforward.clone(),
+            })
+    }
+
+    /// Returns the output limits.
+    pub fn output_limits(&self) -> Option<&OutputLimits<N, D>> {
+        self.output_limits.as_ref()
+    }
+
+    /// Sets the limits the returned force is clamped to.
+    pub fn set_output_limits(&mut self, output_limits: Option<OutputLimits<N, D>>) {
+        self.output_limits = output_limits;
+    }
 }
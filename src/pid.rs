@@ -10,41 +10,191 @@
 //! respectively), hence the name. [[1]](https://en.wikipedia.org/wiki/PID_controller)
 use nalgebra::{
     allocator::Allocator,
+    convert,
     dimension::{Dim, DimName},
     DefaultAllocator, RealField, VectorN,
 };
 use num_traits::Zero;
+use std::time::Duration;
 
-pub struct PID<N, D>
+use crate::{seconds, Clock, Gain, OutputLimits, Sign, SlewLimiter};
+
+/// Strategy to keep the integral error from winding up while the actuator
+/// saturates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum AntiWindup<N>
+where
+    N: RealField,
+{
+    /// The integral error grows without bounds.
+    #[default]
+    None,
+    /// The magnitude of the integral component of every axis is clamped to
+    /// `limit`.
+    Clamping { limit: N },
+    /// The difference between the saturated and the unsaturated force is fed
+    /// back into the integrator with the tracking gain `gain`. This happens
+    /// automatically if the PID has output limits, otherwise the saturated
+    /// force has to be supplied via `PID::track_saturation`.
+    BackCalculation { gain: N },
+}
+
+/// First-order low-pass filter on the derivative component.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum DerivativeFilter<N>
+where
+    N: RealField,
+{
+    /// The derivative is used as is.
+    #[default]
+    None,
+    /// Filters the derivative with a cutoff frequency in Hz.
+    Cutoff(N),
+    /// Filters the derivative with the time constant `k_d / k_p / n`, which
+    /// limits the high frequency gain of the derivative component to `n`
+    /// times the proportional gain.
+    NFactor(N),
+}
+
+/// Discretization of the integral component and the derivative filter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Discretization {
+    /// Integrates with the error of the previous call.
+    ///
+    /// The derivative filter is only stable for time steps up to twice its
+    /// time constant.
+    ForwardEuler,
+    /// Integrates with the error of the current call.
+    #[default]
+    BackwardEuler,
+    /// Integrates with the mean of the current and the previous error, also
+    /// known as the trapezoidal rule.
+    Tustin,
+}
+
+pub struct PID<N, D, G = N>
 where
     N: RealField,
     D: Dim,
+    G: Gain<N, D>,
     DefaultAllocator: Allocator<N, D>,
 {
-    k_p: N,
-    k_i: N,
-    k_d: N,
+    k_p: G,
+    k_i: G,
+    k_d: G,
+    force_sign: Sign,
+    anti_windup: AntiWindup<N>,
+    output_limits: Option<OutputLimits<N, D>>,
+    slew_limiter: Option<SlewLimiter<N, D>>,
+    saturated: bool,
+    clock: Clock,
+    derivative_filter: DerivativeFilter<N>,
+    derivative_on_measurement: bool,
+    setpoint_weight_p: N,
+    setpoint_weight_d: N,
+    integral_leak: N,
+    discretization: Discretization,
 
     integral_error: VectorN<N, D>,
+    filtered_d_error: VectorN<N, D>,
+    last_p_error: VectorN<N, D>,
+    last_d_error: VectorN<N, D>,
+    prev_error: VectorN<N, D>,
+    prev_d_input: VectorN<N, D>,
+    last_force: VectorN<N, D>,
+    feedforward: Option<VectorN<N, D>>,
+    feedforward_gain: Option<G>,
 }
 
-impl<N, D> PID<N, D>
+impl<N, D, G> PID<N, D, G>
 where
     N: RealField,
     D: Dim + DimName,
+    G: Gain<N, D>,
     DefaultAllocator: Allocator<N, D>,
 {
-    // Creates a new PID controller.
-    pub fn new(k_p: N, k_i: N, k_d: N) -> Self {
+    /// Creates a new PID controller.
+    ///
+    /// The gains are either scalars shared by all axes, vectors with one gain
+    /// per axis or `MatrixGain`s that couple the axes.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector3, U3};
+    ///
+    /// let mut pid = PID::<f64, U3, _>::new(
+    ///     Vector3::new(100.0, 100.0, 400.0),
+    ///     Vector3::zeros(),
+    ///     Vector3::new(1.0, 1.0, 2.0),
+    /// );
+    ///
+    /// let zeros = Vector3::zeros();
+    /// let force = pid.calculate_force(&Vector3::repeat(0.01), &zeros, &Vector3::repeat(1.0), &zeros, 0.001);
+    /// assert_eq!(force, Vector3::new(2.0, 2.0, 6.0));
+    /// ```
+    pub fn new(k_p: G, k_i: G, k_d: G) -> Self {
         Self {
             k_p,
             k_i,
             k_d,
+            force_sign: Sign::default(),
+            anti_windup: AntiWindup::default(),
+            output_limits: None,
+            slew_limiter: None,
+            saturated: false,
+            clock: Clock::new(),
+            derivative_filter: DerivativeFilter::default(),
+            derivative_on_measurement: false,
+            setpoint_weight_p: N::one(),
+            setpoint_weight_d: N::one(),
+            integral_leak: N::zero(),
+            discretization: Discretization::default(),
             integral_error: Zero::zero(),
+            filtered_d_error: Zero::zero(),
+            last_p_error: Zero::zero(),
+            last_d_error: Zero::zero(),
+            prev_error: Zero::zero(),
+            prev_d_input: Zero::zero(),
+            last_force: Zero::zero(),
+            feedforward: None,
+            feedforward_gain: None,
         }
     }
 
+    /// Creates a new PID controller with a low-pass filter on the derivative
+    /// component.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{DerivativeFilter, PID};
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::with_derivative_filter(0.0, 0.0, 1.0, DerivativeFilter::Cutoff(10.0));
+    ///
+    /// // The derivative component of a velocity step rises smoothly.
+    /// let zero = Vector1::zeros();
+    /// let vel_ref = Vector1::new(1.0);
+    /// let mut prev_force = 0.0;
+    /// for _ in 0..100 {
+    ///     let force = pid.calculate_force(&zero, &zero, &vel_ref, &zero, 0.001)[0];
+    ///     assert!(force > prev_force && force < 1.0);
+    ///     prev_force = force;
+    /// }
+    /// ```
+    pub fn with_derivative_filter(
+        k_p: G,
+        k_i: G,
+        k_d: G,
+        derivative_filter: DerivativeFilter<N>,
+    ) -> Self {
+        let mut pid = Self::new(k_p, k_i, k_d);
+        pid.set_derivative_filter(derivative_filter);
+        pid
+    }
+
     /// Calculates the force for tracking reference position and velocity.
+    ///
+    /// The derivative component is low-pass filtered according to the
+    /// derivative filter.
     pub fn calculate_force(
         &mut self,
         pos_ref: &VectorN<N, D>,
@@ -52,43 +202,648 @@ where
         vel_ref: &VectorN<N, D>,
         vel: &VectorN<N, D>,
         dt: N,
+    ) -> VectorN<N, D> {
+        let d_error = if self.derivative_on_measurement {
+            -vel
+        } else {
+            vel_ref * self.setpoint_weight_d - vel
+        };
+        let time_constants = match self.derivative_filter {
+            DerivativeFilter::None => None,
+            DerivativeFilter::Cutoff(cutoff) => {
+                Some(VectorN::repeat(N::one() / (N::two_pi() * cutoff)))
+            }
+            DerivativeFilter::NFactor(n) => {
+                let k_p = self.k_p.diagonal();
+                Some(self.k_d.diagonal().zip_map(&k_p, |k_d, k_p| {
+                    if k_p == N::zero() {
+                        N::zero()
+                    } else {
+                        k_d / k_p / n
+                    }
+                }))
+            }
+        };
+        let prev_d_input = std::mem::replace(&mut self.prev_d_input, d_error.clone());
+        match time_constants {
+            Some(time_constants) => {
+                let discretization = self.discretization;
+                let filtered_d_error = &self.filtered_d_error;
+                self.filtered_d_error = VectorN::from_fn(|i, _| {
+                    let (time_constant, filtered) = (time_constants[i], filtered_d_error[i]);
+                    let (input, prev_input) = (d_error[i], prev_d_input[i]);
+                    if time_constant == N::zero() {
+                        return input;
+                    }
+                    match discretization {
+                        Discretization::ForwardEuler => {
+                            filtered + (prev_input - filtered) * dt / time_constant
+                        }
+                        Discretization::BackwardEuler => {
+                            filtered + (input - filtered) * dt / (time_constant + dt)
+                        }
+                        Discretization::Tustin => {
+                            let two_time_constant = time_constant + time_constant;
+                            ((two_time_constant - dt) * filtered + (input + prev_input) * dt)
+                                / (two_time_constant + dt)
+                        }
+                    }
+                });
+                let d_error = self.filtered_d_error.clone();
+                self.calculate_force_with_derivative(pos_ref, pos, &d_error, dt)
+            }
+            None => self.calculate_force_with_derivative(pos_ref, pos, &d_error, dt),
+        }
+    }
+
+    /// Calculates the force like `calculate_force` with the time step given
+    /// as a `Duration`.
+    pub fn calculate_force_duration(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: Duration,
+    ) -> VectorN<N, D> {
+        self.calculate_force(pos_ref, pos, vel_ref, vel, seconds(dt))
+    }
+
+    /// Calculates the force like `calculate_force` with the time since the
+    /// last call measured by the internal clock.
+    ///
+    /// The first call uses a time step of zero.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(0.0, 1.0, 0.0);
+    /// let (pos_ref, zero) = (Vector1::new(1.0), Vector1::zeros());
+    ///
+    /// assert_eq!(pid.calculate_force_clocked(&pos_ref, &zero, &zero, &zero), zero);
+    /// std::thread::sleep(std::time::Duration::from_millis(1));
+    /// assert!(pid.calculate_force_clocked(&pos_ref, &zero, &zero, &zero)[0] >= 0.001);
+    /// ```
+    pub fn calculate_force_clocked(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+    ) -> VectorN<N, D> {
+        let dt = self.clock.tick_seconds();
+        self.calculate_force(pos_ref, pos, vel_ref, vel, dt)
+    }
+
+    /// Calculates the force for tracking the reference position with an
+    /// externally provided derivative of the error.
+    ///
+    /// `d_error` is used as is for the derivative component and bypasses the
+    /// derivative filter, which is useful if an already filtered or estimated
+    /// velocity is available and should not be processed any further. The
+    /// derivative setpoint weight is not applied to `d_error` either.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector2, U2};
+    ///
+    /// let mut pid = PID::<f64, U2>::new(0.0, 0.0, 2.0);
+    /// let zeros = Vector2::zeros();
+    /// let d_error = Vector2::new(0.5, -1.5);
+    ///
+    /// let force = pid.calculate_force_with_derivative(&zeros, &zeros, &d_error, 0.001);
+    /// assert_eq!(force, d_error * 2.0);
+    /// ```
+    pub fn calculate_force_with_derivative(
+        &mut self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        d_error: &VectorN<N, D>,
+        dt: N,
     ) -> VectorN<N, D> {
         let error = pos_ref - pos;
-        self.integral_error += &error * dt;
-        let comp_p = error * self.k_p;
-        let comp_i = &self.integral_error * self.k_i;
-        let comp_d = (vel_ref - vel) * self.k_d;
+        if self.integral_leak > N::zero() {
+            self.integral_error *= (-self.integral_leak * dt).exp();
+        }
+        let integrated_error = match self.discretization {
+            Discretization::ForwardEuler => self.prev_error.clone(),
+            Discretization::BackwardEuler => error.clone(),
+            Discretization::Tustin => (&error + &self.prev_error) * convert::<_, N>(0.5),
+        };
+        self.integral_error += integrated_error * dt;
+        self.prev_error = error.clone();
+        let comp_i = self.k_i.apply(&self.integral_error);
+        let comp_i = match self.anti_windup {
+            AntiWindup::Clamping { limit } => {
+                let clamped = comp_i.map(|comp_i| comp_i.max(-limit).min(limit));
+                if clamped != comp_i {
+                    if let Some(integral_error) = self.k_i.apply_inverse(&clamped) {
+                        self.integral_error = integral_error;
+                    }
+                }
+                clamped
+            }
+            _ => comp_i,
+        };
+        self.last_p_error = pos_ref * self.setpoint_weight_p - pos;
+        self.last_d_error = d_error.clone();
+        let comp_p = self.k_p.apply(&self.last_p_error);
+        let comp_d = self.k_d.apply(d_error);
+
+        self.last_force = self.force_sign.apply(comp_p + comp_i + comp_d);
+        if let Some(feedforward_force) = self.feedforward_force() {
+            self.last_force += feedforward_force;
+        }
+        let mut force = self.last_force.clone();
+        if let Some(output_limits) = &self.output_limits {
+            let (limited_force, saturated) = output_limits.apply(&force);
+            self.saturated = saturated;
+            force = limited_force;
+        }
+        if let Some(slew_limiter) = &mut self.slew_limiter {
+            force = slew_limiter.limit(&force, dt);
+        }
+        if self.output_limits.is_some() || self.slew_limiter.is_some() {
+            self.track_saturation(&force, dt);
+        }
+        force
+    }
+
+    /// Feeds back the force that was actually applied after saturating the
+    /// force returned by the last call to `calculate_force`.
+    ///
+    /// Only has an effect with `AntiWindup::BackCalculation`.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{AntiWindup, PID};
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(0.0, 10.0, 0.0);
+    /// let mut windup_pid = PID::<f64, U1>::new(0.0, 10.0, 0.0);
+    /// pid.set_anti_windup(AntiWindup::BackCalculation { gain: 10.0 });
+    ///
+    /// let (pos_ref, zero) = (Vector1::new(1.0), Vector1::zeros());
+    /// for _ in 0..1000 {
+    ///     let force = pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.01);
+    ///     pid.track_saturation(&force.map(|f| f.min(1.0)), 0.01);
+    ///     windup_pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.01);
+    /// }
+    ///
+    /// // Without anti-windup the integral grows far beyond the saturation.
+    /// let force = pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.01);
+    /// let windup_force = windup_pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.01);
+    /// assert!(force[0] < 2.1);
+    /// assert!(windup_force[0] > 90.0);
+    /// ```
+    pub fn track_saturation(&mut self, saturated_force: &VectorN<N, D>, dt: N) {
+        if let AntiWindup::BackCalculation { gain } = self.anti_windup {
+            let diff = self.force_sign.apply(saturated_force - &self.last_force);
+            if let Some(diff) = self.k_i.apply_inverse(&diff) {
+                self.integral_error += diff * (gain * dt);
+            }
+        }
+    }
+
+    /// Resets the internal state as if the controller was just created.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(1.0, 1.0, 0.0);
+    /// let (pos_ref, zero) = (Vector1::new(1.0), Vector1::zeros());
+    ///
+    /// let first_force = pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.1);
+    /// pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.1);
+    /// assert_eq!(pid.integral_error(), &Vector1::new(0.2));
+    ///
+    /// pid.reset();
+    /// assert_eq!(pid.integral_error(), &zero);
+    /// assert_eq!(pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.1), first_force);
+    /// ```
+    pub fn reset(&mut self) {
+        self.integral_error = Zero::zero();
+        self.filtered_d_error = Zero::zero();
+        self.last_p_error = Zero::zero();
+        self.last_d_error = Zero::zero();
+        self.prev_error = Zero::zero();
+        self.prev_d_input = Zero::zero();
+        self.last_force = Zero::zero();
+        self.saturated = false;
+        self.clock.reset();
+        if let Some(slew_limiter) = &mut self.slew_limiter {
+            slew_limiter.set_value(Zero::zero());
+        }
+    }
+
+    /// Returns the integral of the position error.
+    pub fn integral_error(&self) -> &VectorN<N, D> {
+        &self.integral_error
+    }
+
+    /// Sets the integral of the position error, e.g. to start with the
+    /// integral component that holds a known load.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(1.0, 2.0, 0.0);
+    /// pid.set_integral_error(Vector1::new(1.5));
+    ///
+    /// let zero = Vector1::zeros();
+    /// assert_eq!(pid.calculate_force(&zero, &zero, &zero, &zero, 0.1), Vector1::new(3.0));
+    /// ```
+    pub fn set_integral_error(&mut self, integral_error: VectorN<N, D>) {
+        self.integral_error = integral_error;
+    }
+
+    /// Returns the discretization.
+    pub fn discretization(&self) -> Discretization {
+        self.discretization
+    }
+
+    /// Sets the discretization of the integral component and the derivative
+    /// filter, defaults to `Discretization::BackwardEuler`.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{Discretization, PID};
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(0.0, 1.0, 0.0);
+    /// pid.set_discretization(Discretization::Tustin);
+    ///
+    /// // Integrating a ramp with the trapezoidal rule is exact.
+    /// let zero = Vector1::zeros();
+    /// for i in 1..=10 {
+    ///     pid.calculate_force(&Vector1::new(i as f64 * 0.1), &zero, &zero, &zero, 0.1);
+    /// }
+    /// assert!((pid.integral_error()[0] - 0.5f64).abs() < 1e-12);
+    /// ```
+    pub fn set_discretization(&mut self, discretization: Discretization) {
+        self.discretization = discretization;
+    }
+
+    /// Returns the integral leak rate.
+    pub fn integral_leak(&self) -> N {
+        self.integral_leak
+    }
+
+    /// Sets the rate in 1/s with which the integral error decays
+    /// exponentially, defaults to zero.
+    ///
+    /// A small leak keeps a slowly accumulating bias, e.g. from sensor
+    /// offsets, from building up into a steady parasitic force.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(0.0, 1.0, 0.0);
+    /// pid.set_integral_leak(0.5);
+    ///
+    /// // A constant offset no longer makes the integral grow without bounds,
+    /// // it converges to offset / leak instead.
+    /// let (offset, zero) = (Vector1::new(0.01), Vector1::zeros());
+    /// for _ in 0..100_000 {
+    ///     pid.calculate_force(&offset, &zero, &zero, &zero, 0.001);
+    /// }
+    /// assert!((pid.integral_error()[0] - 0.02f64).abs() < 1e-4);
+    /// ```
+    pub fn set_integral_leak(&mut self, integral_leak: N) {
+        assert!(
+            integral_leak >= N::zero(),
+            "the integral leak must not be negative"
+        );
+        self.integral_leak = integral_leak;
+    }
+
+    /// Returns the anti-windup strategy.
+    pub fn anti_windup(&self) -> AntiWindup<N> {
+        self.anti_windup
+    }
+
+    /// Sets the anti-windup strategy, defaults to `AntiWindup::None`.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{AntiWindup, PID};
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(0.0, 10.0, 0.0);
+    /// pid.set_anti_windup(AntiWindup::Clamping { limit: 2.0 });
+    ///
+    /// let (pos_ref, zero) = (Vector1::new(1.0), Vector1::zeros());
+    /// for _ in 0..100 {
+    ///     let force = pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.01);
+    ///     assert!(force[0] <= 2.0 + 1e-12);
+    /// }
+    /// ```
+    pub fn set_anti_windup(&mut self, anti_windup: AntiWindup<N>) {
+        self.anti_windup = anti_windup;
+    }
+
+    /// Returns the derivative filter.
+    pub fn derivative_filter(&self) -> DerivativeFilter<N> {
+        self.derivative_filter
+    }
+
+    /// Sets the derivative filter, defaults to `DerivativeFilter::None`.
+    pub fn set_derivative_filter(&mut self, derivative_filter: DerivativeFilter<N>) {
+        match derivative_filter {
+            DerivativeFilter::Cutoff(value) | DerivativeFilter::NFactor(value) => {
+                assert!(value > N::zero(), "the filter parameter has to be positive")
+            }
+            DerivativeFilter::None => {}
+        }
+        self.derivative_filter = derivative_filter;
+    }
+
+    /// Returns `true` if the derivative component only uses the measured
+    /// velocity.
+    pub fn derivative_on_measurement(&self) -> bool {
+        self.derivative_on_measurement
+    }
+
+    /// Sets whether the derivative component only uses the measured velocity
+    /// instead of the velocity error.
+    ///
+    /// This avoids the derivative kick when the reference jumps, e.g. because
+    /// it is received as packets over a network.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(0.0, 0.0, 1.0);
+    /// pid.set_derivative_on_measurement(true);
+    ///
+    /// let zero = Vector1::zeros();
+    /// let force = pid.calculate_force(&zero, &zero, &Vector1::new(100.0), &Vector1::new(0.5), 0.001);
+    /// assert_eq!(force, Vector1::new(-0.5));
+    /// ```
+    pub fn set_derivative_on_measurement(&mut self, derivative_on_measurement: bool) {
+        self.derivative_on_measurement = derivative_on_measurement;
+    }
+
+    /// Returns the proportional and derivative setpoint weights.
+    pub fn setpoint_weights(&self) -> (N, N) {
+        (self.setpoint_weight_p, self.setpoint_weight_d)
+    }
+
+    /// Sets the proportional (b) and derivative (c) setpoint weights, both
+    /// default to one.
+    ///
+    /// The proportional component uses `b * pos_ref - pos` and the derivative
+    /// component `c * vel_ref - vel`, while the integral component always
+    /// uses the full error. This way the reaction to reference changes can be
+    /// tuned independently of the disturbance rejection.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(2.0, 0.0, 1.0);
+    /// pid.set_setpoint_weights(0.5, 0.0);
+    ///
+    /// let force = pid.calculate_force(
+    ///     &Vector1::new(1.0),
+    ///     &Vector1::new(0.25),
+    ///     &Vector1::new(3.0),
+    ///     &Vector1::new(0.5),
+    ///     0.001,
+    /// );
+    /// assert_eq!(force, Vector1::new(2.0 * (0.5 - 0.25) - 0.5));
+    /// ```
+    pub fn set_setpoint_weights(&mut self, b: N, c: N) {
+        self.setpoint_weight_p = b;
+        self.setpoint_weight_d = c;
+    }
+
+    /// Returns the feedforward input.
+    pub fn feedforward(&self) -> Option<&VectorN<N, D>> {
+        self.feedforward.as_ref()
+    }
 
-        comp_p + comp_i + comp_d
+    /// Sets the feedforward input that is added to every returned force after
+    /// multiplying it with the feedforward gain, e.g. the desired acceleration
+    /// or a known gravity or friction force.
+    ///
+    /// The feedforward force is not affected by the sign convention, but is
+    /// clamped to the output limits together with the feedback force.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(10.0, 0.0, 0.0);
+    /// pid.set_feedforward_gain(Some(0.5));
+    /// pid.set_feedforward(Some(Vector1::new(4.0)));
+    ///
+    /// let zero = Vector1::zeros();
+    /// let force = pid.calculate_force(&Vector1::new(0.1), &zero, &zero, &zero, 0.001);
+    /// assert_eq!(force, Vector1::new(3.0));
+    /// ```
+    pub fn set_feedforward(&mut self, feedforward: Option<VectorN<N, D>>) {
+        self.feedforward = feedforward;
+    }
+
+    /// Returns the feedforward gain.
+    pub fn feedforward_gain(&self) -> Option<&G> {
+        self.feedforward_gain.as_ref()
+    }
+
+    /// Sets the gain the feedforward input is multiplied with, e.g. a mass
+    /// estimate if the feedforward input is the desired acceleration,
+    /// defaults to `None` which adds the feedforward input as is.
+    pub fn set_feedforward_gain(&mut self, feedforward_gain: Option<G>) {
+        self.feedforward_gain = feedforward_gain;
+    }
+
+    fn feedforward_force(&self) -> Option<VectorN<N, D>> {
+        self.feedforward
+            .as_ref()
+            .map(|feedforward| match &self.feedforward_gain {
+                Some(feedforward_gain) => feedforward_gain.apply(feedforward),
+                None => feedforward.clone(),
+            })
+    }
+
+    /// Returns the output limits.
+    pub fn output_limits(&self) -> Option<&OutputLimits<N, D>> {
+        self.output_limits.as_ref()
+    }
+
+    /// Sets the limits the returned force is clamped to.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{OutputLimits, PID};
+    /// use nalgebra::{Vector2, U2};
+    ///
+    /// let mut pid = PID::<f64, U2>::new(10.0, 0.0, 0.0);
+    /// pid.set_output_limits(Some(OutputLimits::symmetric(Vector2::new(1.0, 5.0))));
+    ///
+    /// let zeros = Vector2::zeros();
+    /// let force = pid.calculate_force(&Vector2::new(0.2, 0.2), &zeros, &zeros, &zeros, 0.01);
+    /// assert_eq!(force, Vector2::new(1.0, 2.0));
+    /// assert!(pid.is_saturated());
+    ///
+    /// pid.calculate_force(&Vector2::new(0.05, 0.2), &zeros, &zeros, &zeros, 0.01);
+    /// assert!(!pid.is_saturated());
+    /// ```
+    pub fn set_output_limits(&mut self, output_limits: Option<OutputLimits<N, D>>) {
+        self.output_limits = output_limits;
+        self.saturated = false;
+    }
+
+    /// Returns the slew limiter.
+    pub fn slew_limiter(&self) -> Option<&SlewLimiter<N, D>> {
+        self.slew_limiter.as_ref()
+    }
+
+    /// Sets the slew limiter that limits the rate of change of the returned
+    /// force after clamping it to the output limits.
+    ///
+    /// The rate limited force is fed back to the anti-windup.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{SlewLimiter, PID};
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(10.0, 0.0, 0.0);
+    /// pid.set_slew_limiter(Some(SlewLimiter::new(Vector1::new(100.0))));
+    ///
+    /// let (pos_ref, zero) = (Vector1::new(1.0), Vector1::zeros());
+    /// let force = pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.001);
+    /// assert!((force[0] - 0.1f64).abs() < 1e-12);
+    /// ```
+    pub fn set_slew_limiter(&mut self, slew_limiter: Option<SlewLimiter<N, D>>) {
+        self.slew_limiter = slew_limiter;
+    }
+
+    /// Returns `true` if the last calculated force had to be clamped.
+    pub fn is_saturated(&self) -> bool {
+        self.saturated
     }
 
     /// Returns k p.
-    pub fn k_p(&self) -> N {
-        self.k_p
+    pub fn k_p(&self) -> G {
+        self.k_p.clone()
     }
 
     /// Returns k i.
-    pub fn k_i(&self) -> N {
-        self.k_i
+    pub fn k_i(&self) -> G {
+        self.k_i.clone()
     }
 
     /// Returns k d.
-    pub fn k_d(&self) -> N {
-        self.k_d
+    pub fn k_d(&self) -> G {
+        self.k_d.clone()
     }
 
     /// Sets k p.
-    pub fn set_k_p(&mut self, k_p: N) {
+    pub fn set_k_p(&mut self, k_p: G) {
         self.k_p = k_p;
     }
 
     /// Sets k i.
-    pub fn set_k_i(&mut self, k_i: N) {
+    ///
+    /// The integral error is rescaled so that the integral component stays
+    /// the same and the force does not jump. If a scalar or a matrix gain is
+    /// not invertible the integral error is kept as is. A vector gain is
+    /// rescaled per axis, so the integral error of each axis with a zero gain
+    /// is reset to zero.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(0.0, 1.0, 0.0);
+    /// let (pos_ref, zero) = (Vector1::new(1.0), Vector1::zeros());
+    /// for _ in 0..10 {
+    ///     pid.calculate_force(&pos_ref, &zero, &zero, &zero, 0.1);
+    /// }
+    ///
+    /// pid.set_k_i(4.0);
+    /// let force = pid.calculate_force(&zero, &zero, &zero, &zero, 0.1);
+    /// assert!((force[0] - 1.0f64).abs() < 1e-12);
+    /// ```
+    pub fn set_k_i(&mut self, k_i: G) {
+        let comp_i = self.k_i.apply(&self.integral_error);
+        if let Some(integral_error) = k_i.apply_inverse(&comp_i) {
+            self.integral_error = integral_error;
+        }
         self.k_i = k_i;
     }
 
+    /// Sets all gains while keeping the force continuous.
+    ///
+    /// The change of the proportional and derivative components for the errors
+    /// of the last call to `calculate_force` is absorbed by the integral
+    /// component, which then decays with the integral action. This only works
+    /// if the new k i is invertible, otherwise the gains are simply set.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::PID;
+    /// use nalgebra::{Vector1, U1};
+    ///
+    /// let mut pid = PID::<f64, U1>::new(10.0, 1.0, 0.0);
+    /// let (pos_ref, pos, zero) = (Vector1::new(1.0), Vector1::new(0.8), Vector1::zeros());
+    /// let force = pid.calculate_force(&pos_ref, &pos, &zero, &zero, 0.0);
+    ///
+    /// pid.set_gains_bumpless(50.0, 2.0, 0.0);
+    /// let bumpless_force = pid.calculate_force(&pos_ref, &pos, &zero, &zero, 0.0);
+    /// assert!((bumpless_force - force).norm() < 1e-12);
+    /// ```
+    pub fn set_gains_bumpless(&mut self, k_p: G, k_i: G, k_d: G) {
+        let comp = self.k_p.apply(&self.last_p_error)
+            + self.k_i.apply(&self.integral_error)
+            + self.k_d.apply(&self.last_d_error);
+        let comp_i = comp - k_p.apply(&self.last_p_error) - k_d.apply(&self.last_d_error);
+        if let Some(integral_error) = k_i.apply_inverse(&comp_i) {
+            self.integral_error = integral_error;
+        }
+        self.k_p = k_p;
+        self.k_i = k_i;
+        self.k_d = k_d;
+    }
+
     /// Sets k d.
-    pub fn set_k_d(&mut self, k_d: N) {
+    pub fn set_k_d(&mut self, k_d: G) {
         self.k_d = k_d;
     }
+
+    /// Returns the sign convention of the returned force.
+    pub fn force_sign(&self) -> Sign {
+        self.force_sign
+    }
+
+    /// Sets the sign convention of the returned force, defaults to
+    /// `Sign::Positive`.
+    ///
+    /// Only the returned force is flipped, the integral error keeps
+    /// accumulating the same way.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::{Sign, PID};
+    /// use nalgebra::{Vector2, U2};
+    ///
+    /// let mut pid = PID::<f64, U2>::new(0.0, 2.0, 0.0);
+    /// let mut flipped_pid = PID::<f64, U2>::new(0.0, 2.0, 0.0);
+    /// flipped_pid.set_force_sign(Sign::Negative);
+    ///
+    /// let pos_ref = Vector2::new(1.0, -1.0);
+    /// let zeros = Vector2::zeros();
+    /// for i in 1..=10 {
+    ///     let force = pid.calculate_force(&pos_ref, &zeros, &zeros, &zeros, 0.1);
+    ///     let flipped_force = flipped_pid.calculate_force(&pos_ref, &zeros, &zeros, &zeros, 0.1);
+    ///     assert_eq!(flipped_force, -force);
+    ///
+    ///     let expected = pos_ref * 0.1 * i as f64 * 2.0;
+    ///     assert!((flipped_force + expected).norm() < 1e-12);
+    /// }
+    /// ```
+    pub fn set_force_sign(&mut self, force_sign: Sign) {
+        self.force_sign = force_sign;
+    }
 }
@@ -0,0 +1,273 @@
+//! Signal Pipeline
+//!
+//! Chains several processing stages (estimators, filters, controllers,
+//! limiters, ...) into a single object with one `process` entry point. Every
+//! stage owns its state, the pipeline only threads the signal through them in
+//! the order they were pushed. Controllers like `PD` and `PID` become stages
+//! with a `ControllerStage`, which tracks a reference with the signal as the
+//! measurement.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{CascadeLoop, OutputLimits, SlewLimiter, ISS};
+
+/// A single stage that transforms a signal sample by sample.
+pub trait SignalProcessor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Processes one sample and returns the output of the stage.
+    fn process(&mut self, input: &VectorN<N, D>, dt: N) -> VectorN<N, D>;
+}
+
+impl<N, D, F> SignalProcessor<N, D> for F
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    F: FnMut(&VectorN<N, D>, N) -> VectorN<N, D>,
+{
+    fn process(&mut self, input: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        self(input, dt)
+    }
+}
+
+impl<N, D> SignalProcessor<N, D> for ISS<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn process(&mut self, input: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        self.calculate_force(input, dt)
+    }
+}
+
+impl<N, D> SignalProcessor<N, D> for SlewLimiter<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn process(&mut self, input: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        self.limit(input, dt)
+    }
+}
+
+impl<N, D> SignalProcessor<N, D> for OutputLimits<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn process(&mut self, input: &VectorN<N, D>, _dt: N) -> VectorN<N, D> {
+        self.apply(input).0
+    }
+}
+
+/// Stage that calculates the output of a controller tracking a reference,
+/// with the input as the measurement.
+///
+/// The rate of change of the measurement is its difference quotient, which is
+/// zero for the first sample.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{ControllerStage, OutputLimits, Pipeline, SlewLimiter, PD};
+///
+/// let (dt, pos_ref) = (0.001, Vector1::new(0.1));
+/// // Estimates the position from encoder counts.
+/// let estimate = |counts: &Vector1<f64>, _dt: f64| counts * 1e-4;
+/// let low_pass = |alpha: f64| {
+///     let mut filtered = Vector1::zeros();
+///     move |input: &Vector1<f64>, _dt: f64| {
+///         filtered += (input - filtered) * alpha;
+///         filtered
+///     }
+/// };
+/// let controller = || PD::<f64, U1>::new(100.0, 2.0);
+/// let rate_limit = SlewLimiter::new(Vector1::new(500.0));
+/// let saturate = OutputLimits::symmetric(Vector1::new(5.0));
+///
+/// let mut pipeline = Pipeline::new();
+/// pipeline.push_stage(estimate);
+/// pipeline.push_stage(low_pass(0.2));
+/// pipeline.push_stage(ControllerStage::new(controller(), pos_ref));
+/// pipeline.push_stage(rate_limit.clone());
+/// pipeline.push_stage(saturate.clone());
+///
+/// let (mut filter, pd, mut slew_limiter) = (low_pass(0.2), controller(), rate_limit);
+/// let mut prev_pos = None;
+/// for i in 0..100 {
+///     let counts = Vector1::new((i * 7 % 50) as f64);
+///
+///     let pos = filter(&estimate(&counts, dt), dt);
+///     let vel = match prev_pos {
+///         Some(prev_pos) => (pos - prev_pos) / dt,
+///         None => Vector1::zeros(),
+///     };
+///     prev_pos = Some(pos);
+///     let force = pd.calculate_force(&pos_ref, &pos, &Vector1::zeros(), &vel);
+///     let force = saturate.apply(&slew_limiter.limit(&force, dt)).0;
+///
+///     assert_eq!(pipeline.process(&counts, dt), force);
+/// }
+/// ```
+pub struct ControllerStage<C, N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    controller: C,
+    reference: VectorN<N, D>,
+    prev_measurement: Option<VectorN<N, D>>,
+}
+
+impl<C, N, D> ControllerStage<C, N, D>
+where
+    C: CascadeLoop<N, D>,
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `ControllerStage` tracking `reference` with
+    /// `controller`.
+    pub fn new(controller: C, reference: VectorN<N, D>) -> Self {
+        Self {
+            controller,
+            reference,
+            prev_measurement: None,
+        }
+    }
+
+    /// Returns the reference.
+    pub fn reference(&self) -> &VectorN<N, D> {
+        &self.reference
+    }
+
+    /// Sets the reference the controller tracks.
+    pub fn set_reference(&mut self, reference: VectorN<N, D>) {
+        self.reference = reference;
+    }
+
+    /// Returns the controller.
+    pub fn controller(&self) -> &C {
+        &self.controller
+    }
+
+    /// Returns the controller mutably.
+    pub fn controller_mut(&mut self) -> &mut C {
+        &mut self.controller
+    }
+}
+
+impl<C, N, D> SignalProcessor<N, D> for ControllerStage<C, N, D>
+where
+    C: CascadeLoop<N, D>,
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn process(&mut self, input: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        let rate = match &self.prev_measurement {
+            Some(prev_measurement) if dt > N::zero() => (input - prev_measurement) / dt,
+            _ => input * N::zero(),
+        };
+        self.prev_measurement = Some(input.clone());
+        self.controller.output(&self.reference, input, &rate, dt).0
+    }
+}
+
+/// Ordered chain of `SignalProcessor` stages.
+///
+/// ```rust
+/// use nalgebra::{Vector2, U2};
+/// use haptic_toolbox::{Pipeline, ISS};
+///
+/// let saturate = |force: &Vector2<f64>, _dt: f64| force.map(|f| f.max(-1.0).min(1.0));
+///
+/// let mut pipeline = Pipeline::new();
+/// pipeline.push_stage(|input: &Vector2<f64>, _dt: f64| input * 2.0);
+/// pipeline.push_stage(ISS::<f64, U2>::new(0.01, 1.0));
+/// pipeline.push_stage(saturate);
+///
+/// let mut iss = ISS::<f64, U2>::new(0.01, 1.0);
+/// for i in 0..10 {
+///     let input = Vector2::new(0.1 * i as f64, -0.05 * i as f64);
+///     let expected = saturate(&iss.calculate_force(&(input * 2.0), 0.1), 0.1);
+///     assert_eq!(pipeline.process(&input, 0.1), expected);
+/// }
+/// ```
+pub struct Pipeline<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    stages: Vec<Box<dyn SignalProcessor<N, D>>>,
+}
+
+impl<N, D> Default for Pipeline<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn default() -> Self {
+        Self { stages: Vec::new() }
+    }
+}
+
+impl<N, D> Pipeline<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new empty `Pipeline`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a stage to the end of the pipeline.
+    pub fn push_stage<S>(&mut self, stage: S)
+    where
+        S: SignalProcessor<N, D> + 'static,
+    {
+        self.stages.push(Box::new(stage));
+    }
+
+    /// Returns the number of stages.
+    pub fn len(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Returns `true` if the pipeline has no stages.
+    pub fn is_empty(&self) -> bool {
+        self.stages.is_empty()
+    }
+
+    /// Passes `input` through all stages in order.
+    pub fn process(&mut self, input: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        self.stages
+            .iter_mut()
+            .fold(input.clone(), |signal, stage| stage.process(&signal, dt))
+    }
+}
+
+impl<N, D> SignalProcessor<N, D> for Pipeline<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn process(&mut self, input: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        Pipeline::process(self, input, dt)
+    }
+}
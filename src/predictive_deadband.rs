@@ -0,0 +1,132 @@
+//! Predictive Deadband Coding
+//!
+//! Sender and receiver run identical first-order predictors. The sender only
+//! transmits a sample if the prediction error exceeds the perceptual
+//! threshold, the receiver uses the prediction until the next sample arrives.
+//! Since both predictors are updated with the same samples at the same time
+//! their states never diverge.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{DeadbandReconstructor, ReconstructionStrategy};
+
+/// Sending side of predictive deadband coding.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::{PredictiveDeadbandDecoder, PredictiveDeadbandEncoder};
+///
+/// let mut encoder = PredictiveDeadbandEncoder::new(0.1, Vector1::new(0.0));
+/// let mut decoder = PredictiveDeadbandDecoder::new(Vector1::new(0.0));
+///
+/// // After the slope of the ramp is known nothing needs to be transmitted
+/// // anymore.
+/// let mut transmitted = 0;
+/// for i in 1..100 {
+///     let vals = Vector1::new(i as f64 * 0.01);
+///     let sample = encoder.encode(&vals, 0.001);
+///     transmitted += sample.is_some() as usize;
+///     let decoded = decoder.decode(sample.as_ref(), 0.001);
+///     assert_eq!(decoded, encoder.prediction());
+/// }
+/// assert_eq!(transmitted, 1);
+/// ```
+#[derive(Debug)]
+pub struct PredictiveDeadbandEncoder<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    threshold: N,
+    predictor: DeadbandReconstructor<N, D>,
+}
+
+impl<N, D> PredictiveDeadbandEncoder<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `PredictiveDeadbandEncoder` with a relative `threshold`.
+    ///
+    /// `initial_vals` have to match the ones of the decoder.
+    pub fn new(threshold: N, initial_vals: VectorN<N, D>) -> Self {
+        assert!(threshold >= N::zero(), "cannot assign a negative threshold");
+        Self {
+            threshold,
+            predictor: DeadbandReconstructor::new(
+                initial_vals,
+                ReconstructionStrategy::FirstOrderPrediction,
+            ),
+        }
+    }
+
+    /// Advances the predictor by `dt` and returns `vals` if they have to be
+    /// transmitted.
+    pub fn encode(&mut self, vals: &VectorN<N, D>, dt: N) -> Option<VectorN<N, D>> {
+        let prediction = self.predictor.update(None, dt);
+        if (&prediction - vals).norm() <= self.threshold * prediction.norm() {
+            return None;
+        }
+        self.predictor.update(Some(vals), N::zero());
+        Some(vals.clone())
+    }
+
+    /// Returns the current prediction, which is also the output of the
+    /// decoder.
+    pub fn prediction(&self) -> VectorN<N, D> {
+        self.predictor.reconstruct()
+    }
+
+    /// Returns the relative threshold.
+    pub fn threshold(&self) -> N {
+        self.threshold
+    }
+
+    /// Sets the relative threshold.
+    pub fn set_threshold(&mut self, threshold: N) {
+        assert!(threshold >= N::zero(), "cannot assign a negative threshold");
+        self.threshold = threshold;
+    }
+}
+
+/// Receiving side of predictive deadband coding.
+#[derive(Debug)]
+pub struct PredictiveDeadbandDecoder<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    predictor: DeadbandReconstructor<N, D>,
+}
+
+impl<N, D> PredictiveDeadbandDecoder<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `PredictiveDeadbandDecoder`.
+    ///
+    /// `initial_vals` have to match the ones of the encoder.
+    pub fn new(initial_vals: VectorN<N, D>) -> Self {
+        Self {
+            predictor: DeadbandReconstructor::new(
+                initial_vals,
+                ReconstructionStrategy::FirstOrderPrediction,
+            ),
+        }
+    }
+
+    /// Advances the predictor by `dt` and returns the decoded values.
+    ///
+    /// `received` is the sample that was received in this step, if any.
+    pub fn decode(&mut self, received: Option<&VectorN<N, D>>, dt: N) -> VectorN<N, D> {
+        self.predictor.update(received, dt)
+    }
+}
@@ -0,0 +1,110 @@
+//! Passive Set-Position Modulation (PSPM)
+//!
+//! The set-position received over a delayed or lossy network is modulated
+//! before it is fed to a local PD coupling, so that moving the set-position
+//! never releases more energy into the coupling spring than is available in
+//! the energy reservoir. As long as the reservoir is only filled with energy
+//! that was actually dissipated or transmitted, the coupled system stays
+//! passive, as proposed by Lee and Huang in "Passive-Set-Position-Modulation
+//! Framework for Interactive Robotic Systems".
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+/// Modulates the set-position of a PD coupling within an energy budget.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::PSPM;
+///
+/// let mut pspm = PSPM::new(100.0, Vector1::new(0.0));
+/// let pos = Vector1::new(0.0);
+///
+/// // Without energy the set-position can not move away from the device.
+/// assert_eq!(pspm.modulate(&Vector1::new(0.1), &pos), Vector1::new(0.0));
+///
+/// // 0.125 J are enough to stretch the spring by 5 cm.
+/// pspm.store(0.125);
+/// let set_pos = pspm.modulate(&Vector1::new(0.1), &pos);
+/// assert!((set_pos[0] - 0.05f64).abs() < 1e-12);
+/// assert!(pspm.energy().abs() < 1e-12);
+///
+/// // Relaxing the spring returns its energy to the reservoir.
+/// pspm.modulate(&Vector1::new(0.0), &pos);
+/// assert!((pspm.energy() - 0.125f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PSPM<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    stiffness: N,
+    energy: N,
+    set_pos: VectorN<N, D>,
+}
+
+impl<N, D> PSPM<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `PSPM` for a coupling with `stiffness` that starts at
+    /// the set-position `set_pos` with an empty reservoir.
+    pub fn new(stiffness: N, set_pos: VectorN<N, D>) -> Self {
+        assert!(stiffness > N::zero(), "the stiffness has to be positive");
+        Self {
+            stiffness,
+            energy: N::zero(),
+            set_pos,
+        }
+    }
+
+    /// Returns the set-position closest to the `desired` one whose spring
+    /// energy at the device position `pos` can be afforded.
+    pub fn modulate(&mut self, desired: &VectorN<N, D>, pos: &VectorN<N, D>) -> VectorN<N, D> {
+        let half_stiffness = self.stiffness / (N::one() + N::one());
+        let spring_energy = (&self.set_pos - pos).norm_squared() * half_stiffness;
+        let desired_offset = desired - pos;
+        let desired_energy = desired_offset.norm_squared() * half_stiffness;
+
+        if desired_energy - spring_energy <= self.energy {
+            self.energy -= desired_energy - spring_energy;
+            self.set_pos = desired.clone();
+        } else {
+            let max_energy = spring_energy + self.energy.max(N::zero());
+            let scale = (max_energy / desired_energy).sqrt();
+            self.set_pos = pos + desired_offset * scale;
+            self.energy -= max_energy - spring_energy;
+        }
+        self.set_pos.clone()
+    }
+
+    /// Stores energy in the reservoir, e.g. energy received over the network
+    /// or dissipated by the damping of the coupling.
+    pub fn store(&mut self, energy: N) {
+        assert!(energy >= N::zero(), "the energy must not be negative");
+        self.energy += energy;
+    }
+
+    /// Returns the energy in the reservoir.
+    pub fn energy(&self) -> N {
+        self.energy
+    }
+
+    /// Returns the last modulated set-position.
+    pub fn set_pos(&self) -> &VectorN<N, D> {
+        &self.set_pos
+    }
+
+    /// Returns the stiffness of the coupling.
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+
+    /// Sets the stiffness of the coupling.
+    pub fn set_stiffness(&mut self, stiffness: N) {
+        assert!(stiffness > N::zero(), "the stiffness has to be positive");
+        self.stiffness = stiffness;
+    }
+}
@@ -0,0 +1,82 @@
+//! Rotation Deadband
+//!
+//! Deadband compression for the orientation channel of 6-DoF teleoperation.
+//! Orientations are compared by the geodesic angle between them, which avoids
+//! the wrap-around issues of comparing Euler angles component-wise.
+use nalgebra::{RealField, Rotation3, UnitQuaternion};
+
+/// Detector for orientations that are in the deadband of the previous
+/// orientation.
+///
+/// ```rust
+/// use nalgebra::{UnitQuaternion, Vector3};
+/// use haptic_toolbox::RotationDeadbandDetector;
+///
+/// let mut deadband_detector = RotationDeadbandDetector::new(0.1, UnitQuaternion::identity());
+///
+/// let small = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.05);
+/// let large = UnitQuaternion::from_axis_angle(&Vector3::z_axis(), 0.2);
+/// assert!(deadband_detector.is_in_deadband(&small));
+/// assert!(!deadband_detector.is_in_deadband(&large));
+/// assert!(deadband_detector.is_in_deadband(&(small * large)));
+/// ```
+#[derive(Debug)]
+pub struct RotationDeadbandDetector<N>
+where
+    N: RealField,
+{
+    prev_rotation: UnitQuaternion<N>,
+    threshold: N,
+}
+
+impl<N> RotationDeadbandDetector<N>
+where
+    N: RealField,
+{
+    /// Creates a new `RotationDeadbandDetector` with an absolute `threshold`
+    /// angle in radians.
+    pub fn new(threshold: N, initial_rotation: UnitQuaternion<N>) -> Self {
+        assert!(threshold >= N::zero(), "cannot assign a negative threshold");
+        Self {
+            prev_rotation: initial_rotation,
+            threshold,
+        }
+    }
+
+    /// Checks if `rotation` is in the deadband of the previously saved
+    /// rotation.
+    pub fn is_in_deadband(&mut self, rotation: &UnitQuaternion<N>) -> bool {
+        let in_deadband = self.prev_rotation.angle_to(rotation) <= self.threshold;
+        if !in_deadband {
+            self.prev_rotation = *rotation;
+        }
+        in_deadband
+    }
+
+    /// Checks if the rotation matrix `rotation` is in the deadband of the
+    /// previously saved rotation.
+    pub fn is_rotation_in_deadband(&mut self, rotation: &Rotation3<N>) -> bool {
+        self.is_in_deadband(&UnitQuaternion::from_rotation_matrix(rotation))
+    }
+
+    /// Sets the new threshold angle.
+    pub fn set_threshold(&mut self, threshold: N) {
+        assert!(threshold >= N::zero(), "cannot assign a negative threshold");
+        self.threshold = threshold;
+    }
+
+    /// Returns the current threshold angle.
+    pub fn threshold(&self) -> N {
+        self.threshold
+    }
+
+    /// Returns the rotation the following ones are compared to.
+    pub fn prev_rotation(&self) -> &UnitQuaternion<N> {
+        &self.prev_rotation
+    }
+
+    /// Sets the rotation the following ones should be compared to.
+    pub fn set_prev_rotation(&mut self, rotation: &UnitQuaternion<N>) {
+        self.prev_rotation = *rotation;
+    }
+}
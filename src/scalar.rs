@@ -0,0 +1,160 @@
+//! Scalar Controllers
+//!
+//! Single degree of freedom versions of the PD and PID controllers that only
+//! depend on `num-traits`. They reproduce exactly the same algorithm as their
+//! `nalgebra` based counterparts and are meant for small targets where a 1-DOF
+//! loop does not justify pulling in a linear algebra library.
+use num_traits::Float;
+
+/// 1-DOF PD controller, see `PD` for the vector version.
+pub struct ScalarPd<N>
+where
+    N: Float,
+{
+    k_p: N,
+    k_d: N,
+}
+
+impl<N> ScalarPd<N>
+where
+    N: Float,
+{
+    /// Creates a new scalar PD controller.
+    pub fn new(k_p: N, k_d: N) -> Self {
+        Self { k_p, k_d }
+    }
+
+    /// Calculates the force for tracking reference position and velocity.
+    pub fn calculate_force(&self, pos_ref: N, pos: N, vel_ref: N, vel: N) -> N {
+        (pos_ref - pos) * self.k_p + (vel_ref - vel) * self.k_d
+    }
+
+    /// Returns k p.
+    pub fn k_p(&self) -> N {
+        self.k_p
+    }
+
+    /// Returns k d.
+    pub fn k_d(&self) -> N {
+        self.k_d
+    }
+
+    /// Sets k p.
+    pub fn set_k_p(&mut self, k_p: N) {
+        self.k_p = k_p;
+    }
+
+    /// Sets k d.
+    pub fn set_k_d(&mut self, k_d: N) {
+        self.k_d = k_d;
+    }
+}
+
+/// 1-DOF PID controller, see `PID` for the vector version.
+///
+/// ```rust
+/// use haptic_toolbox::ScalarPid;
+///
+/// let mut pid = ScalarPid::<f64>::new(2.0, 0.5, 0.1);
+///
+/// // The integral error grows by the position error times dt in each step.
+/// let force = pid.calculate_force(1.0, 0.0, 0.0, 0.0, 0.1);
+/// assert!((force - (2.0 + 0.5 * 0.1)).abs() < 1e-12);
+/// let force = pid.calculate_force(1.0, 0.0, 1.0, 0.0, 0.1);
+/// assert!((force - (2.0 + 0.5 * 0.2 + 0.1)).abs() < 1e-12);
+/// ```
+pub struct ScalarPid<N>
+where
+    N: Float,
+{
+    k_p: N,
+    k_i: N,
+    k_d: N,
+
+    integral_error: N,
+}
+
+impl<N> ScalarPid<N>
+where
+    N: Float,
+{
+    /// Creates a new scalar PID controller.
+    pub fn new(k_p: N, k_i: N, k_d: N) -> Self {
+        Self {
+            k_p,
+            k_i,
+            k_d,
+            integral_error: N::zero(),
+        }
+    }
+
+    /// Calculates the force for tracking reference position and velocity.
+    pub fn calculate_force(&mut self, pos_ref: N, pos: N, vel_ref: N, vel: N, dt: N) -> N {
+        let error = pos_ref - pos;
+        self.integral_error = self.integral_error + error * dt;
+        let comp_p = error * self.k_p;
+        let comp_i = self.integral_error * self.k_i;
+        let comp_d = (vel_ref - vel) * self.k_d;
+
+        comp_p + comp_i + comp_d
+    }
+
+    /// Returns k p.
+    pub fn k_p(&self) -> N {
+        self.k_p
+    }
+
+    /// Returns k i.
+    pub fn k_i(&self) -> N {
+        self.k_i
+    }
+
+    /// Returns k d.
+    pub fn k_d(&self) -> N {
+        self.k_d
+    }
+
+    /// Sets k p.
+    pub fn set_k_p(&mut self, k_p: N) {
+        self.k_p = k_p;
+    }
+
+    /// Sets k i.
+    pub fn set_k_i(&mut self, k_i: N) {
+        self.k_i = k_i;
+    }
+
+    /// Sets k d.
+    pub fn set_k_d(&mut self, k_d: N) {
+        self.k_d = k_d;
+    }
+}
+
+#[cfg(all(test, feature = "nalgebra"))]
+mod tests {
+    use super::ScalarPid;
+    use crate::PID;
+    use nalgebra::{Vector1, U1};
+
+    #[test]
+    fn scalar_pid_matches_pid() {
+        let mut scalar_pid = ScalarPid::new(2.0, 0.5, 0.1);
+        let mut pid = PID::<f64, U1>::new(2.0, 0.5, 0.1);
+
+        for i in 0..100 {
+            let t = i as f64 * 0.001;
+            let (pos_ref, pos) = (t.sin(), 0.5 * t.cos());
+            let (vel_ref, vel) = (t.cos(), -0.5 * t.sin());
+
+            let scalar_force = scalar_pid.calculate_force(pos_ref, pos, vel_ref, vel, 0.001);
+            let force = pid.calculate_force(
+                &Vector1::new(pos_ref),
+                &Vector1::new(pos),
+                &Vector1::new(vel_ref),
+                &Vector1::new(vel),
+                0.001,
+            );
+            assert_eq!(scalar_force, force[0]);
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Scattering Transformation
+//!
+//! General scattering transformation with a symmetric positive definite
+//! impedance matrix `B`, of which the wave variable transformation with a
+//! scalar wave impedance is the special case `B = b * I`. Coupled impedances
+//! let the waves of multi-DoF channels match the coupled dynamics of the
+//! devices.
+//!
+//! With the Cholesky decomposition `B = L * L^T` the waves are
+//!
+//! `u = L^-1 * (force + B * vel) / sqrt(2)`
+//!
+//! `v = L^-1 * (force - B * vel) / sqrt(2)`
+//!
+//! so that `force^T * vel = (|u|^2 - |v|^2) / 2` holds like for the scalar
+//! wave impedance.
+use nalgebra::{
+    allocator::Allocator,
+    convert,
+    dimension::{Dim, DimName},
+    DefaultAllocator, MatrixN, RealField, VectorN,
+};
+
+use crate::WaveVariables;
+
+/// Scattering transformation with an impedance matrix.
+///
+/// The master sends `u` and the slave sends `v`, like for `WaveEncoder`.
+///
+/// ```rust
+/// use nalgebra::{Matrix2, Vector2};
+/// use haptic_toolbox::{ScatteringTransformation, WaveEncoder, WaveVariables};
+///
+/// // A diagonal impedance matrix is the wave transformation per axis.
+/// let scattering = ScatteringTransformation::new(Matrix2::<f64>::identity() * 2.0);
+/// let (force, vel) = (Vector2::new(1.0, -2.0), Vector2::new(0.1, 0.2));
+/// let u = scattering.encode_master(&force, &vel);
+/// assert!((u - WaveEncoder::new(2.0).encode_master(&force, &vel)).norm() < 1e-12);
+///
+/// // The power is preserved for coupled impedances as well.
+/// let scattering = ScatteringTransformation::new(Matrix2::new(2.0, 0.5, 0.5, 1.0));
+/// let waves = WaveVariables {
+///     u: scattering.encode_master(&force, &vel),
+///     v: scattering.encode_slave(&force, &vel),
+/// };
+/// let power = (waves.u.norm_squared() - waves.v.norm_squared()) / 2.0;
+/// assert!((power - force.dot(&vel)).abs() < 1e-12);
+///
+/// let (force_m, vel_m) = scattering.decode_master(&waves);
+/// assert!((force_m - force).norm() < 1e-12);
+/// assert!((vel_m - vel).norm() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ScatteringTransformation<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D, D> + Allocator<N, D>,
+{
+    impedance: MatrixN<N, D>,
+    l: MatrixN<N, D>,
+    l_inv: MatrixN<N, D>,
+}
+
+impl<N, D> ScatteringTransformation<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D, D> + Allocator<N, D>,
+{
+    /// Creates a new `ScatteringTransformation` with the symmetric positive
+    /// definite `impedance` matrix.
+    pub fn new(impedance: MatrixN<N, D>) -> Self {
+        let l = impedance
+            .clone()
+            .cholesky()
+            .expect("the impedance matrix has to be symmetric positive definite")
+            .unpack();
+        let l_inv = l
+            .clone()
+            .try_inverse()
+            .expect("the impedance matrix has to be symmetric positive definite");
+        Self {
+            impedance,
+            l,
+            l_inv,
+        }
+    }
+
+    /// Encodes the force and velocity of the master into the wave `u` sent to
+    /// the slave.
+    pub fn encode_master(&self, force_m: &VectorN<N, D>, vel_m: &VectorN<N, D>) -> VectorN<N, D> {
+        &self.l_inv * (force_m + &self.impedance * vel_m) / Self::sqrt_2()
+    }
+
+    /// Encodes the force and velocity of the slave into the wave `v` sent to
+    /// the master.
+    pub fn encode_slave(&self, force_s: &VectorN<N, D>, vel_s: &VectorN<N, D>) -> VectorN<N, D> {
+        &self.l_inv * (force_s - &self.impedance * vel_s) / Self::sqrt_2()
+    }
+
+    /// Decodes force and velocity `(force_m, vel_m)` of the master from the
+    /// sent wave `u` and the received wave `v`.
+    pub fn decode_master(&self, waves: &WaveVariables<N, D>) -> (VectorN<N, D>, VectorN<N, D>) {
+        self.decode(waves)
+    }
+
+    /// Decodes force and velocity `(force_s, vel_s)` of the slave from the
+    /// received wave `u` and the sent wave `v`.
+    pub fn decode_slave(&self, waves: &WaveVariables<N, D>) -> (VectorN<N, D>, VectorN<N, D>) {
+        self.decode(waves)
+    }
+
+    /// Returns the impedance matrix.
+    pub fn impedance(&self) -> &MatrixN<N, D> {
+        &self.impedance
+    }
+
+    fn decode(&self, waves: &WaveVariables<N, D>) -> (VectorN<N, D>, VectorN<N, D>) {
+        let force = &self.l * (&waves.u + &waves.v) / Self::sqrt_2();
+        let vel = self.l_inv.tr_mul(&(&waves.u - &waves.v)) / Self::sqrt_2();
+        (force, vel)
+    }
+
+    fn sqrt_2() -> N {
+        convert::<f64, N>(2.0).sqrt()
+    }
+}
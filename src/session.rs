@@ -0,0 +1,413 @@
+//! Teleoperation Session
+//!
+//! Composes the parts of a position-force teleoperator into a master node and
+//! a slave node. The master node deadband-compresses its position, displays
+//! the reconstructed environment force and keeps it passive with a
+//! `Stabilizer`. The slave node tracks the received master position with a
+//! `PID` controller and deadband-compresses the environment force. A
+//! `TeleopSession` connects both nodes with a `Transport` per direction, so
+//! that the whole loop runs with one `step` per tick. The nodes can also be
+//! built alone with `build_nodes` when master and slave run on different
+//! machines.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{
+    Channel, DeadbandDetector, DeadbandReconstructor, PairForces, PositionMessage,
+    ReconstructionStrategy, Stabilizer, PID,
+};
+
+/// Transports messages from one node to the other.
+pub trait Transport<N, T>
+where
+    N: RealField,
+{
+    /// Sends `message` at `time`.
+    fn send(&mut self, message: T, time: N);
+
+    /// Returns the newest message received until `time`, if there is a new
+    /// one.
+    fn receive(&mut self, time: N) -> Option<T>;
+}
+
+impl<N, T> Transport<N, T> for Channel<N, T>
+where
+    N: RealField,
+{
+    fn send(&mut self, message: T, time: N) {
+        Channel::send(self, message, time);
+    }
+
+    fn receive(&mut self, time: N) -> Option<T> {
+        Channel::receive(self, time)
+    }
+}
+
+/// Master side of a session.
+pub struct MasterNode<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    position_deadband: Option<DeadbandDetector<N, D>>,
+    force_reconstructor: DeadbandReconstructor<N, D>,
+    stabilizer: Option<Box<dyn Stabilizer<N, D>>>,
+}
+
+impl<N, D> MasterNode<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns the message to send for the master `state`, which is `None`
+    /// while the position is in the deadband.
+    pub fn send(&mut self, state: &PositionMessage<N, D>) -> Option<PositionMessage<N, D>> {
+        if let Some(deadband) = &mut self.position_deadband {
+            if deadband.is_in_deadband(&state.pos) {
+                return None;
+            }
+        }
+        Some(state.clone())
+    }
+
+    /// Returns the force to display at the master `state` with the force
+    /// `received` in this tick, if any.
+    pub fn display(
+        &mut self,
+        state: &PositionMessage<N, D>,
+        received: Option<&VectorN<N, D>>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let force = self.force_reconstructor.update(received, dt);
+        match &mut self.stabilizer {
+            Some(stabilizer) => stabilizer.stabilize_command(&state.pos, &state.vel, &force, dt),
+            None => force,
+        }
+    }
+}
+
+/// Slave side of a session.
+pub struct SlaveNode<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    controller: PID<N, D>,
+    master: PositionMessage<N, D>,
+    force_deadband: Option<DeadbandDetector<N, D>>,
+}
+
+impl<N, D> SlaveNode<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns the force commanded to the slave `state` with the master state
+    /// `received` in this tick, if any. Without a new master state the
+    /// previous one is held.
+    pub fn command(
+        &mut self,
+        state: &PositionMessage<N, D>,
+        received: Option<PositionMessage<N, D>>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        if let Some(master) = received {
+            self.master = master;
+        }
+        self.controller.calculate_force(
+            &self.master.pos,
+            &state.pos,
+            &self.master.vel,
+            &state.vel,
+            dt,
+        )
+    }
+
+    /// Returns the message to send for the `environment_force`, which is
+    /// `None` while the force is in the deadband.
+    pub fn send(&mut self, environment_force: &VectorN<N, D>) -> Option<VectorN<N, D>> {
+        if let Some(deadband) = &mut self.force_deadband {
+            if deadband.is_in_deadband(environment_force) {
+                return None;
+            }
+        }
+        Some(environment_force.clone())
+    }
+
+    /// Returns the controller.
+    pub fn controller(&self) -> &PID<N, D> {
+        &self.controller
+    }
+
+    /// Returns the controller mutably.
+    pub fn controller_mut(&mut self) -> &mut PID<N, D> {
+        &mut self.controller
+    }
+}
+
+/// Builder of a `TeleopSession`.
+pub struct TeleopSessionBuilder<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    controller: PID<N, D>,
+    dt: N,
+    stabilizer: Option<Box<dyn Stabilizer<N, D>>>,
+    position_deadband: Option<N>,
+    force_deadband: Option<N>,
+    force_reconstruction: ReconstructionStrategy,
+    forward: Option<Box<dyn Transport<N, PositionMessage<N, D>>>>,
+    backward: Option<Box<dyn Transport<N, VectorN<N, D>>>>,
+}
+
+impl<N, D> TeleopSessionBuilder<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Sets the stabilizer of the force displayed at the master, defaults to
+    /// none.
+    pub fn with_stabilizer<S>(mut self, stabilizer: S) -> Self
+    where
+        S: Stabilizer<N, D> + 'static,
+    {
+        self.stabilizer = Some(Box::new(stabilizer));
+        self
+    }
+
+    /// Sets the deadband threshold of the master position, defaults to
+    /// sending every sample.
+    pub fn with_position_deadband(mut self, threshold: N) -> Self {
+        self.position_deadband = Some(threshold);
+        self
+    }
+
+    /// Sets the deadband threshold of the environment force, defaults to
+    /// sending every sample.
+    pub fn with_force_deadband(mut self, threshold: N) -> Self {
+        self.force_deadband = Some(threshold);
+        self
+    }
+
+    /// Sets how the master reconstructs the environment force, defaults to
+    /// `ZeroOrderHold`.
+    pub fn with_force_reconstruction(mut self, strategy: ReconstructionStrategy) -> Self {
+        self.force_reconstruction = strategy;
+        self
+    }
+
+    /// Sets the transports from master to slave and back, defaults to
+    /// channels without delay and loss.
+    pub fn with_transport<F, B>(mut self, forward: F, backward: B) -> Self
+    where
+        F: Transport<N, PositionMessage<N, D>> + 'static,
+        B: Transport<N, VectorN<N, D>> + 'static,
+    {
+        self.forward = Some(Box::new(forward));
+        self.backward = Some(Box::new(backward));
+        self
+    }
+
+    /// Builds the master and the slave node alone with both devices starting
+    /// at rest in the origin, e.g. to run them on different machines. The
+    /// transports are not used, the messages of the nodes have to be
+    /// exchanged by the caller.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::{PositionMessage, TeleopSession, PID};
+    ///
+    /// let (mut master, mut slave) =
+    ///     TeleopSession::builder(PID::<f64, U1>::new(200.0, 0.0, 5.0), 0.001)
+    ///         .with_position_deadband(0.1)
+    ///         .build_nodes();
+    ///
+    /// let state = |pos| PositionMessage { pos: Vector1::new(pos), vel: Vector1::new(0.0) };
+    /// // The master moved out of its deadband, so its state is sent.
+    /// let message = master.send(&state(0.01));
+    /// assert!(message.is_some());
+    ///
+    /// let force = slave.command(&state(0.0), message, 0.001);
+    /// assert!((force[0] - 2.0f64).abs() < 1e-12);
+    ///
+    /// let received = slave.send(&Vector1::new(-1.0));
+    /// let force = master.display(&state(0.01), received.as_ref(), 0.001);
+    /// assert_eq!(force, Vector1::new(-1.0));
+    ///
+    /// // Within the deadband nothing is sent and the slave holds the position.
+    /// assert!(master.send(&state(0.0105)).is_none());
+    /// let force = slave.command(&state(0.0), None, 0.001);
+    /// assert!((force[0] - 2.0f64).abs() < 1e-12);
+    /// ```
+    pub fn build_nodes(self) -> (MasterNode<N, D>, SlaveNode<N, D>) {
+        let zeros = VectorN::<N, D>::zeros();
+        let rest = PositionMessage {
+            pos: zeros.clone(),
+            vel: zeros.clone(),
+        };
+        let master = MasterNode {
+            position_deadband: self
+                .position_deadband
+                .map(|threshold| DeadbandDetector::new(threshold, zeros.clone())),
+            force_reconstructor: DeadbandReconstructor::new(
+                zeros.clone(),
+                self.force_reconstruction,
+            ),
+            stabilizer: self.stabilizer,
+        };
+        let slave = SlaveNode {
+            controller: self.controller,
+            master: rest,
+            force_deadband: self
+                .force_deadband
+                .map(|threshold| DeadbandDetector::new(threshold, zeros)),
+        };
+        (master, slave)
+    }
+
+    /// Builds the session with both devices starting at rest in the origin.
+    pub fn build(mut self) -> TeleopSession<N, D> {
+        let zero = N::zero();
+        let forward = self
+            .forward
+            .take()
+            .unwrap_or_else(|| Box::new(Channel::new(zero, zero, zero, 0)));
+        let backward = self
+            .backward
+            .take()
+            .unwrap_or_else(|| Box::new(Channel::new(zero, zero, zero, 0)));
+        let dt = self.dt;
+        let (master, slave) = self.build_nodes();
+        TeleopSession {
+            master,
+            slave,
+            forward,
+            backward,
+            dt,
+            time: N::zero(),
+        }
+    }
+}
+
+/// Position-force teleoperation session.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{Channel, PositionMessage, TeleopSession, PID, TDPA};
+///
+/// let mut session = TeleopSession::builder(PID::<f64, U1>::new(200.0, 0.0, 5.0), 0.001)
+///     .with_stabilizer(TDPA::default())
+///     .with_force_deadband(0.1)
+///     .with_transport(Channel::new(0.01, 0.0, 0.0, 0), Channel::new(0.01, 0.0, 0.0, 1))
+///     .build();
+///
+/// let state = |pos| PositionMessage { pos: Vector1::new(pos), vel: Vector1::new(0.0) };
+/// for _ in 0..30 {
+///     let forces = session.step(&state(0.01), &state(0.0), &Vector1::new(-1.0));
+///     assert!(forces.master[0] <= 0.0);
+/// }
+///
+/// // After the delay the slave tracks the master and the master displays the
+/// // environment force.
+/// let forces = session.step(&state(0.01), &state(0.0), &Vector1::new(-1.0));
+/// assert!((forces.slave[0] - 2.0f64).abs() < 1e-12);
+/// assert_eq!(forces.master, Vector1::new(-1.0));
+/// ```
+pub struct TeleopSession<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    master: MasterNode<N, D>,
+    slave: SlaveNode<N, D>,
+    forward: Box<dyn Transport<N, PositionMessage<N, D>>>,
+    backward: Box<dyn Transport<N, VectorN<N, D>>>,
+    dt: N,
+    time: N,
+}
+
+impl<N, D> TeleopSession<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns a builder of a session whose slave tracks the master with
+    /// `controller`, which is stepped with `dt`.
+    pub fn builder(controller: PID<N, D>, dt: N) -> TeleopSessionBuilder<N, D> {
+        assert!(dt > N::zero(), "the time step has to be positive");
+        TeleopSessionBuilder {
+            controller,
+            dt,
+            stabilizer: None,
+            position_deadband: None,
+            force_deadband: None,
+            force_reconstruction: ReconstructionStrategy::default(),
+            forward: None,
+            backward: None,
+        }
+    }
+
+    /// Steps the session with the measured states of `master` and `slave`
+    /// and the measured `environment_force` on the slave, and returns the
+    /// forces to command to both devices.
+    pub fn step(
+        &mut self,
+        master: &PositionMessage<N, D>,
+        slave: &PositionMessage<N, D>,
+        environment_force: &VectorN<N, D>,
+    ) -> PairForces<N, D> {
+        if let Some(message) = self.master.send(master) {
+            self.forward.send(message, self.time);
+        }
+        if let Some(message) = self.slave.send(environment_force) {
+            self.backward.send(message, self.time);
+        }
+        self.time += self.dt;
+
+        let received_master = self.forward.receive(self.time);
+        let received_force = self.backward.receive(self.time);
+        PairForces {
+            master: self
+                .master
+                .display(master, received_force.as_ref(), self.dt),
+            slave: self.slave.command(slave, received_master, self.dt),
+        }
+    }
+
+    /// Returns the master node.
+    pub fn master(&self) -> &MasterNode<N, D> {
+        &self.master
+    }
+
+    /// Returns the master node mutably.
+    pub fn master_mut(&mut self) -> &mut MasterNode<N, D> {
+        &mut self.master
+    }
+
+    /// Returns the slave node.
+    pub fn slave(&self) -> &SlaveNode<N, D> {
+        &self.slave
+    }
+
+    /// Returns the slave node mutably.
+    pub fn slave_mut(&mut self) -> &mut SlaveNode<N, D> {
+        &mut self.slave
+    }
+
+    /// Returns the time of the session.
+    pub fn time(&self) -> N {
+        self.time
+    }
+}
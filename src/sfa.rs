@@ -0,0 +1,123 @@
+//! Successive Force Augmentation (SFA)
+//!
+//! Instead of injecting damping when the port becomes active, the received
+//! force is scaled by a feedback gain. The gain is cut whenever the full force
+//! would make the observed energy negative, and augmented gradually back to
+//! one while the port is passive. Slowly varying forces, e.g. the steady
+//! contact force with a stiff wall, are therefore displayed with their full
+//! magnitude, which brute force damping injection does not achieve.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+use crate::PassivityObserver;
+
+/// Successive force augmentation for a port that displays a received force.
+///
+/// The energy `force · vel * dt` flows into the port like for the
+/// `PassivityObserver`.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::SFA;
+///
+/// let mut sfa = SFA::new(10.0);
+/// let dt = 0.001;
+///
+/// // A force that pushes along the motion would output energy, so the gain is
+/// // cut as soon as the absorbed energy is spent.
+/// sfa.calculate_force(&Vector1::new(-1.0), &Vector1::new(-0.1), dt);
+/// let force = sfa.calculate_force(&Vector1::new(2.0), &Vector1::new(-0.1), dt);
+/// assert_eq!(force[0], 1.0);
+/// assert!(sfa.gain() < 1.0);
+/// assert!(sfa.observer().energy() >= 0.0);
+///
+/// // Afterwards the gain is augmented back to one within 0.1 s.
+/// for _ in 0..100 {
+///     sfa.calculate_force(&Vector1::new(1.0), &Vector1::new(0.0), dt);
+/// }
+/// assert_eq!(sfa.calculate_force(&Vector1::new(1.0), &Vector1::new(0.0), dt)[0], 1.0);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SFA<N>
+where
+    N: RealField,
+{
+    augmentation_rate: N,
+    gain: N,
+    observer: PassivityObserver<N>,
+}
+
+impl<N> SFA<N>
+where
+    N: RealField,
+{
+    /// Creates a new `SFA` whose gain is augmented by `augmentation_rate` per
+    /// second, starting with the full force.
+    pub fn new(augmentation_rate: N) -> Self {
+        assert!(
+            augmentation_rate > N::zero(),
+            "the augmentation rate has to be positive"
+        );
+        Self {
+            augmentation_rate,
+            gain: N::one(),
+            observer: PassivityObserver::new(),
+        }
+    }
+
+    /// Returns the force to display for the `received_force` at the port
+    /// velocity `vel`.
+    pub fn calculate_force<D>(
+        &mut self,
+        received_force: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        // Cut the gain so that the energy after this sample stays
+        // non-negative.
+        let energy = received_force.dot(vel) * dt;
+        if energy < N::zero() {
+            let available = self.observer.energy().max(N::zero());
+            self.gain = self.gain.min(available / -energy);
+        }
+
+        let force = received_force * self.gain;
+        self.observer.observe(&force, vel, dt);
+        self.gain = (self.gain + self.augmentation_rate * dt).min(N::one());
+        force
+    }
+
+    /// Resets the gain to one and the observed energy.
+    pub fn reset(&mut self) {
+        self.gain = N::one();
+        self.observer.reset();
+    }
+
+    /// Returns the gain for the next sample.
+    pub fn gain(&self) -> N {
+        self.gain
+    }
+
+    /// Returns the passivity observer of the port.
+    pub fn observer(&self) -> &PassivityObserver<N> {
+        &self.observer
+    }
+
+    /// Returns the augmentation rate.
+    pub fn augmentation_rate(&self) -> N {
+        self.augmentation_rate
+    }
+
+    /// Sets the rate per second the gain is augmented by while the port is
+    /// passive.
+    pub fn set_augmentation_rate(&mut self, augmentation_rate: N) {
+        assert!(
+            augmentation_rate > N::zero(),
+            "the augmentation rate has to be positive"
+        );
+        self.augmentation_rate = augmentation_rate;
+    }
+}
@@ -0,0 +1,168 @@
+//! Haptic Shared Control
+//!
+//! Blends the input of the operator with the force of an autonomous guidance,
+//! `(1 - λ) * force_operator + λ * force_guidance`. The arbitration weight `λ`
+//! between `0` and `1` is either constant or depends on the state, e.g. on the
+//! distance to the goal or on the confidence of the automation. A rate limit
+//! lets the weight follow the arbitration smoothly, so that the level of
+//! assistance can be changed during operation without force jumps.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+use std::marker::PhantomData;
+
+/// Arbitration weight of the guidance.
+///
+/// Scalars are constant weights, closures calculate the weight from position
+/// and velocity.
+pub trait Arbitration<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns the weight of the guidance at position `pos` with velocity
+    /// `vel`.
+    fn weight(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>) -> N;
+}
+
+impl<D> Arbitration<f32, D> for f32
+where
+    D: Dim,
+    DefaultAllocator: Allocator<f32, D>,
+{
+    fn weight(&mut self, _pos: &VectorN<f32, D>, _vel: &VectorN<f32, D>) -> f32 {
+        *self
+    }
+}
+
+impl<D> Arbitration<f64, D> for f64
+where
+    D: Dim,
+    DefaultAllocator: Allocator<f64, D>,
+{
+    fn weight(&mut self, _pos: &VectorN<f64, D>, _vel: &VectorN<f64, D>) -> f64 {
+        *self
+    }
+}
+
+impl<N, D, F> Arbitration<N, D> for F
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    F: FnMut(&VectorN<N, D>, &VectorN<N, D>) -> N,
+{
+    fn weight(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>) -> N {
+        self(pos, vel)
+    }
+}
+
+/// Blends the operator force with a guidance force.
+///
+/// ```rust
+/// use nalgebra::{Vector1, VectorN, U1};
+/// use haptic_toolbox::SharedControl;
+///
+/// // Assist more the closer the tool is to the goal at 0.
+/// let arbitration = |pos: &VectorN<f64, U1>, _vel: &VectorN<f64, U1>| 1.0 - pos.norm().min(1.0);
+/// let mut shared_control = SharedControl::<f64, U1, _>::new(arbitration);
+///
+/// let (operator, guidance) = (Vector1::new(2.0), Vector1::new(-2.0));
+/// let pos = Vector1::new(0.75);
+/// let force = shared_control.blend(&operator, &guidance, &pos, &Vector1::new(0.0), 0.001);
+/// assert_eq!(force, Vector1::new(1.0));
+///
+/// // With a rate limit the weight only approaches the arbitration.
+/// shared_control.set_weight_rate(Some(1.0));
+/// let force = shared_control.blend(&operator, &guidance, &Vector1::new(0.0), &Vector1::new(0.0), 0.1);
+/// assert!((shared_control.weight() - 0.35f64).abs() < 1e-12);
+/// assert!((force[0] - 0.6f64).abs() < 1e-12);
+/// ```
+pub struct SharedControl<N, D, A>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    A: Arbitration<N, D>,
+{
+    arbitration: A,
+    weight: Option<N>,
+    weight_rate: Option<N>,
+    _phantom: PhantomData<D>,
+}
+
+impl<N, D, A> SharedControl<N, D, A>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    A: Arbitration<N, D>,
+{
+    /// Creates a new `SharedControl` whose guidance is weighted by
+    /// `arbitration`.
+    pub fn new(arbitration: A) -> Self {
+        Self {
+            arbitration,
+            weight: None,
+            weight_rate: None,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Blends the `operator` force with the `guidance` force at position
+    /// `pos` with velocity `vel`.
+    pub fn blend(
+        &mut self,
+        operator: &VectorN<N, D>,
+        guidance: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let target = self
+            .arbitration
+            .weight(pos, vel)
+            .max(N::zero())
+            .min(N::one());
+        let weight = match (self.weight, self.weight_rate) {
+            (Some(weight), Some(weight_rate)) => {
+                let step = weight_rate * dt;
+                weight + (target - weight).max(-step).min(step)
+            }
+            _ => target,
+        };
+        self.weight = Some(weight);
+        operator * (N::one() - weight) + guidance * weight
+    }
+
+    /// Returns the weight of the guidance of the latest blend.
+    pub fn weight(&self) -> N {
+        self.weight.unwrap_or_else(N::zero)
+    }
+
+    /// Returns the arbitration.
+    pub fn arbitration(&self) -> &A {
+        &self.arbitration
+    }
+
+    /// Sets the arbitration, which the weight follows with the rate limit.
+    pub fn set_arbitration(&mut self, arbitration: A) {
+        self.arbitration = arbitration;
+    }
+
+    /// Returns the maximal rate of the weight.
+    pub fn weight_rate(&self) -> Option<N> {
+        self.weight_rate
+    }
+
+    /// Sets the maximal rate per second the weight changes with, defaults to
+    /// `None` for following the arbitration immediately.
+    pub fn set_weight_rate(&mut self, weight_rate: Option<N>) {
+        if let Some(weight_rate) = weight_rate {
+            assert!(
+                weight_rate > N::zero(),
+                "the weight rate has to be positive"
+            );
+        }
+        self.weight_rate = weight_rate;
+    }
+}
@@ -0,0 +1,29 @@
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+/// Sign convention for the force returned by a controller.
+///
+/// Haptic frameworks disagree on the direction of the feedback force. By
+/// default controllers return the force pointing towards the reference
+/// (`Sign::Positive`). `Sign::Negative` flips the returned force while leaving
+/// the internal state of the controller untouched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Sign {
+    #[default]
+    Positive,
+    Negative,
+}
+
+impl Sign {
+    /// Applies the sign convention to `force`.
+    pub fn apply<N, D>(self, force: VectorN<N, D>) -> VectorN<N, D>
+    where
+        N: RealField,
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        match self {
+            Sign::Positive => force,
+            Sign::Negative => -force,
+        }
+    }
+}
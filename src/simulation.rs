@@ -0,0 +1,339 @@
+//! Teleoperation Simulation
+//!
+//! Steps a complete teleoperation loop without hardware. The master and the
+//! slave are `DeviceModel`s, connected by a bilateral architecture with any
+//! `Stabilizer` and two channels, which delay the messages with a `DelayLine`
+//! and lose some of them. The slave interacts with an environment made of
+//! virtual fixtures. The random numbers are seeded, so runs are reproducible
+//! and stabilizer configurations can be compared deterministically.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::delay_line::Random;
+use crate::{
+    DelayLine, DelayProfile, DeviceModel, PositionMessage, PositionPosition, Stabilizer,
+    VirtualFixture,
+};
+
+#[derive(Clone, Debug)]
+struct Packet<N, T> {
+    sent_time: N,
+    message: T,
+}
+
+/// Channel that delays messages with a `DelayLine` and loses them with a
+/// loss probability.
+///
+/// Messages that arrive after a newer message are discarded.
+///
+/// ```rust
+/// use haptic_toolbox::Channel;
+///
+/// let mut channel = Channel::new(0.01, 0.0, 0.0, 0);
+/// channel.send(1, 0.0);
+/// assert_eq!(channel.receive(0.005), None);
+/// assert_eq!(channel.receive(0.01), Some(1));
+/// assert_eq!(channel.receive(0.02), None);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Channel<N, T>
+where
+    N: RealField,
+{
+    delay_line: DelayLine<N, Packet<N, T>>,
+    loss: N,
+    random: Random,
+    last_sent_time: Option<N>,
+}
+
+impl<N, T> Channel<N, T>
+where
+    N: RealField,
+{
+    /// Creates a new `Channel` that delays messages by `delay` plus up to
+    /// `jitter` seconds and loses them with the probability `loss`. The
+    /// random numbers are generated from `seed`.
+    pub fn new(delay: N, jitter: N, loss: N, seed: u64) -> Self {
+        assert!(jitter >= N::zero(), "the jitter must not be negative");
+        let profile = DelayProfile::Stochastic {
+            min: delay,
+            max: delay + jitter,
+            seed,
+        };
+        Self::with_delay_profile(profile, loss, seed)
+    }
+
+    /// Creates a new `Channel` that delays messages by `profile`, e.g. by a
+    /// recorded delay trace, and loses them with the probability `loss`. The
+    /// losses are drawn from `seed`.
+    pub fn with_delay_profile(profile: DelayProfile<N>, loss: N, seed: u64) -> Self {
+        assert!(
+            loss >= N::zero() && loss <= N::one(),
+            "the loss has to be in [0, 1]"
+        );
+        Self {
+            delay_line: DelayLine::new(profile),
+            loss,
+            random: Random::new(!seed),
+            last_sent_time: None,
+        }
+    }
+
+    /// Sends `message` at `time`.
+    pub fn send(&mut self, message: T, time: N) {
+        if self.random.uniform::<N>() < self.loss {
+            return;
+        }
+        let packet = Packet {
+            sent_time: time,
+            message,
+        };
+        self.delay_line.push(packet, time);
+    }
+
+    /// Returns the newest message that arrived until `time`, if it is newer
+    /// than the previously received one.
+    pub fn receive(&mut self, time: N) -> Option<T> {
+        let mut newest = None;
+        while let Some(packet) = self.delay_line.pop(time) {
+            let is_newer = match self.last_sent_time {
+                Some(last_sent_time) => packet.sent_time > last_sent_time,
+                None => true,
+            };
+            if is_newer {
+                self.last_sent_time = Some(packet.sent_time);
+                newest = Some(packet.message);
+            }
+        }
+        newest
+    }
+
+    /// Returns the number of messages that are in flight.
+    pub fn in_flight(&self) -> usize {
+        self.delay_line.len()
+    }
+
+    /// Returns the delay profile.
+    pub fn delay_profile(&self) -> &DelayProfile<N> {
+        self.delay_line.profile()
+    }
+
+    /// Returns the loss probability.
+    pub fn loss(&self) -> N {
+        self.loss
+    }
+}
+
+fn state<N, D>(device: &DeviceModel<N, D>) -> PositionMessage<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    PositionMessage {
+        pos: device.pos().clone(),
+        vel: device.vel().clone(),
+    }
+}
+
+/// State of the loop after a step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimulationStep<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Simulated time.
+    pub time: N,
+    /// State of the master.
+    pub master: PositionMessage<N, D>,
+    /// State of the slave.
+    pub slave: PositionMessage<N, D>,
+    /// Force commanded to the master.
+    pub master_force: VectorN<N, D>,
+    /// Force commanded to the slave.
+    pub slave_force: VectorN<N, D>,
+    /// Force of the environment on the slave.
+    pub environment_force: VectorN<N, D>,
+}
+
+/// Closed teleoperation loop.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{
+///     Channel, DeviceModel, PositionPosition, Simulation, VirtualWall, PD, PID, TDPA,
+/// };
+///
+/// let architecture = PositionPosition::new(
+///     PD::<f64, U1>::new(200.0, 5.0),
+///     PID::new(200.0, 0.0, 5.0),
+///     TDPA::default(),
+/// );
+/// let mut simulation = Simulation::new(
+///     architecture,
+///     Channel::new(0.02, 0.005, 0.1, 1),
+///     Channel::new(0.02, 0.005, 0.1, 2),
+///     0.001,
+/// );
+/// simulation.push_fixture(VirtualWall::new(Vector1::new(-1.0), -0.05, 5000.0, 0.0));
+/// simulation.set_slave_device(DeviceModel::new(2.0, 5.0));
+///
+/// // The operator pushes the master, the slave follows until the wall.
+/// let mut step = None;
+/// for _ in 0..3000 {
+///     step = Some(simulation.step(&Vector1::new(5.0)));
+/// }
+/// let step = step.unwrap();
+/// assert!((step.slave.pos[0] - 0.05f64).abs() < 0.01);
+/// assert!(step.environment_force[0] < 0.0);
+/// ```
+pub struct Simulation<N, D, S>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    architecture: PositionPosition<N, D, S>,
+    forward: Channel<N, PositionMessage<N, D>>,
+    backward: Channel<N, PositionMessage<N, D>>,
+    environment: Vec<Box<dyn VirtualFixture<N, D>>>,
+    master: DeviceModel<N, D>,
+    slave: DeviceModel<N, D>,
+    received_master: PositionMessage<N, D>,
+    received_slave: PositionMessage<N, D>,
+    dt: N,
+    time: N,
+}
+
+impl<N, D, S> Simulation<N, D, S>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    /// Creates a new `Simulation` of `architecture` with the `forward`
+    /// channel from master to slave and the `backward` channel, which is
+    /// stepped with `dt`. Both devices have a mass of `1` and a damping of
+    /// `1` and rest at the origin, the environment is free space.
+    pub fn new(
+        architecture: PositionPosition<N, D, S>,
+        forward: Channel<N, PositionMessage<N, D>>,
+        backward: Channel<N, PositionMessage<N, D>>,
+        dt: N,
+    ) -> Self {
+        assert!(dt > N::zero(), "the time step has to be positive");
+        let master = DeviceModel::new(N::one(), N::one());
+        let slave = DeviceModel::new(N::one(), N::one());
+        Self {
+            architecture,
+            forward,
+            backward,
+            environment: Vec::new(),
+            received_master: state(&master),
+            received_slave: state(&slave),
+            master,
+            slave,
+            dt,
+            time: N::zero(),
+        }
+    }
+
+    /// Adds a fixture to the environment of the slave.
+    pub fn push_fixture<F>(&mut self, fixture: F)
+    where
+        F: VirtualFixture<N, D> + 'static,
+    {
+        self.environment.push(Box::new(fixture));
+    }
+
+    /// Returns the model of the master.
+    pub fn master_device(&self) -> &DeviceModel<N, D> {
+        &self.master
+    }
+
+    /// Sets the model of the master, whose state becomes the received state
+    /// at the slave.
+    pub fn set_master_device(&mut self, master: DeviceModel<N, D>) {
+        self.received_master = state(&master);
+        self.master = master;
+    }
+
+    /// Returns the model of the slave.
+    pub fn slave_device(&self) -> &DeviceModel<N, D> {
+        &self.slave
+    }
+
+    /// Sets the model of the slave, whose state becomes the received state
+    /// at the master.
+    pub fn set_slave_device(&mut self, slave: DeviceModel<N, D>) {
+        self.received_slave = state(&slave);
+        self.slave = slave;
+    }
+
+    /// Steps the loop with the force of the operator on the master.
+    pub fn step(&mut self, operator_force: &VectorN<N, D>) -> SimulationStep<N, D> {
+        let dt = self.dt;
+        self.forward.send(state(&self.master), self.time);
+        self.backward.send(state(&self.slave), self.time);
+        self.time += dt;
+        if let Some(master) = self.forward.receive(self.time) {
+            self.received_master = master;
+        }
+        if let Some(slave) = self.backward.receive(self.time) {
+            self.received_slave = slave;
+        }
+
+        let master = state(&self.master);
+        let slave = state(&self.slave);
+        let master_force =
+            self.architecture
+                .calculate_master_force(&master, &self.received_slave, dt);
+        let slave_force =
+            self.architecture
+                .calculate_slave_force(&slave, &self.received_master, dt);
+        let environment_force = self
+            .environment
+            .iter_mut()
+            .fold(VectorN::zeros(), |force: VectorN<N, D>, fixture| {
+                force + fixture.force(&slave.pos, &slave.vel, dt)
+            });
+
+        self.master.step(&(operator_force + &master_force), dt);
+        self.slave.step(&(&slave_force + &environment_force), dt);
+        SimulationStep {
+            time: self.time,
+            master: state(&self.master),
+            slave: state(&self.slave),
+            master_force,
+            slave_force,
+            environment_force,
+        }
+    }
+
+    /// Returns the simulated time.
+    pub fn time(&self) -> N {
+        self.time
+    }
+
+    /// Returns the time step.
+    pub fn dt(&self) -> N {
+        self.dt
+    }
+
+    /// Returns the architecture.
+    pub fn architecture(&self) -> &PositionPosition<N, D, S> {
+        &self.architecture
+    }
+
+    /// Returns the architecture mutably.
+    pub fn architecture_mut(&mut self) -> &mut PositionPosition<N, D, S> {
+        &mut self.architecture
+    }
+}
@@ -0,0 +1,86 @@
+//! Slew Rate Limiter
+//!
+//! Limits how fast a signal may change per axis, which keeps force steps from
+//! exciting resonances of the device or tripping the protection of the
+//! amplifier.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+/// Limits the rate of change of a signal.
+///
+/// ```rust
+/// use haptic_toolbox::{SlewLimiter, PD};
+/// use nalgebra::{Vector2, U2};
+///
+/// // At most 100 N/s along x and 10 N/s along y.
+/// let mut slew_limiter = SlewLimiter::new(Vector2::new(100.0, 10.0));
+/// let pd = PD::<f64, U2>::new(10.0, 0.0);
+///
+/// let (pos_ref, zeros) = (Vector2::new(0.1, 0.1), Vector2::zeros());
+/// let force = pd.calculate_force(&pos_ref, &zeros, &zeros, &zeros);
+/// assert!((slew_limiter.limit(&force, 0.001) - Vector2::new(0.1, 0.01)).norm() < 1e-12);
+/// assert!((slew_limiter.limit(&force, 0.001) - Vector2::new(0.2, 0.02)).norm() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SlewLimiter<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    max_rate: VectorN<N, D>,
+    value: VectorN<N, D>,
+}
+
+impl<N, D> SlewLimiter<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `SlewLimiter` with the maximum rate of change per axis,
+    /// starting at zero.
+    pub fn new(max_rate: VectorN<N, D>) -> Self {
+        assert!(
+            max_rate.iter().all(|max_rate| *max_rate >= N::zero()),
+            "the maximum rate must not be negative"
+        );
+        Self {
+            value: max_rate.map(|_| N::zero()),
+            max_rate,
+        }
+    }
+
+    /// Moves the output towards `target` as far as the maximum rate allows
+    /// within `dt` and returns it.
+    pub fn limit(&mut self, target: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        let step = (target - &self.value).zip_map(&self.max_rate, |diff, max_rate| {
+            diff.max(-max_rate * dt).min(max_rate * dt)
+        });
+        self.value += step;
+        self.value.clone()
+    }
+
+    /// Returns the last output.
+    pub fn value(&self) -> &VectorN<N, D> {
+        &self.value
+    }
+
+    /// Sets the output the next call to `limit` starts from.
+    pub fn set_value(&mut self, value: VectorN<N, D>) {
+        self.value = value;
+    }
+
+    /// Returns the maximum rate of change per axis.
+    pub fn max_rate(&self) -> &VectorN<N, D> {
+        &self.max_rate
+    }
+
+    /// Sets the maximum rate of change per axis.
+    pub fn set_max_rate(&mut self, max_rate: VectorN<N, D>) {
+        assert!(
+            max_rate.iter().all(|max_rate| *max_rate >= N::zero()),
+            "the maximum rate must not be negative"
+        );
+        self.max_rate = max_rate;
+    }
+}
@@ -0,0 +1,163 @@
+//! Sliding-Mode Controller
+//!
+//! A sliding-mode controller drives the tracking error onto the sliding
+//! surface `s = de + lambda * e`, on which it decays exponentially with the
+//! slope `lambda`. The switching force `eta * sign(s)` reaches the surface
+//! despite unknown slave dynamics as long as `eta` exceeds their effect,
+//! which makes the controller robust where a PD or PID would need a model.
+//! Since the discontinuous switching chatters on sampled hardware, it is
+//! smoothed to `eta * s / phi` within a boundary layer of width `phi`.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+use std::marker::PhantomData;
+
+/// Sliding-mode tracking controller with boundary-layer smoothing.
+///
+/// ```rust
+/// use nalgebra::{Vector2, U2};
+/// use haptic_toolbox::SlidingMode;
+///
+/// let controller = SlidingMode::<f64, U2>::new(10.0, 5.0, 0.1);
+///
+/// let zeros = Vector2::zeros();
+/// // Far from the surface the full switching force is applied.
+/// let force = controller.calculate_force(&Vector2::new(0.1, 0.0), &zeros, &zeros, &zeros);
+/// assert_eq!(force, Vector2::new(5.0, 0.0));
+///
+/// // Within the boundary layer the force is smoothed.
+/// let force = controller.calculate_force(&Vector2::new(0.005, 0.0), &zeros, &zeros, &zeros);
+/// assert!((force - Vector2::new(2.5, 0.0)).norm() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SlidingMode<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    slope: N,
+    switching_gain: N,
+    boundary_layer: N,
+    linear_gain: N,
+    _phantom: PhantomData<D>,
+}
+
+impl<N, D> SlidingMode<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `SlidingMode` controller with the `slope` of the
+    /// sliding surface, the `switching_gain` and the width of the
+    /// `boundary_layer`. Without a boundary layer the switching force is
+    /// discontinuous.
+    pub fn new(slope: N, switching_gain: N, boundary_layer: N) -> Self {
+        assert!(slope > N::zero(), "the slope has to be positive");
+        assert!(
+            switching_gain >= N::zero(),
+            "the switching gain must not be negative"
+        );
+        assert!(
+            boundary_layer >= N::zero(),
+            "the boundary layer must not be negative"
+        );
+        Self {
+            slope,
+            switching_gain,
+            boundary_layer,
+            linear_gain: N::zero(),
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Returns the sliding variable `s = de + lambda * e` for tracking the
+    /// reference position and velocity.
+    pub fn sliding_variable(
+        &self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+    ) -> VectorN<N, D> {
+        vel_ref - vel + (pos_ref - pos) * self.slope
+    }
+
+    /// Calculates the force for tracking reference position and velocity.
+    pub fn calculate_force(
+        &self,
+        pos_ref: &VectorN<N, D>,
+        pos: &VectorN<N, D>,
+        vel_ref: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+    ) -> VectorN<N, D> {
+        let s = self.sliding_variable(pos_ref, pos, vel_ref, vel);
+        let boundary_layer = self.boundary_layer;
+        let switching = s.map(|s| {
+            if boundary_layer > N::zero() {
+                (s / boundary_layer).max(-N::one()).min(N::one())
+            } else if s > N::zero() {
+                N::one()
+            } else if s < N::zero() {
+                -N::one()
+            } else {
+                N::zero()
+            }
+        });
+        switching * self.switching_gain + s * self.linear_gain
+    }
+
+    /// Returns the slope of the sliding surface.
+    pub fn slope(&self) -> N {
+        self.slope
+    }
+
+    /// Sets the slope the error decays with on the sliding surface.
+    pub fn set_slope(&mut self, slope: N) {
+        assert!(slope > N::zero(), "the slope has to be positive");
+        self.slope = slope;
+    }
+
+    /// Returns the switching gain.
+    pub fn switching_gain(&self) -> N {
+        self.switching_gain
+    }
+
+    /// Sets the switching gain, which has to exceed the force of the unknown
+    /// dynamics.
+    pub fn set_switching_gain(&mut self, switching_gain: N) {
+        assert!(
+            switching_gain >= N::zero(),
+            "the switching gain must not be negative"
+        );
+        self.switching_gain = switching_gain;
+    }
+
+    /// Returns the width of the boundary layer.
+    pub fn boundary_layer(&self) -> N {
+        self.boundary_layer
+    }
+
+    /// Sets the width of the boundary layer the switching is smoothed in.
+    pub fn set_boundary_layer(&mut self, boundary_layer: N) {
+        assert!(
+            boundary_layer >= N::zero(),
+            "the boundary layer must not be negative"
+        );
+        self.boundary_layer = boundary_layer;
+    }
+
+    /// Returns the linear gain.
+    pub fn linear_gain(&self) -> N {
+        self.linear_gain
+    }
+
+    /// Sets the gain of the force proportional to the sliding variable, which
+    /// speeds up reaching the surface, defaults to `0`.
+    pub fn set_linear_gain(&mut self, linear_gain: N) {
+        assert!(
+            linear_gain >= N::zero(),
+            "the linear gain must not be negative"
+        );
+        self.linear_gain = linear_gain;
+    }
+}
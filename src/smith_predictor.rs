@@ -0,0 +1,212 @@
+//! Smith Predictor
+//!
+//! A dead time in the position loop makes a controller react to where the
+//! plant was instead of where it is. The Smith predictor runs a model of the
+//! plant with the commanded force and adds the difference between the model
+//! now and the model delayed by the known delay to the measurement. If the
+//! model matches the plant, the controller sees the undelayed position and can
+//! be tuned like without delay. It is meant for moderate and nearly constant
+//! delays, since a wrong delay or model reintroduces the mismatch.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+use num_traits::Zero;
+use std::collections::VecDeque;
+
+/// Smith predictor with a mass-damper plant model.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{SmithPredictor, PD};
+/// use std::collections::VecDeque;
+///
+/// let (mass, damping, dt) = (0.5, 2.0, 0.001);
+/// let mut smith = SmithPredictor::<f64, U1>::new(mass, damping, 0.05);
+/// let pd = PD::<f64, U1>::new(100.0, 5.0);
+///
+/// let (mut pos, mut vel) = (Vector1::new(0.0), Vector1::new(0.0));
+/// let mut measurements = VecDeque::from(vec![(pos, vel); 50]);
+/// for _ in 0..1000 {
+///     // The measurement arrives 50 samples late.
+///     measurements.push_back((pos, vel));
+///     let (measured_pos, measured_vel) = measurements.pop_front().unwrap();
+///     let (pred_pos, pred_vel) = smith.feedback(&measured_pos, &measured_vel);
+///
+///     // The feedback is the undelayed state, since the model is exact.
+///     assert!((pred_pos - pos).norm() < 1e-12);
+///
+///     let force = pd.calculate_force(&Vector1::new(0.1), &pred_pos, &Vector1::new(0.0), &pred_vel);
+///     smith.update(&force, dt);
+///     vel += (force - vel * damping) / mass * dt;
+///     pos += vel * dt;
+/// }
+/// assert!((pos[0] - 0.1f64).abs() < 1e-3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SmithPredictor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    mass: N,
+    damping: N,
+    delay: N,
+    time: N,
+    last_dt: N,
+    pos: VectorN<N, D>,
+    vel: VectorN<N, D>,
+    history: VecDeque<ModelState<N, D>>,
+}
+
+#[derive(Clone, Debug)]
+struct ModelState<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    time: N,
+    pos: VectorN<N, D>,
+    vel: VectorN<N, D>,
+}
+
+impl<N, D> SmithPredictor<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `SmithPredictor` for a plant with `mass` and `damping`
+    /// whose measurement is delayed by `delay` in seconds, starting at rest at
+    /// zero.
+    pub fn new(mass: N, damping: N, delay: N) -> Self {
+        assert!(mass > N::zero(), "the mass has to be positive");
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        assert!(delay >= N::zero(), "the delay must not be negative");
+        let mut smith_predictor = Self {
+            mass,
+            damping,
+            delay,
+            time: N::zero(),
+            last_dt: N::zero(),
+            pos: Zero::zero(),
+            vel: Zero::zero(),
+            history: VecDeque::new(),
+        };
+        smith_predictor.reset(Zero::zero(), Zero::zero());
+        smith_predictor
+    }
+}
+
+impl<N, D> SmithPredictor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Resets the model to the position `pos` and velocity `vel`, as if it had
+    /// been there for longer than the delay.
+    pub fn reset(&mut self, pos: VectorN<N, D>, vel: VectorN<N, D>) {
+        self.time = N::zero();
+        self.history.clear();
+        self.history.push_back(ModelState {
+            time: N::zero(),
+            pos: pos.clone(),
+            vel: vel.clone(),
+        });
+        self.pos = pos;
+        self.vel = vel;
+    }
+
+    /// Integrates the model with the `force` commanded for the next `dt`.
+    pub fn update(&mut self, force: &VectorN<N, D>, dt: N) {
+        self.vel += (force - &self.vel * self.damping) * (dt / self.mass);
+        self.pos += &self.vel * dt;
+        self.time += dt;
+        self.last_dt = dt;
+        self.history.push_back(ModelState {
+            time: self.time,
+            pos: self.pos.clone(),
+            vel: self.vel.clone(),
+        });
+
+        // Keep the latest state at or before the delayed time.
+        let delayed_time = self.delayed_time();
+        while self.history.len() > 1 && self.history[1].time <= delayed_time {
+            self.history.pop_front();
+        }
+    }
+
+    /// Returns the predicted position and velocity `(pos, vel)` for the
+    /// measured and delayed position and velocity.
+    pub fn feedback(
+        &self,
+        measured_pos: &VectorN<N, D>,
+        measured_vel: &VectorN<N, D>,
+    ) -> (VectorN<N, D>, VectorN<N, D>) {
+        let delayed_time = self.delayed_time();
+        let delayed = self
+            .history
+            .iter()
+            .rev()
+            .find(|state| state.time <= delayed_time)
+            .unwrap_or(&self.history[0]);
+        (
+            measured_pos + &self.pos - &delayed.pos,
+            measured_vel + &self.vel - &delayed.vel,
+        )
+    }
+
+    fn delayed_time(&self) -> N {
+        // Half a sample of slack keeps rounding errors from picking the
+        // previous sample.
+        self.time - self.delay + self.last_dt / (N::one() + N::one())
+    }
+
+    /// Returns the position of the model.
+    pub fn pos(&self) -> &VectorN<N, D> {
+        &self.pos
+    }
+
+    /// Returns the velocity of the model.
+    pub fn vel(&self) -> &VectorN<N, D> {
+        &self.vel
+    }
+
+    /// Returns the delay.
+    pub fn delay(&self) -> N {
+        self.delay
+    }
+
+    /// Sets the delay of the measurement in seconds, e.g. from a delay
+    /// measurement.
+    pub fn set_delay(&mut self, delay: N) {
+        assert!(delay >= N::zero(), "the delay must not be negative");
+        self.delay = delay;
+    }
+
+    /// Returns the mass of the model.
+    pub fn mass(&self) -> N {
+        self.mass
+    }
+
+    /// Sets the mass of the model.
+    pub fn set_mass(&mut self, mass: N) {
+        assert!(mass > N::zero(), "the mass has to be positive");
+        self.mass = mass;
+    }
+
+    /// Returns the damping of the model.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the damping of the model.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        self.damping = damping;
+    }
+}
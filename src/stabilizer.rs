@@ -0,0 +1,325 @@
+//! Stabilizers
+//!
+//! The stabilizers share the `Stabilizer` interface at the port where the
+//! force is displayed, so that they can be exchanged without touching the
+//! surrounding code. `HybridStabilizer` switches between several stabilizers
+//! at runtime, e.g. to the wave variables for long delays and to the TDPA for
+//! short ones.
+use nalgebra::{
+    allocator::Allocator,
+    convert,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::{PassivityObserver, EBA, ISS, SFA, TDPA, WAVE};
+
+/// Stabilizes the force displayed at a port.
+///
+/// The power `force · vel` is positive when the port absorbs energy, like for
+/// the `PassivityObserver`.
+pub trait Stabilizer<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns the force to display for the desired `force` at the port with
+    /// position `pos` and velocity `vel`.
+    fn stabilize(
+        &mut self,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D>;
+
+    /// Returns the force to command to the device for the commanded `force`
+    /// at the port with position `pos` and velocity `vel`.
+    ///
+    /// The port absorbs the energy of the force the device applies, which is
+    /// opposite to the commanded force, so the commanded force is stabilized
+    /// negated.
+    fn stabilize_command(
+        &mut self,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        -self.stabilize(pos, vel, &-force, dt)
+    }
+}
+
+impl<N, D> Stabilizer<N, D> for TDPA<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn stabilize(
+        &mut self,
+        _pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.calculate_force(vel, force, dt)
+    }
+}
+
+impl<N, D> Stabilizer<N, D> for ISS<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn stabilize(
+        &mut self,
+        _pos: &VectorN<N, D>,
+        _vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.calculate_force(force, dt)
+    }
+}
+
+impl<N, D> Stabilizer<N, D> for EBA<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn stabilize(
+        &mut self,
+        pos: &VectorN<N, D>,
+        _vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.calculate_force(pos, force, dt)
+    }
+}
+
+impl<N, D> Stabilizer<N, D> for SFA<N>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn stabilize(
+        &mut self,
+        _pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.calculate_force(force, vel, dt)
+    }
+}
+
+/// The desired force is treated as the force of the other side of a wave
+/// channel without delay. Its wave is filtered with `filter_master` and
+/// decoded with the velocity of the port, so the wave filter damps the force
+/// without a delayed channel. Without a cutoff the force is passed unchanged.
+impl<N, D> Stabilizer<N, D> for WAVE<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn stabilize(
+        &mut self,
+        _pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let v_m = self.filter_master(&self.encode_slave(force, vel), dt);
+        // Solve the decoding for the force with the velocity of the port.
+        vel * self.b() + v_m * (convert::<f64, N>(2.0) * self.b()).sqrt()
+    }
+}
+
+/// Switches between stabilizers at runtime.
+///
+/// All stabilizers are updated in every step, so that their states are
+/// up-to-date when they are switched to. After a switch the output is blended
+/// from the previous to the new stabilizer over the blend time. The energy the
+/// blending outputs beyond the new stabilizer is observed and dissipated with
+/// a damping like the TDPA, so the transition never makes the port more active
+/// than the new stabilizer.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{HybridStabilizer, Stabilizer, ISS, TDPA};
+///
+/// let mut hybrid = HybridStabilizer::<f64, U1>::new(0.01);
+/// let tdpa = hybrid.push_stabilizer(TDPA::default());
+/// let iss = hybrid.push_stabilizer(ISS::new(0.0, 1.0));
+/// assert_eq!(hybrid.active(), tdpa);
+///
+/// let (pos, vel) = (Vector1::new(0.0), Vector1::new(1.0));
+/// let force = hybrid.stabilize(&pos, &vel, &Vector1::new(-1.0), 0.001);
+/// assert_eq!(force, Vector1::new(0.0));
+///
+/// // ISS with `tau = 0` passes the force, which the transition only blends in.
+/// hybrid.switch_to(iss);
+/// let force = hybrid.stabilize(&pos, &vel, &Vector1::new(-1.0), 0.001);
+/// assert!(force[0] > -1.0 && force[0] <= 0.0);
+/// for _ in 0..20 {
+///     hybrid.stabilize(&pos, &vel, &Vector1::new(-1.0), 0.001);
+/// }
+/// assert!(!hybrid.is_blending());
+/// assert_eq!(hybrid.stabilize(&pos, &vel, &Vector1::new(-1.0), 0.001), Vector1::new(-1.0));
+/// ```
+pub struct HybridStabilizer<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    stabilizers: Vec<Box<dyn Stabilizer<N, D>>>,
+    active: usize,
+    previous: Option<usize>,
+    blend: N,
+    blend_time: N,
+    transition_observer: PassivityObserver<N>,
+}
+
+impl<N, D> HybridStabilizer<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `HybridStabilizer` without stabilizers that blends over
+    /// `blend_time` in seconds after a switch.
+    pub fn new(blend_time: N) -> Self {
+        assert!(
+            blend_time >= N::zero(),
+            "the blend time must not be negative"
+        );
+        Self {
+            stabilizers: Vec::new(),
+            active: 0,
+            previous: None,
+            blend: N::one(),
+            blend_time,
+            transition_observer: PassivityObserver::new(),
+        }
+    }
+
+    /// Appends a stabilizer and returns its index. The first stabilizer is
+    /// active.
+    pub fn push_stabilizer<S>(&mut self, stabilizer: S) -> usize
+    where
+        S: Stabilizer<N, D> + 'static,
+    {
+        self.stabilizers.push(Box::new(stabilizer));
+        self.stabilizers.len() - 1
+    }
+
+    /// Switches to the stabilizer with `index`, blending from the output of
+    /// the active one.
+    pub fn switch_to(&mut self, index: usize) {
+        assert!(
+            index < self.stabilizers.len(),
+            "there is no such stabilizer"
+        );
+        if index == self.active {
+            return;
+        }
+        self.previous = Some(self.active);
+        self.active = index;
+        self.blend = N::zero();
+        self.transition_observer.reset();
+    }
+
+    /// Returns the index of the active stabilizer.
+    pub fn active(&self) -> usize {
+        self.active
+    }
+
+    /// Returns whether the output is still blended after a switch.
+    pub fn is_blending(&self) -> bool {
+        self.previous.is_some()
+    }
+
+    /// Returns the number of stabilizers.
+    pub fn len(&self) -> usize {
+        self.stabilizers.len()
+    }
+
+    /// Returns `true` if there are no stabilizers.
+    pub fn is_empty(&self) -> bool {
+        self.stabilizers.is_empty()
+    }
+
+    /// Returns the blend time.
+    pub fn blend_time(&self) -> N {
+        self.blend_time
+    }
+
+    /// Sets the time in seconds the output is blended over after a switch.
+    pub fn set_blend_time(&mut self, blend_time: N) {
+        assert!(
+            blend_time >= N::zero(),
+            "the blend time must not be negative"
+        );
+        self.blend_time = blend_time;
+    }
+}
+
+impl<N, D> Stabilizer<N, D> for HybridStabilizer<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn stabilize(
+        &mut self,
+        pos: &VectorN<N, D>,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        if self.stabilizers.is_empty() {
+            return force.clone();
+        }
+        let forces = self
+            .stabilizers
+            .iter_mut()
+            .map(|stabilizer| stabilizer.stabilize(pos, vel, force, dt))
+            .collect::<Vec<_>>();
+        let target = forces[self.active].clone();
+        let previous = match self.previous {
+            Some(previous) => previous,
+            None => return target,
+        };
+
+        self.blend = if self.blend_time > N::zero() {
+            (self.blend + dt / self.blend_time).min(N::one())
+        } else {
+            N::one()
+        };
+        let mut output = &forces[previous] * (N::one() - self.blend) + &target * self.blend;
+
+        // Dissipate the energy the blending outputs beyond the target.
+        let energy = self
+            .transition_observer
+            .observe(&(&output - &target), vel, dt);
+        let flow_energy = vel.dot(vel) * dt;
+        if energy < N::zero() && flow_energy > N::zero() {
+            let alpha = -energy / flow_energy;
+            output += vel * alpha;
+            self.transition_observer.dissipate(alpha * flow_energy);
+        }
+
+        if self.blend >= N::one() {
+            self.previous = None;
+        }
+        output
+    }
+}
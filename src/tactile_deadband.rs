@@ -0,0 +1,231 @@
+//! Tactile Array Deadband
+//!
+//! Deadband compression for tactile sensor arrays. Instead of transmitting the
+//! whole frame every sample only the taxels, or regions of taxels, whose
+//! change exceeds their deadband are transmitted. The receiver applies these
+//! sparse updates to its copy of the frame.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, MatrixMN, RealField,
+};
+use std::ops::Range;
+
+/// How taxels are grouped for deadband detection.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TactileGrouping {
+    /// Every taxel is checked against its own deadband.
+    #[default]
+    PerTaxel,
+    /// The array is split into blocks of `rows` x `cols` taxels that are
+    /// checked and transmitted as a whole.
+    Regions {
+        /// Number of taxel rows of a block.
+        rows: usize,
+        /// Number of taxel columns of a block.
+        cols: usize,
+    },
+}
+
+/// Value of a single taxel that has to be transmitted.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TactileUpdate<N>
+where
+    N: RealField,
+{
+    /// Row of the taxel.
+    pub row: usize,
+    /// Column of the taxel.
+    pub col: usize,
+    /// Value of the taxel.
+    pub value: N,
+}
+
+/// Deadband detector for tactile arrays.
+///
+/// ```rust
+/// use nalgebra::Matrix4;
+/// use haptic_toolbox::{TactileArrayDeadband, TactileArrayReconstructor, TactileGrouping};
+///
+/// let initial_frame = Matrix4::repeat(1.0);
+/// let mut deadband = TactileArrayDeadband::new(0.1, initial_frame, TactileGrouping::PerTaxel);
+/// let mut reconstructor = TactileArrayReconstructor::new(initial_frame);
+///
+/// let mut frame = Matrix4::repeat(1.05);
+/// frame[(2, 3)] = 2.0;
+/// let updates = deadband.check(&frame);
+/// assert_eq!(updates.len(), 1);
+///
+/// let reconstructed = reconstructor.apply(&updates);
+/// assert_eq!(reconstructed[(2, 3)], 2.0);
+/// assert_eq!(reconstructed[(0, 0)], 1.0);
+///
+/// // In region mode the whole 2x2 block containing the taxel is transmitted.
+/// let grouping = TactileGrouping::Regions { rows: 2, cols: 2 };
+/// let mut deadband = TactileArrayDeadband::new(0.1, initial_frame, grouping);
+/// assert_eq!(deadband.check(&frame).len(), 4);
+/// ```
+#[derive(Debug)]
+pub struct TactileArrayDeadband<N, R, C>
+where
+    N: RealField,
+    R: Dim,
+    C: Dim,
+    DefaultAllocator: Allocator<N, R, C>,
+{
+    prev_frame: MatrixMN<N, R, C>,
+    thresholds: MatrixMN<N, R, C>,
+    grouping: TactileGrouping,
+}
+
+impl<N, R, C> TactileArrayDeadband<N, R, C>
+where
+    N: RealField,
+    R: Dim + DimName,
+    C: Dim + DimName,
+    DefaultAllocator: Allocator<N, R, C>,
+{
+    /// Creates a new `TactileArrayDeadband` with the same relative `threshold`
+    /// for every taxel.
+    pub fn new(threshold: N, initial_frame: MatrixMN<N, R, C>, grouping: TactileGrouping) -> Self {
+        Self::with_thresholds(MatrixMN::repeat(threshold), initial_frame, grouping)
+    }
+
+    /// Creates a new `TactileArrayDeadband` with a relative threshold per
+    /// taxel.
+    pub fn with_thresholds(
+        thresholds: MatrixMN<N, R, C>,
+        initial_frame: MatrixMN<N, R, C>,
+        grouping: TactileGrouping,
+    ) -> Self {
+        assert!(
+            thresholds.iter().all(|&threshold| threshold >= N::zero()),
+            "cannot assign a negative threshold"
+        );
+        if let TactileGrouping::Regions { rows, cols } = grouping {
+            assert!(rows > 0 && cols > 0, "regions cannot be empty");
+        }
+        Self {
+            prev_frame: initial_frame,
+            thresholds,
+            grouping,
+        }
+    }
+
+    /// Checks `frame` against the deadband of the previously saved frame and
+    /// returns the taxels that have to be transmitted.
+    pub fn check(&mut self, frame: &MatrixMN<N, R, C>) -> Vec<TactileUpdate<N>> {
+        let (rows, cols) = match self.grouping {
+            TactileGrouping::PerTaxel => (1, 1),
+            TactileGrouping::Regions { rows, cols } => (rows, cols),
+        };
+
+        let mut updates = Vec::new();
+        for row in (0..frame.nrows()).step_by(rows) {
+            for col in (0..frame.ncols()).step_by(cols) {
+                let row_end = (row + rows).min(frame.nrows());
+                let col_end = (col + cols).min(frame.ncols());
+                if !self.region_in_deadband(frame, row..row_end, col..col_end) {
+                    for r in row..row_end {
+                        for c in col..col_end {
+                            self.prev_frame[(r, c)] = frame[(r, c)];
+                            updates.push(TactileUpdate {
+                                row: r,
+                                col: c,
+                                value: frame[(r, c)],
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        updates
+    }
+
+    /// Returns the relative thresholds of the taxels.
+    pub fn thresholds(&self) -> &MatrixMN<N, R, C> {
+        &self.thresholds
+    }
+
+    /// Sets the relative thresholds of the taxels.
+    pub fn set_thresholds(&mut self, thresholds: MatrixMN<N, R, C>) {
+        assert!(
+            thresholds.iter().all(|&threshold| threshold >= N::zero()),
+            "cannot assign a negative threshold"
+        );
+        self.thresholds = thresholds;
+    }
+
+    /// Returns the grouping of the taxels.
+    pub fn grouping(&self) -> TactileGrouping {
+        self.grouping
+    }
+
+    /// Returns the frame the following ones are compared to.
+    pub fn prev_frame(&self) -> &MatrixMN<N, R, C> {
+        &self.prev_frame
+    }
+
+    fn region_in_deadband(
+        &self,
+        frame: &MatrixMN<N, R, C>,
+        rows: Range<usize>,
+        cols: Range<usize>,
+    ) -> bool {
+        let mut diff = N::zero();
+        let mut deadband = N::zero();
+        for r in rows {
+            for c in cols.clone() {
+                let prev = self.prev_frame[(r, c)];
+                let taxel_diff = prev - frame[(r, c)];
+                let taxel_deadband = self.thresholds[(r, c)] * prev;
+                diff += taxel_diff * taxel_diff;
+                deadband += taxel_deadband * taxel_deadband;
+            }
+        }
+        diff <= deadband
+    }
+}
+
+/// Receiver side of `TactileArrayDeadband`, holds the last received value of
+/// every taxel.
+#[derive(Debug)]
+pub struct TactileArrayReconstructor<N, R, C>
+where
+    N: RealField,
+    R: Dim,
+    C: Dim,
+    DefaultAllocator: Allocator<N, R, C>,
+{
+    frame: MatrixMN<N, R, C>,
+}
+
+impl<N, R, C> TactileArrayReconstructor<N, R, C>
+where
+    N: RealField,
+    R: Dim + DimName,
+    C: Dim + DimName,
+    DefaultAllocator: Allocator<N, R, C>,
+{
+    /// Creates a new `TactileArrayReconstructor`.
+    ///
+    /// `initial_frame` has to match the one of the `TactileArrayDeadband`.
+    pub fn new(initial_frame: MatrixMN<N, R, C>) -> Self {
+        Self {
+            frame: initial_frame,
+        }
+    }
+
+    /// Applies the received `updates` and returns the reconstructed frame.
+    pub fn apply(&mut self, updates: &[TactileUpdate<N>]) -> &MatrixMN<N, R, C> {
+        for update in updates {
+            self.frame[(update.row, update.col)] = update.value;
+        }
+        &self.frame
+    }
+
+    /// Returns the reconstructed frame.
+    pub fn frame(&self) -> &MatrixMN<N, R, C> {
+        &self.frame
+    }
+}
@@ -3,22 +3,64 @@
 //! An energy-based method is presented for controlling a haptic interface
 //! system to ensure stable contact under a wide variety of operating
 //! conditions. [[1]](https://ieeexplore.ieee.org/document/932880)
+//!
+//! The energy is integrated with the time step `dt` that is passed in seconds,
+//! so the resulting damping is in physical units and does not depend on the
+//! sample rate.
+//!
+//! By default the energy of all axes is observed together, so energy generated
+//! along one axis can be masked by dissipation along another. With
+//! `set_per_dof` every axis gets its own passivity observer and controller.
+//!
+//! The damping required for passivity jumps from one step to the next, which
+//! can be felt as chatter. With `set_damping_time_constant` the damping decays
+//! smoothly instead of dropping to zero. It never falls below the required
+//! damping, so the smoothing only dissipates additional energy, which is
+//! credited to the passivity observer.
+//!
+//! In long sessions the observer accumulates a large surplus of dissipated
+//! energy, which masks active behavior later on for a long time. With
+//! `set_forgetting_time_constant` the surplus is forgotten exponentially. The
+//! observed energy then underestimates the actual energy, so the controller
+//! gets more conservative and dissipates more than required, but never less.
 use nalgebra::{
     allocator::Allocator,
     dimension::{Dim, DimName},
-    DefaultAllocator, RealField, VectorN,
+    DefaultAllocator, RealField, Vector1, VectorN, U1,
 };
 use num_traits::Zero;
 
+use crate::{EnergyTank, PassivityObserver};
+
+/// Signal the passivity controller corrects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PassivityControllerType {
+    /// Adds a damping force, for impedance-type devices that take a force
+    /// command.
+    #[default]
+    Impedance,
+    /// Adds a velocity proportional to the force, for admittance-type devices
+    /// that take a velocity command.
+    Admittance,
+}
+
+#[derive(Debug)]
 pub struct TDPA<N, D>
 where
     N: RealField,
     D: Dim,
     DefaultAllocator: Allocator<N, D>,
 {
-    alpha: N,
-    energy: N,
-    prev_vel: VectorN<N, D>,
+    alpha: VectorN<N, D>,
+    controller_type: PassivityControllerType,
+    observer: PassivityObserver<N>,
+    dof_observers: Vec<PassivityObserver<N>>,
+    prev_flow: VectorN<N, D>,
+    prev_dt: N,
+    correction: VectorN<N, D>,
+    damping_time_constant: Option<N>,
+    forgetting_time_constant: Option<N>,
+    energy_tank: Option<EnergyTank<N>>,
 }
 
 impl<N, D> Default for TDPA<N, D>
@@ -29,9 +71,32 @@ where
 {
     fn default() -> Self {
         Self {
-            alpha: N::zero(),
-            energy: N::zero(),
-            prev_vel: Zero::zero(),
+            alpha: Zero::zero(),
+            controller_type: PassivityControllerType::default(),
+            observer: PassivityObserver::new(),
+            dof_observers: Vec::new(),
+            prev_flow: Zero::zero(),
+            prev_dt: N::zero(),
+            correction: Zero::zero(),
+            damping_time_constant: None,
+            forgetting_time_constant: None,
+            energy_tank: None,
+        }
+    }
+}
+
+impl<N, D> TDPA<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `TDPA` that corrects the signal of `controller_type`
+    /// when calling `calculate`.
+    pub fn with_controller_type(controller_type: PassivityControllerType) -> Self {
+        Self {
+            controller_type,
+            ..Self::default()
         }
     }
 }
@@ -43,19 +108,331 @@ where
     DefaultAllocator: Allocator<N, D>,
 {
     /// Calculate the TDPA force while ensuring passivity.
-    pub fn calculate_force(&mut self, vel: &VectorN<N, D>, force: &VectorN<N, D>) -> VectorN<N, D> {
-        let energy = force.dot(vel) + self.alpha * self.prev_vel.dot(&self.prev_vel);
-        self.energy += energy;
-        self.prev_vel = vel.clone();
-        self.alpha = if self.energy < N::zero() {
-            -self.energy / (vel.dot(vel))
+    ///
+    /// This is the impedance-type passivity controller which adds a damping
+    /// force.
+    pub fn calculate_force(
+        &mut self,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.update(vel, force, dt, PassivityControllerType::Impedance);
+        if self.alpha.iter().all(|alpha| alpha.is_zero()) {
+            force.clone()
+        } else {
+            force + &self.correction
+        }
+    }
+
+    /// Calculate the TDPA velocity while ensuring passivity.
+    ///
+    /// This is the admittance-type passivity controller which adds a velocity
+    /// proportional to the force.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::TDPA;
+    ///
+    /// let mut tdpa = TDPA::<f64, U1>::default();
+    /// let force = Vector1::new(2.0);
+    ///
+    /// // The port generates energy, so the velocity is corrected until the
+    /// // energy is dissipated again.
+    /// let passive_vel = tdpa.calculate_vel(&Vector1::new(-1.0), &force, 0.5);
+    /// assert_eq!(passive_vel, Vector1::new(0.0));
+    /// assert_eq!(tdpa.last_dissipated_energy(), 1.0);
+    /// ```
+    pub fn calculate_vel(
+        &mut self,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        self.update(vel, force, dt, PassivityControllerType::Admittance);
+        if self.alpha.iter().all(|alpha| alpha.is_zero()) {
+            vel.clone()
         } else {
-            N::zero()
+            vel + &self.correction
+        }
+    }
+
+    /// Corrects the signal of the controller type and returns the velocity
+    /// and the force.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::{PassivityControllerType, TDPA};
+    ///
+    /// let mut tdpa = TDPA::<f64, U1>::with_controller_type(PassivityControllerType::Admittance);
+    /// let (vel, force) = (Vector1::new(-1.0), Vector1::new(2.0));
+    ///
+    /// let (passive_vel, passive_force) = tdpa.calculate(&vel, &force, 0.001);
+    /// assert_eq!(passive_force, force);
+    /// assert_eq!(passive_vel, Vector1::new(0.0));
+    /// ```
+    pub fn calculate(
+        &mut self,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> (VectorN<N, D>, VectorN<N, D>) {
+        match self.controller_type {
+            PassivityControllerType::Impedance => {
+                (vel.clone(), self.calculate_force(vel, force, dt))
+            }
+            PassivityControllerType::Admittance => {
+                (self.calculate_vel(vel, force, dt), force.clone())
+            }
+        }
+    }
+
+    /// Returns the controller type used by `calculate`.
+    pub fn controller_type(&self) -> PassivityControllerType {
+        self.controller_type
+    }
+
+    /// Sets the controller type used by `calculate`, defaults to
+    /// `PassivityControllerType::Impedance`.
+    pub fn set_controller_type(&mut self, controller_type: PassivityControllerType) {
+        self.controller_type = controller_type;
+    }
+
+    fn update(
+        &mut self,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+        controller_type: PassivityControllerType,
+    ) {
+        let flow = match controller_type {
+            PassivityControllerType::Impedance => vel,
+            PassivityControllerType::Admittance => force,
         };
-        if self.alpha == N::zero() {
-            force.clone()
+        if let Some(forgetting_time_constant) = self.forgetting_time_constant {
+            let forgetting = N::one() - (-dt / forgetting_time_constant).exp();
+            for observer in std::iter::once(&mut self.observer).chain(self.dof_observers.iter_mut())
+            {
+                let surplus = observer.energy().max(N::zero());
+                observer.transfer(-surplus * forgetting);
+            }
+        }
+        self.observer.observe(force, vel, dt);
+        let energy = self.observer.dissipate(self.last_dissipated_energy());
+        let prev_alpha = self.alpha.clone();
+        if self.dof_observers.is_empty() {
+            let alpha = if energy < N::zero() && dt > N::zero() && flow.dot(flow) > N::zero() {
+                -energy / (flow.dot(flow) * dt)
+            } else {
+                N::zero()
+            };
+            self.alpha.fill(alpha);
         } else {
-            force + vel * self.alpha
+            for (i, observer) in self.dof_observers.iter_mut().enumerate() {
+                observer.observe::<U1>(&Vector1::new(force[i]), &Vector1::new(vel[i]), dt);
+                let energy =
+                    observer.dissipate(self.correction[i] * self.prev_flow[i] * self.prev_dt);
+                self.alpha[i] = if energy < N::zero() && dt > N::zero() && !flow[i].is_zero() {
+                    -energy / (flow[i] * flow[i] * dt)
+                } else {
+                    N::zero()
+                };
+            }
         }
+        if let Some(damping_time_constant) = self.damping_time_constant {
+            let decay = (-dt / damping_time_constant).exp();
+            self.alpha.zip_apply(&prev_alpha, |alpha, prev_alpha| {
+                alpha.max(prev_alpha * decay)
+            });
+        }
+        self.prev_flow = flow.clone();
+        self.prev_dt = dt;
+        self.correction = flow.component_mul(&self.alpha);
+        let dissipated_energy = self.last_dissipated_energy();
+        if let Some(energy_tank) = &mut self.energy_tank {
+            energy_tank.store(dissipated_energy.max(N::zero()));
+        }
+    }
+
+    /// Returns the correction that was added to the force by the last call to
+    /// `calculate_force`, or to the velocity by the last call to
+    /// `calculate_vel`.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector2, U2};
+    /// use haptic_toolbox::TDPA;
+    ///
+    /// let mut tdpa = TDPA::<f64, U2>::default();
+    /// let vel = Vector2::new(1.0, 0.5);
+    /// let force = Vector2::new(-2.0, 0.0);
+    ///
+    /// let passive_force = tdpa.calculate_force(&vel, &force, 0.001);
+    /// assert_eq!(tdpa.last_correction(), &(passive_force - force));
+    /// assert!(tdpa.last_dissipated_energy() > 0.0);
+    /// ```
+    pub fn last_correction(&self) -> &VectorN<N, D> {
+        &self.correction
+    }
+
+    /// Returns the energy that is dissipated by the last correction over its
+    /// time step.
+    pub fn last_dissipated_energy(&self) -> N {
+        self.correction.dot(&self.prev_flow) * self.prev_dt
+    }
+
+    /// Returns the time constant the damping decays with.
+    pub fn damping_time_constant(&self) -> Option<N> {
+        self.damping_time_constant
+    }
+
+    /// Sets the time constant in seconds the damping decays with once it is no
+    /// longer required, defaults to `None` for no smoothing.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::TDPA;
+    ///
+    /// let mut tdpa = TDPA::<f64, U1>::default();
+    /// tdpa.set_damping_time_constant(Some(0.01));
+    ///
+    /// let vel = Vector1::new(1.0);
+    /// tdpa.calculate_force(&vel, &Vector1::new(-1.0), 0.001);
+    /// assert_eq!(tdpa.alpha()[0], 1.0);
+    ///
+    /// // The damping is no longer required, but only decays.
+    /// tdpa.calculate_force(&vel, &Vector1::new(0.0), 0.001);
+    /// assert!((tdpa.alpha()[0] - (-0.1f64).exp()).abs() < 1e-12);
+    /// assert!(tdpa.observer().energy() >= 0.0);
+    /// ```
+    pub fn set_damping_time_constant(&mut self, damping_time_constant: Option<N>) {
+        if let Some(damping_time_constant) = damping_time_constant {
+            assert!(
+                damping_time_constant > N::zero(),
+                "the damping time constant has to be positive"
+            );
+        }
+        self.damping_time_constant = damping_time_constant;
+    }
+
+    /// Returns the time constant the energy surplus is forgotten with.
+    pub fn forgetting_time_constant(&self) -> Option<N> {
+        self.forgetting_time_constant
+    }
+
+    /// Sets the time constant in seconds the positive observed energy is
+    /// forgotten with, defaults to `None` for no forgetting.
+    ///
+    /// A short time constant reacts quickly to active behavior after a long
+    /// passive phase, but also dissipates energy that could still have been
+    /// spent without violating passivity.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::TDPA;
+    ///
+    /// let mut tdpa = TDPA::<f64, U1>::default();
+    /// tdpa.set_forgetting_time_constant(Some(1.0));
+    /// let vel = Vector1::new(1.0);
+    ///
+    /// // A long passive phase builds up a surplus of 1 J.
+    /// tdpa.calculate_force(&vel, &Vector1::new(1000.0), 0.001);
+    ///
+    /// // After 10 s it is mostly forgotten, so the generated energy is
+    /// // dissipated right away.
+    /// for _ in 0..10_000 {
+    ///     tdpa.calculate_force(&vel, &Vector1::new(0.0), 0.001);
+    /// }
+    /// let force = tdpa.calculate_force(&vel, &Vector1::new(-1.0), 0.001);
+    /// assert!(force[0] > -0.1);
+    /// ```
+    pub fn set_forgetting_time_constant(&mut self, forgetting_time_constant: Option<N>) {
+        if let Some(forgetting_time_constant) = forgetting_time_constant {
+            assert!(
+                forgetting_time_constant > N::zero(),
+                "the forgetting time constant has to be positive"
+            );
+        }
+        self.forgetting_time_constant = forgetting_time_constant;
+    }
+
+    /// Returns the passivity observer of all axes.
+    pub fn observer(&self) -> &PassivityObserver<N> {
+        &self.observer
+    }
+
+    /// Returns the passivity observers per axis, which are empty unless
+    /// `set_per_dof` was enabled.
+    pub fn dof_observers(&self) -> &[PassivityObserver<N>] {
+        &self.dof_observers
+    }
+
+    /// Returns the damping per axis of the last correction.
+    pub fn alpha(&self) -> &VectorN<N, D> {
+        &self.alpha
+    }
+
+    /// Returns `true` if every axis has its own passivity observer and
+    /// controller.
+    pub fn is_per_dof(&self) -> bool {
+        !self.dof_observers.is_empty()
+    }
+
+    /// Sets whether every axis gets its own passivity observer and controller
+    /// instead of observing the energy of all axes together, defaults to
+    /// `false`.
+    ///
+    /// The observers per axis start without any observed energy.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector2, U2};
+    /// use haptic_toolbox::TDPA;
+    ///
+    /// let vel = Vector2::new(1.0, 1.0);
+    /// let force = Vector2::new(-1.0, 2.0);
+    ///
+    /// // The energy generated along x is masked by the energy along y.
+    /// let mut tdpa = TDPA::<f64, U2>::default();
+    /// assert_eq!(tdpa.calculate_force(&vel, &force, 1.0), force);
+    ///
+    /// let mut tdpa = TDPA::<f64, U2>::default();
+    /// tdpa.set_per_dof(true);
+    /// assert_eq!(tdpa.calculate_force(&vel, &force, 1.0), Vector2::new(0.0, 2.0));
+    /// assert_eq!(tdpa.alpha(), &Vector2::new(1.0, 0.0));
+    /// ```
+    pub fn set_per_dof(&mut self, per_dof: bool) {
+        self.dof_observers = if per_dof {
+            vec![PassivityObserver::new(); self.alpha.len()]
+        } else {
+            Vec::new()
+        };
+    }
+
+    /// Returns the energy tank.
+    pub fn energy_tank(&self) -> Option<&EnergyTank<N>> {
+        self.energy_tank.as_ref()
+    }
+
+    /// Returns the energy tank mutably, e.g. to draw the harvested energy
+    /// from it in another controller.
+    pub fn energy_tank_mut(&mut self) -> Option<&mut EnergyTank<N>> {
+        self.energy_tank.as_mut()
+    }
+
+    /// Sets the energy tank the energy dissipated by the corrections is
+    /// stored in, instead of being lost.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::{EnergyTank, TDPA};
+    ///
+    /// let mut tdpa = TDPA::<f64, U1>::default();
+    /// tdpa.set_energy_tank(Some(EnergyTank::new(0.0, 0.0)));
+    ///
+    /// tdpa.calculate_force(&Vector1::new(1.0), &Vector1::new(-2.0), 0.001);
+    /// let dissipated = tdpa.last_dissipated_energy();
+    /// assert_eq!(tdpa.energy_tank().unwrap().energy(), dissipated);
+    /// ```
+    pub fn set_energy_tank(&mut self, energy_tank: Option<EnergyTank<N>>) {
+        self.energy_tank = energy_tank;
     }
 }
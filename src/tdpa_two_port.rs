@@ -0,0 +1,486 @@
+//! Two-Port Time Domain Passivity Control
+//!
+//! For bilateral teleoperation over a delayed channel the energy of each port
+//! is split into the energy flowing into the channel and the energy flowing out
+//! of it. Every side sends its input energy to the other side, where the
+//! output energy is limited to the received input energy. Since the channel
+//! can never output more energy than was put in on the other side, it stays
+//! passive for arbitrary delays. The energy values are cumulative, so lost or
+//! reordered messages only delay the energy that may be output.
+//!
+//! With energy reflection (TDPA-ER) a port does not hold on to the received
+//! energy it has no use for, but reflects it back to the other side, which
+//! reduces the conservatism of the approach when the energy flow is
+//! asymmetric.
+//!
+//! When the delay varies, the energy that is still in flight in the channel
+//! has to be known for the bookkeeping. With `timed_message` and
+//! `receive_timed` the messages carry the time they were sent and acknowledge
+//! the energy received from the other side, so that every port measures the
+//! one-way delay and knows how much of its input energy is still in flight.
+use nalgebra::{
+    allocator::Allocator,
+    convert,
+    dimension::{Dim, DimName},
+    try_convert, DefaultAllocator, RealField, VectorN,
+};
+use num_traits::Zero;
+
+use crate::{PassivityControllerType, PassivityObserver};
+
+/// Input energy of a port that is sent to the other side of the channel.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EnergyMessage<N>
+where
+    N: RealField,
+{
+    /// Energy that flowed into the channel at the sending port since it was
+    /// created.
+    pub energy: N,
+}
+
+impl<N> EnergyMessage<N>
+where
+    N: RealField,
+{
+    /// Number of bytes of the serialized message.
+    pub const SIZE: usize = 8;
+
+    /// Serializes the message to the little-endian bytes of the energy as
+    /// `f64`.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::EnergyMessage;
+    ///
+    /// let message = EnergyMessage { energy: 1.5f32 };
+    /// let bytes = message.to_bytes();
+    /// assert_eq!(bytes, 1.5f64.to_le_bytes());
+    /// assert_eq!(EnergyMessage::from_bytes(&bytes), message);
+    /// ```
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let energy: f64 =
+            try_convert(self.energy).expect("the energy has to be representable as f64");
+        energy.to_le_bytes()
+    }
+
+    /// Deserializes a message from the bytes created by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8; 8]) -> Self {
+        Self {
+            energy: convert(f64::from_le_bytes(*bytes)),
+        }
+    }
+}
+
+/// Input energy of a port with the send timestamp and the acknowledged
+/// energy of the other side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimedEnergyMessage<N>
+where
+    N: RealField,
+{
+    /// Energy that flowed into the channel at the sending port since it was
+    /// created.
+    pub energy: N,
+    /// Latest input energy of the receiving port that the sending port
+    /// received.
+    pub acknowledged_energy: N,
+    /// Time in seconds the message was sent, on a clock shared by both sides.
+    pub sent_time: N,
+}
+
+impl<N> TimedEnergyMessage<N>
+where
+    N: RealField,
+{
+    /// Number of bytes of the serialized message.
+    pub const SIZE: usize = 24;
+
+    /// Serializes the message to the little-endian bytes of the energy, the
+    /// acknowledged energy and the send time as `f64`.
+    ///
+    /// ```rust
+    /// use haptic_toolbox::TimedEnergyMessage;
+    ///
+    /// let message = TimedEnergyMessage {
+    ///     energy: 1.5,
+    ///     acknowledged_energy: 0.5,
+    ///     sent_time: 10.0,
+    /// };
+    /// assert_eq!(TimedEnergyMessage::from_bytes(&message.to_bytes()), message);
+    /// ```
+    pub fn to_bytes(&self) -> [u8; 24] {
+        let mut bytes = [0; 24];
+        for (chunk, value) in
+            bytes
+                .chunks_mut(8)
+                .zip(&[self.energy, self.acknowledged_energy, self.sent_time])
+        {
+            let value: f64 =
+                try_convert(*value).expect("the values have to be representable as f64");
+            chunk.copy_from_slice(&value.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserializes a message from the bytes created by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8; 24]) -> Self {
+        let value = |i: usize| {
+            let mut value = [0; 8];
+            value.copy_from_slice(&bytes[i * 8..(i + 1) * 8]);
+            convert(f64::from_le_bytes(value))
+        };
+        Self {
+            energy: value(0),
+            acknowledged_energy: value(1),
+            sent_time: value(2),
+        }
+    }
+}
+
+/// One side of the two-port passivity controller.
+///
+/// The power `force * vel` is positive when energy flows from the local device
+/// into the channel. The passivity observer holds the energy that may still be
+/// output at this port.
+#[derive(Clone, Debug)]
+pub struct TdpaPort<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    controller_type: PassivityControllerType,
+    energy_in: N,
+    energy_out: N,
+    received_energy: N,
+    observer: PassivityObserver<N>,
+    reflection_rate: Option<N>,
+    reflected_energy: N,
+    acknowledged_energy: N,
+    delay: Option<N>,
+    correction: VectorN<N, D>,
+}
+
+impl<N, D> TdpaPort<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `TdpaPort` that corrects the signal of `controller_type`.
+    pub fn new(controller_type: PassivityControllerType) -> Self {
+        Self {
+            controller_type,
+            energy_in: N::zero(),
+            energy_out: N::zero(),
+            received_energy: N::zero(),
+            observer: PassivityObserver::new(),
+            reflection_rate: None,
+            reflected_energy: N::zero(),
+            acknowledged_energy: N::zero(),
+            delay: None,
+            correction: Zero::zero(),
+        }
+    }
+}
+
+impl<N, D> TdpaPort<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Updates the energy flows of the port and returns the velocity and the
+    /// force, one of which is corrected so that the output energy does not
+    /// exceed the received input energy of the other side.
+    pub fn calculate(
+        &mut self,
+        vel: &VectorN<N, D>,
+        force: &VectorN<N, D>,
+        dt: N,
+    ) -> (VectorN<N, D>, VectorN<N, D>) {
+        let energy = force.dot(vel) * dt;
+        if energy >= N::zero() {
+            self.energy_in += energy;
+            self.correction.fill(N::zero());
+        } else {
+            let flow = match self.controller_type {
+                PassivityControllerType::Impedance => vel,
+                PassivityControllerType::Admittance => force,
+            };
+            let available = self.observer.observe(force, vel, dt);
+            let flow_energy = flow.dot(flow) * dt;
+            let alpha = if available < N::zero() && flow_energy > N::zero() {
+                -available / flow_energy
+            } else {
+                N::zero()
+            };
+            self.correction = flow * alpha;
+            let dissipated_energy = self.correction.dot(flow) * dt;
+            self.observer.dissipate(dissipated_energy);
+            self.energy_out -= energy + dissipated_energy;
+        }
+
+        if let Some(reflection_rate) = self.reflection_rate {
+            let reflected_energy =
+                self.observer.energy().max(N::zero()) * (N::one() - (-reflection_rate * dt).exp());
+            self.observer.transfer(-reflected_energy);
+            self.energy_in += reflected_energy;
+            self.reflected_energy += reflected_energy;
+        }
+
+        if self
+            .correction
+            .iter()
+            .all(|correction| correction.is_zero())
+        {
+            return (vel.clone(), force.clone());
+        }
+        match self.controller_type {
+            PassivityControllerType::Impedance => (vel.clone(), force + &self.correction),
+            PassivityControllerType::Admittance => (vel + &self.correction, force.clone()),
+        }
+    }
+
+    /// Returns the message with the input energy for the other side.
+    pub fn message(&self) -> EnergyMessage<N> {
+        EnergyMessage {
+            energy: self.energy_in,
+        }
+    }
+
+    /// Receives the input energy of the other side.
+    ///
+    /// Messages with less energy than an earlier one were reordered by the
+    /// network and are ignored.
+    pub fn receive(&mut self, message: &EnergyMessage<N>) {
+        if message.energy > self.received_energy {
+            self.observer
+                .transfer(message.energy - self.received_energy);
+            self.received_energy = message.energy;
+        }
+    }
+
+    /// Returns the message with the input energy for the other side, the
+    /// acknowledged energy of the other side and the send time `sent_time` in
+    /// seconds.
+    pub fn timed_message(&self, sent_time: N) -> TimedEnergyMessage<N> {
+        TimedEnergyMessage {
+            energy: self.energy_in,
+            acknowledged_energy: self.received_energy,
+            sent_time,
+        }
+    }
+
+    /// Receives the input energy of the other side like `receive` at the time
+    /// `received_time` in seconds and measures the one-way delay.
+    ///
+    /// The clocks of both sides have to be synchronized for the measured
+    /// delay to be meaningful, the energy bookkeeping does not depend on it.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::TdpaTwoPort;
+    ///
+    /// let (mut master, mut slave) = TdpaTwoPort::<f64, U1>::new().into_halves();
+    ///
+    /// master.calculate(&Vector1::new(1.0), &Vector1::new(1.0), 1.0);
+    /// let message = master.timed_message(1.0);
+    /// assert_eq!(master.in_flight_energy(), 1.0);
+    ///
+    /// // The slave receives the energy 50 ms later and acknowledges it.
+    /// slave.receive_timed(&message, 1.05);
+    /// assert!((slave.delay().unwrap() - 0.05).abs() < 1e-12);
+    /// master.receive_timed(&slave.timed_message(1.05), 1.1);
+    /// assert_eq!(master.in_flight_energy(), 0.0);
+    /// ```
+    pub fn receive_timed(&mut self, message: &TimedEnergyMessage<N>, received_time: N) {
+        if message.energy < self.received_energy {
+            return;
+        }
+        self.delay = Some(received_time - message.sent_time);
+        self.acknowledged_energy = self.acknowledged_energy.max(message.acknowledged_energy);
+        self.receive(&EnergyMessage {
+            energy: message.energy,
+        });
+    }
+
+    /// Returns the input energy of this port that is still in flight, i.e.
+    /// that was not acknowledged by the other side yet.
+    pub fn in_flight_energy(&self) -> N {
+        (self.energy_in - self.acknowledged_energy).max(N::zero())
+    }
+
+    /// Returns the last measured one-way delay from the other side.
+    pub fn delay(&self) -> Option<N> {
+        self.delay
+    }
+
+    /// Returns the energy that flowed into the channel at this port.
+    pub fn energy_in(&self) -> N {
+        self.energy_in
+    }
+
+    /// Returns the energy that flowed out of the channel at this port.
+    pub fn energy_out(&self) -> N {
+        self.energy_out
+    }
+
+    /// Returns the latest received input energy of the other side.
+    pub fn received_energy(&self) -> N {
+        self.received_energy
+    }
+
+    /// Returns the passivity observer with the energy that may still be output
+    /// at this port.
+    pub fn observer(&self) -> &PassivityObserver<N> {
+        &self.observer
+    }
+
+    /// Returns the rate the unused received energy is reflected with.
+    pub fn reflection_rate(&self) -> Option<N> {
+        self.reflection_rate
+    }
+
+    /// Sets the rate in 1/s the unused received energy is reflected back to
+    /// the other side with, defaults to `None` for no reflection.
+    ///
+    /// The reflected energy is sent as input energy of this port.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::{EnergyMessage, PassivityControllerType, TdpaPort};
+    ///
+    /// let mut port = TdpaPort::<f64, U1>::new(PassivityControllerType::Impedance);
+    /// port.set_reflection_rate(Some(10.0));
+    /// port.receive(&EnergyMessage { energy: 2.0 });
+    ///
+    /// let zero = Vector1::new(0.0);
+    /// port.calculate(&zero, &zero, 0.1);
+    ///
+    /// let reflected = 2.0 * (1.0 - (-1.0f64).exp());
+    /// assert!((port.message().energy - reflected).abs() < 1e-12);
+    /// assert!((port.observer().energy() - (2.0 - reflected)).abs() < 1e-12);
+    /// ```
+    pub fn set_reflection_rate(&mut self, reflection_rate: Option<N>) {
+        if let Some(reflection_rate) = reflection_rate {
+            assert!(
+                reflection_rate >= N::zero(),
+                "the reflection rate must not be negative"
+            );
+        }
+        self.reflection_rate = reflection_rate;
+    }
+
+    /// Returns the energy that was reflected back to the other side.
+    pub fn reflected_energy(&self) -> N {
+        self.reflected_energy
+    }
+
+    /// Returns the correction of the last call to `calculate`.
+    pub fn last_correction(&self) -> &VectorN<N, D> {
+        &self.correction
+    }
+
+    /// Returns the controller type.
+    pub fn controller_type(&self) -> PassivityControllerType {
+        self.controller_type
+    }
+}
+
+/// Two-port passivity controller for a delayed channel.
+///
+/// The master side corrects the force displayed to the operator and the slave
+/// side corrects the velocity commanded to the slave. In a distributed setup
+/// each side runs its own half, which can be taken with `into_halves`.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{EnergyMessage, TdpaTwoPort};
+///
+/// let mut two_port = TdpaTwoPort::<f64, U1>::new();
+///
+/// // The operator puts 1 J into the channel.
+/// two_port
+///     .master_mut()
+///     .calculate(&Vector1::new(1.0), &Vector1::new(1.0), 1.0);
+/// let bytes = two_port.master().message().to_bytes();
+/// two_port.slave_mut().receive(&EnergyMessage::from_bytes(&bytes));
+///
+/// // The slave would output 3 J, so its velocity is reduced until only the
+/// // received 1 J is output.
+/// let (vel, _) = two_port
+///     .slave_mut()
+///     .calculate(&Vector1::new(1.0), &Vector1::new(-3.0), 1.0);
+/// assert!((vel[0] - 1.0 / 3.0).abs() < 1e-12);
+/// assert!((two_port.slave().energy_out() - 1.0f64).abs() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct TdpaTwoPort<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    master: TdpaPort<N, D>,
+    slave: TdpaPort<N, D>,
+}
+
+impl<N, D> Default for TdpaTwoPort<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn default() -> Self {
+        Self {
+            master: TdpaPort::new(PassivityControllerType::Impedance),
+            slave: TdpaPort::new(PassivityControllerType::Admittance),
+        }
+    }
+}
+
+impl<N, D> TdpaTwoPort<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `TdpaTwoPort` with an impedance-type master side and an
+    /// admittance-type slave side.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<N, D> TdpaTwoPort<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns the master side.
+    pub fn master(&self) -> &TdpaPort<N, D> {
+        &self.master
+    }
+
+    /// Returns the master side mutably.
+    pub fn master_mut(&mut self) -> &mut TdpaPort<N, D> {
+        &mut self.master
+    }
+
+    /// Returns the slave side.
+    pub fn slave(&self) -> &TdpaPort<N, D> {
+        &self.slave
+    }
+
+    /// Returns the slave side mutably.
+    pub fn slave_mut(&mut self) -> &mut TdpaPort<N, D> {
+        &mut self.slave
+    }
+
+    /// Returns the master and the slave side.
+    pub fn into_halves(self) -> (TdpaPort<N, D>, TdpaPort<N, D>) {
+        (self.master, self.slave)
+    }
+}
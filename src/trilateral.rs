@@ -0,0 +1,151 @@
+//! Trilateral Teleoperation
+//!
+//! Two masters share the control of one slave, e.g. a mentor and a trainee.
+//! The dominance factor `α` between `0` and `1` weights the authority of the
+//! first master. The slave tracks `α * x_1 + (1 - α) * x_2`, the first master
+//! is pulled towards `α * x_s + (1 - α) * x_2` and the second master towards
+//! `(1 - α) * x_s + α * x_1`. With `α = 1` the first master controls the slave
+//! alone and the second master is guided along, with `α = 0.5` both have the
+//! same authority. Every port of the three-port network has its own
+//! `Stabilizer`, so that the network stays passive with delayed channels.
+use nalgebra::{allocator::Allocator, dimension::Dim, DefaultAllocator, RealField, VectorN};
+
+use crate::{PositionMessage, Stabilizer, PD};
+
+/// Port of the trilateral network.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrilateralPort {
+    /// The first master, whose authority is the dominance factor.
+    FirstMaster,
+    /// The second master.
+    SecondMaster,
+    /// The slave.
+    Slave,
+}
+
+/// Dual-user architecture with a dominance factor.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{PositionMessage, Trilateral, TrilateralPort, PD, TDPA};
+///
+/// let mut trilateral = Trilateral::new(
+///     [PD::<f64, U1>::new(100.0, 0.0), PD::new(100.0, 0.0), PD::new(100.0, 0.0)],
+///     [TDPA::default(), TDPA::default(), TDPA::default()],
+///     1.0,
+/// );
+/// let first = PositionMessage { pos: Vector1::new(0.1), vel: Vector1::new(0.0) };
+/// let second = PositionMessage { pos: Vector1::new(0.0), vel: Vector1::new(0.0) };
+/// let slave = PositionMessage { pos: Vector1::new(0.0), vel: Vector1::new(0.0) };
+///
+/// // The first master is dominant, so both the slave and the second master
+/// // follow it.
+/// let force = trilateral.calculate_force(TrilateralPort::Slave, &first, &second, &slave, 0.001);
+/// assert!((force[0] - 10.0f64).abs() < 1e-12);
+/// let force = trilateral.calculate_force(TrilateralPort::SecondMaster, &first, &second, &slave, 0.001);
+/// assert!((force[0] - 10.0f64).abs() < 1e-12);
+/// ```
+pub struct Trilateral<N, D, S>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    controllers: [PD<N, D>; 3],
+    stabilizers: [S; 3],
+    dominance: N,
+}
+
+impl<N, D, S> Trilateral<N, D, S>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+    S: Stabilizer<N, D>,
+{
+    /// Creates a new `Trilateral` with the `controllers` and `stabilizers` of
+    /// the first master, the second master and the slave and the `dominance`
+    /// factor of the first master.
+    pub fn new(controllers: [PD<N, D>; 3], stabilizers: [S; 3], dominance: N) -> Self {
+        Self::assert_dominance(dominance);
+        Self {
+            controllers,
+            stabilizers,
+            dominance,
+        }
+    }
+
+    /// Calculates the stabilized force commanded to `port` from the latest
+    /// states of the first master, the second master and the slave.
+    pub fn calculate_force(
+        &mut self,
+        port: TrilateralPort,
+        first: &PositionMessage<N, D>,
+        second: &PositionMessage<N, D>,
+        slave: &PositionMessage<N, D>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        let alpha = self.dominance;
+        let beta = N::one() - alpha;
+        let (state, pos_ref, vel_ref) = match port {
+            TrilateralPort::FirstMaster => (
+                first,
+                &slave.pos * alpha + &second.pos * beta,
+                &slave.vel * alpha + &second.vel * beta,
+            ),
+            TrilateralPort::SecondMaster => (
+                second,
+                &slave.pos * beta + &first.pos * alpha,
+                &slave.vel * beta + &first.vel * alpha,
+            ),
+            TrilateralPort::Slave => (
+                slave,
+                &first.pos * alpha + &second.pos * beta,
+                &first.vel * alpha + &second.vel * beta,
+            ),
+        };
+        let index = port as usize;
+        let force =
+            self.controllers[index].calculate_force(&pos_ref, &state.pos, &vel_ref, &state.vel);
+        self.stabilizers[index].stabilize_command(&state.pos, &state.vel, &force, dt)
+    }
+
+    /// Returns the controller of `port`.
+    pub fn controller(&self, port: TrilateralPort) -> &PD<N, D> {
+        &self.controllers[port as usize]
+    }
+
+    /// Returns the controller of `port` mutably.
+    pub fn controller_mut(&mut self, port: TrilateralPort) -> &mut PD<N, D> {
+        &mut self.controllers[port as usize]
+    }
+
+    /// Returns the stabilizer of `port`.
+    pub fn stabilizer(&self, port: TrilateralPort) -> &S {
+        &self.stabilizers[port as usize]
+    }
+
+    /// Returns the stabilizer of `port` mutably.
+    pub fn stabilizer_mut(&mut self, port: TrilateralPort) -> &mut S {
+        &mut self.stabilizers[port as usize]
+    }
+
+    /// Returns the dominance factor.
+    pub fn dominance(&self) -> N {
+        self.dominance
+    }
+
+    /// Sets the dominance factor of the first master between `0` and `1`.
+    pub fn set_dominance(&mut self, dominance: N) {
+        Self::assert_dominance(dominance);
+        self.dominance = dominance;
+    }
+
+    fn assert_dominance(dominance: N) {
+        assert!(
+            dominance >= N::zero() && dominance <= N::one(),
+            "the dominance has to be in [0, 1]"
+        );
+    }
+}
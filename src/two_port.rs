@@ -0,0 +1,361 @@
+//! Two-Port Networks
+//!
+//! A 1-DoF teleoperation system is a two-port network between the operator
+//! and the environment. Each block of the chain (controllers, communication
+//! channel, stabilizers) is described by its chain matrix at the frequency
+//! `omega` in rad/s
+//!
+//! `[force_1, vel_1]^T = [[a, b], [c, d]] * [force_2, vel_2]^T`
+//!
+//! with the force and velocity at the operator side `1` and at the environment
+//! side `2`, where `vel_2` flows out of the block. The chain matrix of a
+//! composed chain is the product of the chain matrices of its blocks.
+//!
+//! The hybrid matrix of the chain
+//!
+//! `[force_1, -vel_2]^T = [[h11, h12], [h21, h22]] * [vel_1, force_2]^T`
+//!
+//! is `[[0, 1], [-1, 0]]` for a perfectly transparent system. Llewellyn's
+//! criterion tells from it whether the chain is stable when coupled to any
+//! passive operator and environment.
+use nalgebra::{convert, Complex, ComplexField, Matrix2, RealField, U1};
+
+use crate::PD;
+
+/// A block of a 1-DoF teleoperation chain.
+pub trait TwoPort<N>
+where
+    N: RealField,
+{
+    /// Returns the chain matrix at the frequency `omega` in rad/s.
+    fn chain_matrix(&self, omega: N) -> Matrix2<Complex<N>>;
+
+    /// Returns the hybrid matrix at the frequency `omega` in rad/s, or `None`
+    /// if it does not exist, e.g. because the block blocks the velocity.
+    fn hybrid_matrix(&self, omega: N) -> Option<Matrix2<Complex<N>>> {
+        let t = self.chain_matrix(omega);
+        let (a, b, c, d) = (t[(0, 0)], t[(0, 1)], t[(1, 0)], t[(1, 1)]);
+        if d.modulus() == N::zero() {
+            return None;
+        }
+        Some(Matrix2::new(
+            b / d,
+            (a * d - b * c) / d,
+            -Complex::new(N::one(), N::zero()) / d,
+            c / d,
+        ))
+    }
+
+    /// Returns the distance of the hybrid matrix at the frequency `omega` in
+    /// rad/s to the one of a perfectly transparent system.
+    fn transparency_error(&self, omega: N) -> Option<N> {
+        let ideal = Matrix2::new(
+            Complex::new(N::zero(), N::zero()),
+            Complex::new(N::one(), N::zero()),
+            Complex::new(-N::one(), N::zero()),
+            Complex::new(N::zero(), N::zero()),
+        );
+        self.hybrid_matrix(omega).map(|h| {
+            (h - ideal)
+                .iter()
+                .map(|e| e.modulus_squared())
+                .fold(N::zero(), |s, e| s + e)
+                .sqrt()
+        })
+    }
+
+    /// Returns whether the block is absolutely stable at the frequency
+    /// `omega` in rad/s by Llewellyn's criterion
+    ///
+    /// `re(h11) >= 0`, `re(h22) >= 0` and
+    /// `2 * re(h11) * re(h22) - re(h12 * h21) - |h12 * h21| >= 0`.
+    ///
+    /// The conditions are checked within a relative tolerance of `1e-9`, so
+    /// that lossless blocks count as stable.
+    fn is_absolutely_stable(&self, omega: N) -> bool {
+        let h = match self.hybrid_matrix(omega) {
+            Some(h) => h,
+            None => return false,
+        };
+        let tolerance = convert::<f64, N>(1e-9);
+        let product = h[(0, 1)] * h[(1, 0)];
+        let margin =
+            (N::one() + N::one()) * h[(0, 0)].re * h[(1, 1)].re - product.re - product.modulus();
+        h[(0, 0)].re >= -tolerance
+            && h[(1, 1)].re >= -tolerance
+            && margin >= -tolerance * (N::one() + product.modulus())
+    }
+
+    /// Returns whether the block is absolutely stable at all `frequencies` in
+    /// rad/s.
+    fn is_absolutely_stable_over(&self, frequencies: &[N]) -> bool {
+        frequencies
+            .iter()
+            .all(|&omega| self.is_absolutely_stable(omega))
+    }
+}
+
+/// A chain of two-ports from the operator to the environment.
+///
+/// ```rust
+/// use haptic_toolbox::{DelayChannel, MechanicalImpedance, TwoPort, TwoPortChain, WaveChannel};
+///
+/// let frequencies: Vec<f64> = (1..1000).map(|i| i as f64 * 0.1).collect();
+///
+/// // Without delay, the direct channel is perfectly transparent.
+/// let direct = TwoPortChain::new().with(DelayChannel::new(0.0));
+/// assert!(direct.transparency_error(10.0).unwrap() < 1e-12);
+///
+/// // With delay, it is not absolutely stable.
+/// let direct = TwoPortChain::new().with(DelayChannel::new(0.1));
+/// assert!(!direct.is_absolutely_stable_over(&frequencies));
+///
+/// // The wave channel is for any delay, also with a damper at the master.
+/// let wave = TwoPortChain::new()
+///     .with(MechanicalImpedance::new(0.0, 2.0, 0.0))
+///     .with(WaveChannel::new(5.0, 0.1));
+/// assert!(wave.is_absolutely_stable_over(&frequencies));
+/// ```
+#[derive(Default)]
+pub struct TwoPortChain<N>
+where
+    N: RealField,
+{
+    two_ports: Vec<Box<dyn TwoPort<N>>>,
+}
+
+impl<N> TwoPortChain<N>
+where
+    N: RealField,
+{
+    /// Creates a new empty `TwoPortChain`, which is perfectly transparent.
+    pub fn new() -> Self {
+        Self {
+            two_ports: Vec::new(),
+        }
+    }
+
+    /// Appends a two-port at the environment side of the chain.
+    pub fn with<T>(mut self, two_port: T) -> Self
+    where
+        T: TwoPort<N> + 'static,
+    {
+        self.push(two_port);
+        self
+    }
+
+    /// Appends a two-port at the environment side of the chain.
+    pub fn push<T>(&mut self, two_port: T)
+    where
+        T: TwoPort<N> + 'static,
+    {
+        self.two_ports.push(Box::new(two_port));
+    }
+
+    /// Returns the number of two-ports in the chain.
+    pub fn len(&self) -> usize {
+        self.two_ports.len()
+    }
+
+    /// Returns whether the chain has no two-ports.
+    pub fn is_empty(&self) -> bool {
+        self.two_ports.is_empty()
+    }
+}
+
+impl<N> TwoPort<N> for TwoPortChain<N>
+where
+    N: RealField,
+{
+    fn chain_matrix(&self, omega: N) -> Matrix2<Complex<N>> {
+        self.two_ports
+            .iter()
+            .fold(identity(), |t, two_port| t * two_port.chain_matrix(omega))
+    }
+}
+
+/// A mass-damper-spring that moves with the velocity at both sides and adds
+/// its force, e.g. the damping injected by a stabilizer or the dynamics of a
+/// device.
+#[derive(Clone, Copy, Debug)]
+pub struct MechanicalImpedance<N>
+where
+    N: RealField,
+{
+    mass: N,
+    damping: N,
+    stiffness: N,
+}
+
+impl<N> MechanicalImpedance<N>
+where
+    N: RealField,
+{
+    /// Creates a new `MechanicalImpedance` with the impedance
+    /// `mass * s + damping + stiffness / s`.
+    pub fn new(mass: N, damping: N, stiffness: N) -> Self {
+        Self {
+            mass,
+            damping,
+            stiffness,
+        }
+    }
+
+    /// Returns the impedance at the frequency `omega` in rad/s, which has to
+    /// be positive since the impedance of a spring is infinite at DC.
+    pub fn impedance(&self, omega: N) -> Complex<N> {
+        assert!(omega > N::zero(), "the frequency has to be positive");
+        Complex::new(self.damping, self.mass * omega - self.stiffness / omega)
+    }
+
+    /// Returns the mass.
+    pub fn mass(&self) -> N {
+        self.mass
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Returns the stiffness.
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+}
+
+impl<N> TwoPort<N> for MechanicalImpedance<N>
+where
+    N: RealField,
+{
+    fn chain_matrix(&self, omega: N) -> Matrix2<Complex<N>> {
+        common_velocity(self.impedance(omega))
+    }
+}
+
+/// A 1-DoF PD coupling transmits the force from the velocity difference of
+/// both sides with the impedance `z = k_d + k_p / s`, so the chain matrix is
+/// `[[1, 0], [1 / z, 1]]`. The frequency has to be positive, since the
+/// impedance of the spring is infinite at DC.
+impl<N> TwoPort<N> for PD<N, U1>
+where
+    N: RealField,
+{
+    fn chain_matrix(&self, omega: N) -> Matrix2<Complex<N>> {
+        assert!(omega > N::zero(), "the frequency has to be positive");
+        let z = Complex::new(self.k_d(), -self.k_p() / omega);
+        let one = Complex::new(N::one(), N::zero());
+        let zero = Complex::new(N::zero(), N::zero());
+        Matrix2::new(one, zero, one / z, one)
+    }
+}
+
+/// A channel that transmits velocity to the environment and force back to
+/// the operator, each delayed by the one-way delay.
+#[derive(Clone, Copy, Debug)]
+pub struct DelayChannel<N>
+where
+    N: RealField,
+{
+    delay: N,
+}
+
+impl<N> DelayChannel<N>
+where
+    N: RealField,
+{
+    /// Creates a new `DelayChannel` with the one-way `delay` in seconds.
+    pub fn new(delay: N) -> Self {
+        assert!(delay >= N::zero(), "the delay must not be negative");
+        Self { delay }
+    }
+
+    /// Returns the one-way delay.
+    pub fn delay(&self) -> N {
+        self.delay
+    }
+}
+
+impl<N> TwoPort<N> for DelayChannel<N>
+where
+    N: RealField,
+{
+    fn chain_matrix(&self, omega: N) -> Matrix2<Complex<N>> {
+        let phase = omega * self.delay;
+        let zero = Complex::new(N::zero(), N::zero());
+        Matrix2::new(
+            Complex::new(phase.cos(), -phase.sin()),
+            zero,
+            zero,
+            Complex::new(phase.cos(), phase.sin()),
+        )
+    }
+}
+
+/// A channel that transmits wave variables with the wave impedance `b`, each
+/// delayed by the one-way delay, like `WAVE`.
+///
+/// It behaves like a lossless transmission line.
+#[derive(Clone, Copy, Debug)]
+pub struct WaveChannel<N>
+where
+    N: RealField,
+{
+    b: N,
+    delay: N,
+}
+
+impl<N> WaveChannel<N>
+where
+    N: RealField,
+{
+    /// Creates a new `WaveChannel` with the wave impedance `b` and the one-way
+    /// `delay` in seconds.
+    pub fn new(b: N, delay: N) -> Self {
+        assert!(b > N::zero(), "the wave impedance has to be positive");
+        assert!(delay >= N::zero(), "the delay must not be negative");
+        Self { b, delay }
+    }
+
+    /// Returns the wave impedance.
+    pub fn b(&self) -> N {
+        self.b
+    }
+
+    /// Returns the one-way delay.
+    pub fn delay(&self) -> N {
+        self.delay
+    }
+}
+
+impl<N> TwoPort<N> for WaveChannel<N>
+where
+    N: RealField,
+{
+    fn chain_matrix(&self, omega: N) -> Matrix2<Complex<N>> {
+        let phase = omega * self.delay;
+        let (sin, cos) = (phase.sin(), phase.cos());
+        Matrix2::new(
+            Complex::new(cos, N::zero()),
+            Complex::new(N::zero(), self.b * sin),
+            Complex::new(N::zero(), sin / self.b),
+            Complex::new(cos, N::zero()),
+        )
+    }
+}
+
+fn identity<N>() -> Matrix2<Complex<N>>
+where
+    N: RealField,
+{
+    common_velocity(Complex::new(N::zero(), N::zero()))
+}
+
+fn common_velocity<N>(z: Complex<N>) -> Matrix2<Complex<N>>
+where
+    N: RealField,
+{
+    let one = Complex::new(N::one(), N::zero());
+    let zero = Complex::new(N::zero(), N::zero());
+    Matrix2::new(one, z, zero, one)
+}
@@ -0,0 +1,163 @@
+//! Virtual Coupling
+//!
+//! Connects the haptic device to a proxy in the simulation with a
+//! spring-damper. The virtual environment only interacts with the proxy, so
+//! the device sees at most the coupling impedance, however stiff the
+//! environment is. Sampling with period `T` keeps a device with the inherent
+//! damping `b` passive as long as `b > K * T / 2 + B` for the coupling
+//! stiffness `K` and damping `B`, which bounds the gains of the coupling.
+use nalgebra::{
+    allocator::Allocator, convert, dimension::Dim, DefaultAllocator, RealField, VectorN,
+};
+
+/// Spring-damper between a haptic device and its simulation proxy.
+///
+/// The gains are limited to the passive region of the device. The damping is
+/// limited first and the stiffness to what the damping leaves.
+///
+/// ```rust
+/// use nalgebra::Vector1;
+/// use haptic_toolbox::VirtualCoupling;
+///
+/// // A device sampled with 1 kHz and an inherent damping of 2 Ns/m.
+/// let mut coupling = VirtualCoupling::new(1000.0, 2.0);
+/// coupling.set_damping(0.5);
+/// assert!((coupling.max_stiffness() - 3000.0f64).abs() < 1e-9);
+///
+/// // Stiffer couplings are cut to the limit.
+/// coupling.set_stiffness(5000.0);
+/// assert_eq!(coupling.stiffness(), coupling.max_stiffness());
+///
+/// let force = coupling.calculate_force(
+///     &Vector1::new(0.0),
+///     &Vector1::new(0.0),
+///     &Vector1::new(0.001),
+///     &Vector1::new(0.0),
+/// );
+/// assert!((force[0] - 3.0f64).abs() < 1e-9);
+/// ```
+#[derive(Clone, Debug)]
+pub struct VirtualCoupling<N>
+where
+    N: RealField,
+{
+    stiffness: N,
+    damping: N,
+    sample_rate: N,
+    device_damping: N,
+}
+
+impl<N> VirtualCoupling<N>
+where
+    N: RealField,
+{
+    /// Creates a new `VirtualCoupling` for a device sampled with
+    /// `sample_rate` in Hz and the inherent `device_damping`. The coupling
+    /// starts with the maximal stiffness and without damping.
+    pub fn new(sample_rate: N, device_damping: N) -> Self {
+        assert!(
+            sample_rate > N::zero(),
+            "the sample rate has to be positive"
+        );
+        assert!(
+            device_damping >= N::zero(),
+            "the device damping must not be negative"
+        );
+        let mut coupling = Self {
+            stiffness: N::zero(),
+            damping: N::zero(),
+            sample_rate,
+            device_damping,
+        };
+        coupling.stiffness = coupling.max_stiffness();
+        coupling
+    }
+
+    /// Calculates the force on the device at `device_pos` and `device_vel`
+    /// from the proxy at `proxy_pos` and `proxy_vel`. The proxy experiences
+    /// the opposite force.
+    pub fn calculate_force<D>(
+        &self,
+        device_pos: &VectorN<N, D>,
+        device_vel: &VectorN<N, D>,
+        proxy_pos: &VectorN<N, D>,
+        proxy_vel: &VectorN<N, D>,
+    ) -> VectorN<N, D>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        (proxy_pos - device_pos) * self.stiffness + (proxy_vel - device_vel) * self.damping
+    }
+
+    /// Returns the maximal damping, which is the damping of the device.
+    pub fn max_damping(&self) -> N {
+        self.device_damping
+    }
+
+    /// Returns the maximal stiffness for the current damping.
+    pub fn max_stiffness(&self) -> N {
+        convert::<f64, N>(2.0) * (self.device_damping - self.damping) * self.sample_rate
+    }
+
+    /// Returns the stiffness.
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+
+    /// Sets the stiffness, which is cut to the maximal stiffness.
+    pub fn set_stiffness(&mut self, stiffness: N) {
+        assert!(stiffness >= N::zero(), "the stiffness must not be negative");
+        self.stiffness = stiffness.min(self.max_stiffness());
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the damping, which is cut to the maximal damping, defaults to
+    /// `0`. The stiffness is cut to the new maximal stiffness.
+    pub fn set_damping(&mut self, damping: N) {
+        assert!(damping >= N::zero(), "the damping must not be negative");
+        self.damping = damping.min(self.max_damping());
+        self.limit();
+    }
+
+    /// Returns the sample rate.
+    pub fn sample_rate(&self) -> N {
+        self.sample_rate
+    }
+
+    /// Sets the sample rate of the device in Hz. The gains are cut to the new
+    /// limits.
+    pub fn set_sample_rate(&mut self, sample_rate: N) {
+        assert!(
+            sample_rate > N::zero(),
+            "the sample rate has to be positive"
+        );
+        self.sample_rate = sample_rate;
+        self.limit();
+    }
+
+    /// Returns the damping of the device.
+    pub fn device_damping(&self) -> N {
+        self.device_damping
+    }
+
+    /// Sets the inherent damping of the device. The gains are cut to the new
+    /// limits.
+    pub fn set_device_damping(&mut self, device_damping: N) {
+        assert!(
+            device_damping >= N::zero(),
+            "the device damping must not be negative"
+        );
+        self.device_damping = device_damping;
+        self.damping = self.damping.min(self.max_damping());
+        self.limit();
+    }
+
+    fn limit(&mut self) {
+        self.stiffness = self.stiffness.min(self.max_stiffness());
+    }
+}
@@ -0,0 +1,333 @@
+//! Virtual Fixtures
+//!
+//! Virtual fixtures assist the operator with forces that depend on the
+//! position of the tool. Guidance fixtures attract the tool towards a path,
+//! forbidden-region fixtures repel it from volumes it must not enter. The
+//! fixtures share the `VirtualFixture` interface and are combined in a
+//! `FixtureSet` that sums their forces every tick.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::VirtualWall;
+
+/// Force of a virtual fixture.
+pub trait VirtualFixture<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Calculates the force of the fixture on the tool at position `pos` with
+    /// velocity `vel`.
+    fn force(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> VectorN<N, D>;
+}
+
+/// The wall is a forbidden half-space.
+impl<N, D> VirtualFixture<N, D> for VirtualWall<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn force(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        VirtualWall::force(self, pos, vel, dt)
+    }
+}
+
+/// Guidance fixture that attracts the tool towards a path.
+///
+/// The path is a polyline through waypoints. The tool is pulled towards the
+/// closest point of the path by a spring, and the motion away from the path
+/// is damped, while the motion along the path stays free.
+///
+/// ```rust
+/// use nalgebra::{Vector2, U2};
+/// use haptic_toolbox::{PathFixture, VirtualFixture};
+///
+/// let mut fixture = PathFixture::<f64, U2>::new(
+///     vec![Vector2::new(0.0, 0.0), Vector2::new(1.0, 0.0)],
+///     100.0,
+///     10.0,
+/// );
+/// let force = fixture.force(&Vector2::new(0.5, 0.1), &Vector2::new(1.0, 0.0), 0.001);
+/// assert!((force - Vector2::new(0.0, -10.0)).norm() < 1e-12);
+/// ```
+#[derive(Clone, Debug)]
+pub struct PathFixture<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    waypoints: Vec<VectorN<N, D>>,
+    stiffness: N,
+    damping: N,
+}
+
+impl<N, D> PathFixture<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `PathFixture` along the `waypoints`.
+    pub fn new(waypoints: Vec<VectorN<N, D>>, stiffness: N, damping: N) -> Self {
+        assert!(!waypoints.is_empty(), "the path needs a waypoint");
+        Self {
+            waypoints,
+            stiffness,
+            damping,
+        }
+    }
+
+    /// Returns the point of the path that is closest to `pos` and the
+    /// direction of the path there, which is `None` for a single waypoint.
+    pub fn closest_point(&self, pos: &VectorN<N, D>) -> (VectorN<N, D>, Option<VectorN<N, D>>) {
+        let mut closest = (self.waypoints[0].clone(), None);
+        let mut min_distance = (pos - &self.waypoints[0]).norm_squared();
+        for segment in self.waypoints.windows(2) {
+            let direction = &segment[1] - &segment[0];
+            let length_squared = direction.norm_squared();
+            if length_squared <= N::zero() {
+                continue;
+            }
+            let t = ((pos - &segment[0]).dot(&direction) / length_squared)
+                .max(N::zero())
+                .min(N::one());
+            let point = &segment[0] + &direction * t;
+            let distance = (pos - &point).norm_squared();
+            if distance < min_distance || closest.1.is_none() {
+                min_distance = distance;
+                closest = (point, Some(direction / length_squared.sqrt()));
+            }
+        }
+        closest
+    }
+
+    /// Returns the waypoints.
+    pub fn waypoints(&self) -> &[VectorN<N, D>] {
+        &self.waypoints
+    }
+
+    /// Returns the stiffness.
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+
+    /// Sets the stiffness towards the path.
+    pub fn set_stiffness(&mut self, stiffness: N) {
+        self.stiffness = stiffness;
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the damping of the motion away from the path.
+    pub fn set_damping(&mut self, damping: N) {
+        self.damping = damping;
+    }
+}
+
+impl<N, D> VirtualFixture<N, D> for PathFixture<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn force(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>, _dt: N) -> VectorN<N, D> {
+        let (point, direction) = self.closest_point(pos);
+        let normal_vel = match direction {
+            Some(direction) => vel - &direction * direction.dot(vel),
+            None => vel.clone(),
+        };
+        (point - pos) * self.stiffness - normal_vel * self.damping
+    }
+}
+
+/// Forbidden-region fixture that repels the tool from a sphere.
+///
+/// ```rust
+/// use nalgebra::{Vector3, U3};
+/// use haptic_toolbox::{SphereFixture, VirtualFixture};
+///
+/// let mut fixture = SphereFixture::<f64, U3>::new(Vector3::zeros(), 0.1, 1000.0, 0.0);
+/// let outside = fixture.force(&Vector3::new(0.2, 0.0, 0.0), &Vector3::zeros(), 0.001);
+/// assert_eq!(outside, Vector3::zeros());
+///
+/// let inside = fixture.force(&Vector3::new(0.09, 0.0, 0.0), &Vector3::zeros(), 0.001);
+/// assert!((inside - Vector3::new(10.0, 0.0, 0.0)).norm() < 1e-9);
+/// ```
+#[derive(Clone, Debug)]
+pub struct SphereFixture<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    center: VectorN<N, D>,
+    radius: N,
+    stiffness: N,
+    damping: N,
+}
+
+impl<N, D> SphereFixture<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `SphereFixture` around `center` with `radius`.
+    pub fn new(center: VectorN<N, D>, radius: N, stiffness: N, damping: N) -> Self {
+        assert!(radius > N::zero(), "the radius has to be positive");
+        Self {
+            center,
+            radius,
+            stiffness,
+            damping,
+        }
+    }
+
+    /// Returns how deep `pos` penetrates the sphere, negative outside.
+    pub fn penetration(&self, pos: &VectorN<N, D>) -> N {
+        self.radius - (pos - &self.center).norm()
+    }
+
+    /// Returns the center.
+    pub fn center(&self) -> &VectorN<N, D> {
+        &self.center
+    }
+
+    /// Returns the radius.
+    pub fn radius(&self) -> N {
+        self.radius
+    }
+
+    /// Returns the stiffness.
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+
+    /// Sets the stiffness of the boundary.
+    pub fn set_stiffness(&mut self, stiffness: N) {
+        self.stiffness = stiffness;
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the damping of the motion into the sphere.
+    pub fn set_damping(&mut self, damping: N) {
+        self.damping = damping;
+    }
+}
+
+impl<N, D> VirtualFixture<N, D> for SphereFixture<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn force(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>, _dt: N) -> VectorN<N, D> {
+        let penetration = self.penetration(pos);
+        let normal = match (pos - &self.center).try_normalize(N::default_epsilon()) {
+            Some(normal) if penetration > N::zero() => normal,
+            _ => return pos.map(|_| N::zero()),
+        };
+        let magnitude = self.stiffness * penetration - self.damping * normal.dot(vel);
+        normal * magnitude.max(N::zero())
+    }
+}
+
+/// Set of fixtures whose forces are summed.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{FixtureSet, PathFixture, SphereFixture, VirtualFixture};
+///
+/// let mut fixtures = FixtureSet::<f64, U1>::new();
+/// fixtures.push_fixture(PathFixture::new(vec![Vector1::new(0.0)], 10.0, 0.0));
+/// fixtures.push_fixture(SphereFixture::new(Vector1::new(1.0), 0.5, 100.0, 0.0));
+///
+/// let force = fixtures.force(&Vector1::new(0.6), &Vector1::new(0.0), 0.001);
+/// assert!((force[0] + 16.0f64).abs() < 1e-9);
+/// ```
+pub struct FixtureSet<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fixtures: Vec<Box<dyn VirtualFixture<N, D>>>,
+}
+
+impl<N, D> Default for FixtureSet<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<N, D> FixtureSet<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `FixtureSet` without fixtures.
+    pub fn new() -> Self {
+        Self {
+            fixtures: Vec::new(),
+        }
+    }
+
+    /// Appends a fixture and returns its index.
+    pub fn push_fixture<F>(&mut self, fixture: F) -> usize
+    where
+        F: VirtualFixture<N, D> + 'static,
+    {
+        self.fixtures.push(Box::new(fixture));
+        self.fixtures.len() - 1
+    }
+
+    /// Removes the fixture with `index`. The indices of later fixtures
+    /// shift down.
+    pub fn remove_fixture(&mut self, index: usize) {
+        self.fixtures.remove(index);
+    }
+
+    /// Returns the number of fixtures.
+    pub fn len(&self) -> usize {
+        self.fixtures.len()
+    }
+
+    /// Returns `true` if there are no fixtures.
+    pub fn is_empty(&self) -> bool {
+        self.fixtures.is_empty()
+    }
+}
+
+impl<N, D> VirtualFixture<N, D> for FixtureSet<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn force(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        self.fixtures
+            .iter_mut()
+            .fold(pos.map(|_| N::zero()), |force, fixture| {
+                force + fixture.force(pos, vel, dt)
+            })
+    }
+}
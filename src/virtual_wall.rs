@@ -0,0 +1,127 @@
+//! Virtual Wall
+//!
+//! Renders a one-sided spring-damper that only pushes back while the device
+//! penetrates a plane. Increasing the stiffness with the penetration depth
+//! keeps light contacts soft while deep penetrations feel hard. Since sampled
+//! springs can generate energy, the rendered force can optionally be limited
+//! by an `EnergyTank`.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+
+use crate::EnergyTank;
+
+/// One-sided spring-damper wall.
+///
+/// ```rust
+/// use nalgebra::Vector3;
+/// use haptic_toolbox::VirtualWall;
+///
+/// // Wall at z = 0 with free space above it.
+/// let mut wall = VirtualWall::new(Vector3::z(), 0.0, 1000.0, 5.0);
+///
+/// let free_space = wall.force(&Vector3::new(0.0, 0.0, 0.01), &Vector3::zeros(), 0.001);
+/// assert_eq!(free_space, Vector3::zeros());
+///
+/// let contact = wall.force(&Vector3::new(0.0, 0.0, -0.01), &Vector3::zeros(), 0.001);
+/// assert!((contact - Vector3::new(0.0, 0.0, 10.0)).norm() < 1e-12);
+/// ```
+#[derive(Debug)]
+pub struct VirtualWall<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    normal: VectorN<N, D>,
+    offset: N,
+    stiffness: N,
+    stiffness_growth: N,
+    damping: N,
+    energy_tank: Option<EnergyTank<N>>,
+}
+
+impl<N, D> VirtualWall<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `VirtualWall` on the plane `normal * x = offset`.
+    ///
+    /// The `normal` points from the wall into free space and gets normalized.
+    pub fn new(normal: VectorN<N, D>, offset: N, stiffness: N, damping: N) -> Self {
+        Self {
+            normal: normal.normalize(),
+            offset,
+            stiffness,
+            stiffness_growth: N::zero(),
+            damping,
+            energy_tank: None,
+        }
+    }
+
+    /// Calculates the force rendered at position `pos` with velocity `vel`.
+    pub fn force(&mut self, pos: &VectorN<N, D>, vel: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        let penetration = self.penetration(pos);
+        if penetration <= N::zero() {
+            return VectorN::zeros();
+        }
+
+        let stiffness = self.stiffness + self.stiffness_growth * penetration;
+        let magnitude = stiffness * penetration - self.damping * self.normal.dot(vel);
+        let force = &self.normal * magnitude.max(N::zero());
+
+        match &mut self.energy_tank {
+            Some(energy_tank) => energy_tank.modulate(&force, vel, dt),
+            None => force,
+        }
+    }
+
+    /// Returns how deep `pos` penetrates the wall, negative in free space.
+    pub fn penetration(&self, pos: &VectorN<N, D>) -> N {
+        self.offset - self.normal.dot(pos)
+    }
+
+    /// Returns the stiffness at zero penetration.
+    pub fn stiffness(&self) -> N {
+        self.stiffness
+    }
+
+    /// Sets the stiffness at zero penetration.
+    pub fn set_stiffness(&mut self, stiffness: N) {
+        self.stiffness = stiffness;
+    }
+
+    /// Returns how much the stiffness increases per unit of penetration.
+    pub fn stiffness_growth(&self) -> N {
+        self.stiffness_growth
+    }
+
+    /// Sets how much the stiffness increases per unit of penetration.
+    pub fn set_stiffness_growth(&mut self, stiffness_growth: N) {
+        self.stiffness_growth = stiffness_growth;
+    }
+
+    /// Returns the damping.
+    pub fn damping(&self) -> N {
+        self.damping
+    }
+
+    /// Sets the damping.
+    pub fn set_damping(&mut self, damping: N) {
+        self.damping = damping;
+    }
+
+    /// Returns the energy tank limiting the rendered force.
+    pub fn energy_tank(&self) -> Option<&EnergyTank<N>> {
+        self.energy_tank.as_ref()
+    }
+
+    /// Sets an energy tank that limits the energy the wall may output.
+    pub fn set_energy_tank(&mut self, energy_tank: Option<EnergyTank<N>>) {
+        self.energy_tank = energy_tank;
+    }
+}
@@ -2,13 +2,460 @@
 //!
 //! Wave transformation is a control strategy for bilateral haptic data exchange
 //! with time delay. [[1]](https://www.researchgate.net/publication/238498174_Some_Recent_Approaches_to_Teleoperation_in_the_Presence_of_Time_Delay)
+//!
+//! `WaveEncoder` transforms the force and velocity of a port into the wave it
+//! sends and `WaveDecoder` transforms the `WaveVariables` at a port back into
+//! force and velocity.
+//!
+//! Reflections of the waves at the ports cause oscillations with long delays,
+//! which can be damped by low-pass filtering the received waves with
+//! `filter_master` and `filter_slave`.
+//!
+//! Lost packets make the positions of master and slave drift apart, since the
+//! integral of a wave is related to the position of the port. With
+//! `encode_master_packet` and `encode_slave_packet` the integral and the
+//! energy of the sent waves are transmitted along with the wave, so that the
+//! receiver can reconstruct the wave integral once packets arrive again,
+//! without ever outputting more wave energy than was sent. What is output while
+//! packets are missing is selected by the `WaveLossPolicy`.
+//!
+//! The wave channel is passive for any constant delay, but a varying delay
+//! compresses or stretches the received waves, which can generate energy.
+//! `correct_master` and `correct_slave` scale the received waves by
+//! `sqrt(1 - dT/dt)`, with the delay derivative estimated from the send and
+//! receive timestamps of the packets.
 use nalgebra::{
     allocator::Allocator,
     convert,
     dimension::{Dim, DimName},
     DefaultAllocator, RealField, VectorN,
 };
-use std::marker::PhantomData;
+use num_traits::Zero;
+
+/// Waves at a port.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WaveVariables<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Wave travelling from the master to the slave.
+    pub u: VectorN<N, D>,
+    /// Wave travelling from the slave to the master.
+    pub v: VectorN<N, D>,
+}
+
+/// Transforms force and velocity into the wave sent by a port.
+///
+/// The wave variables are related to force and velocity at the master by
+///
+/// `u = (force_m + b * vel_m) / sqrt(2 * b)`
+///
+/// `v = (force_m - b * vel_m) / sqrt(2 * b)`
+///
+/// and at the slave by
+///
+/// `u = (force_s + b * vel_s) / sqrt(2 * b)`
+///
+/// `v = (force_s - b * vel_s) / sqrt(2 * b)`
+///
+/// where the master sends `u` and the slave sends `v`.
+///
+/// ```rust
+/// use nalgebra::Vector3;
+/// use haptic_toolbox::{WaveDecoder, WaveEncoder, WaveVariables};
+///
+/// let (encoder, decoder) = (WaveEncoder::new(2.0), WaveDecoder::new(2.0));
+/// let force = Vector3::new(1.0, -2.0, 0.5);
+/// let vel = Vector3::new(0.1, 0.2, -0.3);
+///
+/// // Without delay both ports are in the same state.
+/// let waves = WaveVariables {
+///     u: encoder.encode_master(&force, &vel),
+///     v: encoder.encode_slave(&force, &vel),
+/// };
+///
+/// let (force_m, vel_m) = decoder.decode_master(&waves);
+/// let (force_s, vel_s) = decoder.decode_slave(&waves);
+/// assert!((force_m - force).norm() < 1e-12);
+/// assert!((vel_m - vel).norm() < 1e-12);
+/// assert!((force_s - force).norm() < 1e-12);
+/// assert!((vel_s - vel).norm() < 1e-12);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct WaveEncoder<N>
+where
+    N: RealField,
+{
+    b: N,
+}
+
+impl<N> WaveEncoder<N>
+where
+    N: RealField,
+{
+    /// Creates a new `WaveEncoder` with the wave impedance `b`.
+    pub fn new(b: N) -> Self {
+        assert!(b > N::zero(), "the wave impedance has to be positive");
+        Self { b }
+    }
+
+    /// Encodes the force and velocity of the master into the wave `u` sent to
+    /// the slave.
+    pub fn encode_master<D>(&self, force_m: &VectorN<N, D>, vel_m: &VectorN<N, D>) -> VectorN<N, D>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        (force_m + vel_m * self.b) / (self.b * convert(2.0)).sqrt()
+    }
+
+    /// Encodes the force and velocity of the slave into the wave `v` sent to
+    /// the master.
+    pub fn encode_slave<D>(&self, force_s: &VectorN<N, D>, vel_s: &VectorN<N, D>) -> VectorN<N, D>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        (force_s - vel_s * self.b) / (self.b * convert(2.0)).sqrt()
+    }
+
+    /// Returns the wave impedance b.
+    pub fn b(&self) -> N {
+        self.b
+    }
+}
+
+/// Transforms the waves at a port into force and velocity.
+///
+/// This is the inverse of the transformation described in `WaveEncoder`.
+#[derive(Clone, Copy, Debug)]
+pub struct WaveDecoder<N>
+where
+    N: RealField,
+{
+    b: N,
+}
+
+impl<N> WaveDecoder<N>
+where
+    N: RealField,
+{
+    /// Creates a new `WaveDecoder` with the wave impedance `b`.
+    pub fn new(b: N) -> Self {
+        assert!(b > N::zero(), "the wave impedance has to be positive");
+        Self { b }
+    }
+
+    /// Decodes force and velocity `(force_m, vel_m)` of the master from the
+    /// sent wave `u` and the received wave `v`.
+    pub fn decode_master<D>(&self, waves: &WaveVariables<N, D>) -> (VectorN<N, D>, VectorN<N, D>)
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        self.decode(&waves.u, &waves.v)
+    }
+
+    /// Decodes force and velocity `(force_s, vel_s)` of the slave from the
+    /// received wave `u` and the sent wave `v`.
+    pub fn decode_slave<D>(&self, waves: &WaveVariables<N, D>) -> (VectorN<N, D>, VectorN<N, D>)
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        self.decode(&waves.u, &waves.v)
+    }
+
+    /// Returns the wave impedance b.
+    pub fn b(&self) -> N {
+        self.b
+    }
+
+    fn decode<D>(&self, u: &VectorN<N, D>, v: &VectorN<N, D>) -> (VectorN<N, D>, VectorN<N, D>)
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        let force = (u + v) * (self.b / convert(2.0)).sqrt();
+        let vel = (u - v) / (self.b * convert(2.0)).sqrt();
+        (force, vel)
+    }
+}
+
+/// First-order low-pass filter for a received wave.
+///
+/// The filter state stores the energy `|y|^2 / alpha`. If the filter
+/// coefficient `alpha` decreases, e.g. because the cutoff was lowered, the
+/// state is scaled so that the stored energy does not grow, which keeps the
+/// filter passive.
+#[derive(Debug)]
+struct WaveFilter<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    state: VectorN<N, D>,
+    alpha: N,
+}
+
+impl<N, D> WaveFilter<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn new() -> Self {
+        Self {
+            state: Zero::zero(),
+            alpha: N::one(),
+        }
+    }
+}
+
+impl<N, D> WaveFilter<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn apply(&mut self, wave: &VectorN<N, D>, cutoff: Option<N>, dt: N) -> VectorN<N, D> {
+        let alpha = match cutoff {
+            Some(cutoff) => {
+                let time_constant = N::one() / (N::two_pi() * cutoff);
+                dt / (time_constant + dt)
+            }
+            None => N::one(),
+        };
+        if alpha < self.alpha {
+            self.state *= (alpha / self.alpha).sqrt();
+        }
+        self.state += (wave - &self.state) * alpha;
+        self.alpha = alpha;
+        self.state.clone()
+    }
+}
+
+/// Wave sample with the data needed for the wave integral correction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WavePacket<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// The wave.
+    pub wave: VectorN<N, D>,
+    /// The integral of all waves sent so far.
+    pub integral: VectorN<N, D>,
+    /// The energy `∫ |wave|^2 dt` of all waves sent so far.
+    pub energy: N,
+}
+
+/// Reconstruction of the waves of lost packets.
+///
+/// Whatever the policy, the energy of the output waves never exceeds the
+/// energy of the sent waves known from the received packets, so reconstructed
+/// waves are attenuated as far as needed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum WaveLossPolicy<N>
+where
+    N: RealField,
+{
+    /// Outputs zero.
+    #[default]
+    HoldZero,
+    /// Outputs the last wave.
+    HoldLast,
+    /// Outputs the last wave scaled by the factor for every lost packet.
+    ScaledHold(N),
+}
+
+/// Energy of the sent waves known from the received packets and energy of the
+/// output waves at a port.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WaveEnergyAudit<N>
+where
+    N: RealField,
+{
+    /// Energy of the sent waves known from the received packets.
+    pub received_energy: N,
+    /// Energy of the output waves.
+    pub output_energy: N,
+}
+
+/// Integral and energy of a wave.
+#[derive(Debug)]
+struct WaveIntegral<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    integral: VectorN<N, D>,
+    energy: N,
+}
+
+impl<N, D> WaveIntegral<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn new() -> Self {
+        Self {
+            integral: Zero::zero(),
+            energy: N::zero(),
+        }
+    }
+}
+
+impl<N, D> WaveIntegral<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn integrate(&mut self, wave: &VectorN<N, D>, dt: N) {
+        self.integral += wave * dt;
+        self.energy += wave.norm_squared() * dt;
+    }
+}
+
+/// Reconstructs the received waves from the transmitted wave integrals.
+#[derive(Debug)]
+struct WaveIntegralReceiver<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    received: WaveIntegral<N, D>,
+    output: WaveIntegral<N, D>,
+    last: VectorN<N, D>,
+}
+
+impl<N, D> WaveIntegralReceiver<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn new() -> Self {
+        Self {
+            received: WaveIntegral::new(),
+            output: WaveIntegral::new(),
+            last: Zero::zero(),
+        }
+    }
+}
+
+impl<N, D> WaveIntegralReceiver<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn receive(
+        &mut self,
+        packet: Option<&WavePacket<N, D>>,
+        loss_policy: WaveLossPolicy<N>,
+        dt: N,
+    ) -> VectorN<N, D> {
+        if let Some(packet) = packet {
+            // Reordered packets carry less energy than the latest one.
+            if packet.energy >= self.received.energy {
+                self.received.integral = packet.integral.clone();
+                self.received.energy = packet.energy;
+            }
+        }
+        if dt <= N::zero() {
+            return self.output.integral.map(|_| N::zero());
+        }
+
+        let mut wave = match (packet, loss_policy) {
+            (Some(_), _) => (&self.received.integral - &self.output.integral) / dt,
+            (None, WaveLossPolicy::HoldZero) => self.last.map(|_| N::zero()),
+            (None, WaveLossPolicy::HoldLast) => self.last.clone(),
+            (None, WaveLossPolicy::ScaledHold(scale)) => &self.last * scale,
+        };
+        supervise_energy(&mut wave, self.received.energy, self.output.energy, dt);
+        self.output.integrate(&wave, dt);
+        self.last = wave.clone();
+        wave
+    }
+
+    fn energy_audit(&self) -> WaveEnergyAudit<N> {
+        WaveEnergyAudit {
+            received_energy: self.received.energy,
+            output_energy: self.output.energy,
+        }
+    }
+}
+
+/// Attenuates `wave` so that the energy it outputs over `dt` does not exceed
+/// the energy that was sent but not yet output.
+pub(crate) fn supervise_energy<N, D>(
+    wave: &mut VectorN<N, D>,
+    sent_energy: N,
+    output_energy: N,
+    dt: N,
+) where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    let available_energy = (sent_energy - output_energy).max(N::zero());
+    let energy = wave.norm_squared() * dt;
+    if energy > available_energy {
+        *wave *= (available_energy / energy).sqrt();
+    }
+}
+
+/// Gain correction for a time-varying delay.
+#[derive(Clone, Copy, Debug)]
+struct DelayCorrection<N>
+where
+    N: RealField,
+{
+    last_times: Option<(N, N)>,
+}
+
+impl<N> DelayCorrection<N>
+where
+    N: RealField,
+{
+    fn correct<D>(&mut self, wave: &VectorN<N, D>, sent_time: N, received_time: N) -> VectorN<N, D>
+    where
+        D: Dim,
+        DefaultAllocator: Allocator<N, D>,
+    {
+        // With the delay `T = received_time - sent_time`, `1 - dT/dt` is the
+        // ratio of the send and the receive intervals.
+        let gain = match self.last_times {
+            Some((last_sent_time, last_received_time)) => {
+                let sent_interval = sent_time - last_sent_time;
+                let received_interval = received_time - last_received_time;
+                if sent_interval > N::zero() && received_interval > N::zero() {
+                    (sent_interval / received_interval).sqrt()
+                } else {
+                    N::zero()
+                }
+            }
+            None => N::one(),
+        };
+        let newer = match self.last_times {
+            Some((last_sent_time, _)) => sent_time > last_sent_time,
+            None => true,
+        };
+        if newer {
+            self.last_times = Some((sent_time, received_time));
+        }
+        wave * gain
+    }
+}
 
 pub struct WAVE<N, D>
 where
@@ -17,7 +464,16 @@ where
     DefaultAllocator: Allocator<N, D>,
 {
     b: N,
-    _phantom: PhantomData<D>,
+    cutoff: Option<N>,
+    master_filter: WaveFilter<N, D>,
+    slave_filter: WaveFilter<N, D>,
+    master_sent: WaveIntegral<N, D>,
+    slave_sent: WaveIntegral<N, D>,
+    master_receiver: WaveIntegralReceiver<N, D>,
+    slave_receiver: WaveIntegralReceiver<N, D>,
+    loss_policy: WaveLossPolicy<N>,
+    master_delay_correction: DelayCorrection<N>,
+    slave_delay_correction: DelayCorrection<N>,
 }
 
 impl<N, D> WAVE<N, D>
@@ -28,52 +484,391 @@ where
 {
     /// Creates a new WAVE controller with the wave impedance `b`.
     pub fn new(b: N) -> Self {
+        assert!(b > N::zero(), "the wave impedance has to be positive");
         Self {
             b,
-            _phantom: PhantomData,
+            cutoff: None,
+            master_filter: WaveFilter::new(),
+            slave_filter: WaveFilter::new(),
+            master_sent: WaveIntegral::new(),
+            slave_sent: WaveIntegral::new(),
+            master_receiver: WaveIntegralReceiver::new(),
+            slave_receiver: WaveIntegralReceiver::new(),
+            loss_policy: WaveLossPolicy::default(),
+            master_delay_correction: DelayCorrection { last_times: None },
+            slave_delay_correction: DelayCorrection { last_times: None },
+        }
+    }
+
+    /// Encodes the force and velocity of the master into the wave `u` that is
+    /// sent to the slave.
+    ///
+    /// The wave variables at the master port are related to its force and
+    /// velocity by
+    ///
+    /// `u = (force_m + b * vel_m) / sqrt(2 * b)`
+    ///
+    /// `v = (force_m - b * vel_m) / sqrt(2 * b)`
+    ///
+    /// where `v` is the wave received from the slave.
+    pub fn encode_master(&self, force_m: &VectorN<N, D>, vel_m: &VectorN<N, D>) -> VectorN<N, D> {
+        self.encoder().encode_master(force_m, vel_m)
+    }
+
+    /// Decodes force and velocity `(force_m, vel_m)` of the master from the
+    /// sent wave `u` and the wave `v` received from the slave.
+    ///
+    /// This is the inverse of the transformation described in
+    /// `encode_master`.
+    pub fn decode_master(
+        &self,
+        u: &VectorN<N, D>,
+        v: &VectorN<N, D>,
+    ) -> (VectorN<N, D>, VectorN<N, D>) {
+        self.decoder().decode(u, v)
+    }
+
+    /// Encodes the force and velocity of the slave into the wave `v` that is
+    /// sent to the master.
+    ///
+    /// The wave variables at the slave port are related to its force and
+    /// velocity by
+    ///
+    /// `u = (force_s + b * vel_s) / sqrt(2 * b)`
+    ///
+    /// `v = (force_s - b * vel_s) / sqrt(2 * b)`
+    ///
+    /// where `u` is the wave received from the master.
+    pub fn encode_slave(&self, force_s: &VectorN<N, D>, vel_s: &VectorN<N, D>) -> VectorN<N, D> {
+        self.encoder().encode_slave(force_s, vel_s)
+    }
+
+    /// Decodes force and velocity `(force_s, vel_s)` of the slave from the
+    /// wave `u` received from the master and the sent wave `v`.
+    ///
+    /// This is the inverse of the transformation described in
+    /// `encode_slave`.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector3, U3};
+    /// use haptic_toolbox::WAVE;
+    ///
+    /// let wave = WAVE::<f64, U3>::new(2.0);
+    /// let force = Vector3::new(1.0, -2.0, 0.5);
+    /// let vel = Vector3::new(0.1, 0.2, -0.3);
+    ///
+    /// // Without delay both ports are in the same state, so the waves sent by
+    /// // one side are exactly the ones the other side receives.
+    /// let u = wave.encode_master(&force, &vel);
+    /// let v = wave.encode_slave(&force, &vel);
+    ///
+    /// let (force_m, vel_m) = wave.decode_master(&u, &v);
+    /// let (force_s, vel_s) = wave.decode_slave(&u, &v);
+    /// assert!((force_m - force).norm() < 1e-12);
+    /// assert!((vel_m - vel).norm() < 1e-12);
+    /// assert!((force_s - force).norm() < 1e-12);
+    /// assert!((vel_s - vel).norm() < 1e-12);
+    /// ```
+    pub fn decode_slave(
+        &self,
+        u: &VectorN<N, D>,
+        v: &VectorN<N, D>,
+    ) -> (VectorN<N, D>, VectorN<N, D>) {
+        self.decoder().decode(u, v)
+    }
+
+    /// Encodes the force and velocity of the master like `encode_master` into
+    /// a packet that also carries the integral and the energy of all sent
+    /// waves.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::WAVE;
+    ///
+    /// let mut wave = WAVE::<f64, U1>::new(2.0);
+    /// let dt = 0.001;
+    ///
+    /// let (mut sent, mut received) = (0.0, 0.0);
+    /// for i in 0..20 {
+    ///     let vel = Vector1::new(if i < 10 { 1.0 } else { -1.0 });
+    ///     let packet = wave.encode_master_packet(&Vector1::new(0.0), &vel, dt);
+    ///     sent += packet.wave[0] * dt;
+    ///
+    ///     // Two packets are lost on the way to the slave.
+    ///     let lost = i == 3 || i == 4;
+    ///     let u = wave.receive_slave(if lost { None } else { Some(&packet) }, dt);
+    ///     received += u[0] * dt;
+    /// }
+    ///
+    /// // The integral of the received waves caught up again.
+    /// assert!((sent - received).abs() < 1e-12);
+    /// ```
+    pub fn encode_master_packet(
+        &mut self,
+        force_m: &VectorN<N, D>,
+        vel_m: &VectorN<N, D>,
+        dt: N,
+    ) -> WavePacket<N, D> {
+        let wave = self.encode_master(force_m, vel_m);
+        self.master_sent.integrate(&wave, dt);
+        WavePacket {
+            wave,
+            integral: self.master_sent.integral.clone(),
+            energy: self.master_sent.energy,
+        }
+    }
+
+    /// Encodes the force and velocity of the slave like `encode_slave` into a
+    /// packet that also carries the integral and the energy of all sent
+    /// waves.
+    pub fn encode_slave_packet(
+        &mut self,
+        force_s: &VectorN<N, D>,
+        vel_s: &VectorN<N, D>,
+        dt: N,
+    ) -> WavePacket<N, D> {
+        let wave = self.encode_slave(force_s, vel_s);
+        self.slave_sent.integrate(&wave, dt);
+        WavePacket {
+            wave,
+            integral: self.slave_sent.integral.clone(),
+            energy: self.slave_sent.energy,
         }
     }
 
+    /// Returns the wave `v` received by the master, given the latest packet
+    /// of the slave or `None` if it was lost.
+    ///
+    /// The wave is reconstructed so that its integral follows the integral
+    /// of the sent waves, as far as the energy of the sent waves allows.
+    pub fn receive_master(&mut self, packet: Option<&WavePacket<N, D>>, dt: N) -> VectorN<N, D> {
+        self.master_receiver.receive(packet, self.loss_policy, dt)
+    }
+
+    /// Returns the wave `u` received by the slave, given the latest packet
+    /// of the master or `None` if it was lost.
+    ///
+    /// The wave is reconstructed so that its integral follows the integral
+    /// of the sent waves, as far as the energy of the sent waves allows.
+    pub fn receive_slave(&mut self, packet: Option<&WavePacket<N, D>>, dt: N) -> VectorN<N, D> {
+        self.slave_receiver.receive(packet, self.loss_policy, dt)
+    }
+
+    /// Corrects the wave `v` received by the master for a time-varying
+    /// delay, given the time the slave sent it and the time it was received.
+    ///
+    /// The wave is scaled by `sqrt(1 - dT/dt)`, so that the energy of the
+    /// received waves equals the energy of the sent waves however the delay
+    /// `T` varies. Waves that were sent before the last corrected one are
+    /// dropped. The first wave is passed unchanged.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::WAVE;
+    ///
+    /// let mut wave = WAVE::<f64, U1>::new(2.0);
+    /// let dt = 0.001;
+    ///
+    /// let (mut sent, mut received, mut corrected) = (0.0, 0.0, 0.0);
+    /// for i in 1..1000 {
+    ///     let v = Vector1::new((i as f64 * 0.01).sin());
+    ///     // The delay grows from 10 ms by 0.5 ms per sample.
+    ///     let sent_time = i as f64 * dt;
+    ///     let received_time = sent_time + 0.01 + i as f64 * 0.0005;
+    ///     let corrected_v = wave.correct_master(&v, sent_time, received_time);
+    ///
+    ///     if i > 1 {
+    ///         sent += v.norm_squared() * dt;
+    ///         received += v.norm_squared() * dt * 1.5;
+    ///         corrected += corrected_v.norm_squared() * dt * 1.5;
+    ///     }
+    /// }
+    ///
+    /// // The stretched waves would output 50 % more energy than was sent.
+    /// assert!(received > sent * 1.49);
+    /// assert!((corrected - sent).abs() < 1e-9);
+    /// ```
+    pub fn correct_master(
+        &mut self,
+        v: &VectorN<N, D>,
+        sent_time: N,
+        received_time: N,
+    ) -> VectorN<N, D> {
+        self.master_delay_correction
+            .correct(v, sent_time, received_time)
+    }
+
+    /// Corrects the wave `u` received by the slave for a time-varying delay
+    /// like `correct_master`, given the time the master sent it and the time
+    /// it was received.
+    pub fn correct_slave(
+        &mut self,
+        u: &VectorN<N, D>,
+        sent_time: N,
+        received_time: N,
+    ) -> VectorN<N, D> {
+        self.slave_delay_correction
+            .correct(u, sent_time, received_time)
+    }
+
+    /// Returns the energy audit of the waves received by the master.
+    pub fn master_energy_audit(&self) -> WaveEnergyAudit<N> {
+        self.master_receiver.energy_audit()
+    }
+
+    /// Returns the energy audit of the waves received by the slave.
+    pub fn slave_energy_audit(&self) -> WaveEnergyAudit<N> {
+        self.slave_receiver.energy_audit()
+    }
+
+    /// Returns the policy for lost packets.
+    pub fn loss_policy(&self) -> WaveLossPolicy<N> {
+        self.loss_policy
+    }
+
+    /// Sets the policy for lost packets, defaults to
+    /// `WaveLossPolicy::HoldZero`.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::{WaveLossPolicy, WAVE};
+    ///
+    /// let mut wave = WAVE::<f64, U1>::new(2.0);
+    /// wave.set_loss_policy(WaveLossPolicy::ScaledHold(0.5));
+    /// let dt = 0.001;
+    ///
+    /// for i in 0..20 {
+    ///     let vel = Vector1::new((i as f64 * 0.3).sin());
+    ///     let packet = wave.encode_master_packet(&Vector1::new(0.0), &vel, dt);
+    ///     let lost = i % 4 == 1;
+    ///     wave.receive_slave(if lost { None } else { Some(&packet) }, dt);
+    ///
+    ///     // Reconstructed waves never inject energy.
+    ///     let audit = wave.slave_energy_audit();
+    ///     assert!(audit.output_energy <= audit.received_energy + 1e-12);
+    /// }
+    /// ```
+    pub fn set_loss_policy(&mut self, loss_policy: WaveLossPolicy<N>) {
+        if let WaveLossPolicy::ScaledHold(scale) = loss_policy {
+            assert!(
+                scale >= N::zero() && scale <= N::one(),
+                "the hold scale has to be within [0, 1]"
+            );
+        }
+        self.loss_policy = loss_policy;
+    }
+
     /// Calculates the input wave by the master.
+    #[deprecated(note = "use `encode_master` instead")]
     pub fn calculate_u_m(&self, force_m: &VectorN<N, D>, vel_m: &VectorN<N, D>) -> VectorN<N, D> {
-        (force_m + vel_m * self.b) / (self.b * convert(2.0)).sqrt()
+        self.encode_master(force_m, vel_m)
     }
 
     /// Calculates the input wave by the slave.
+    #[deprecated(note = "the wave sent by the slave is `v`, use `encode_slave` instead")]
     pub fn calculate_u_s(&self, force_s: &VectorN<N, D>, vel_s: &VectorN<N, D>) -> VectorN<N, D> {
-        (force_s - vel_s * self.b) / (self.b * convert(2.0)).sqrt()
+        self.encode_slave(force_s, vel_s)
     }
 
     /// Calculates the output wave by the master.
+    #[deprecated(
+        note = "the wave received by the master is the one encoded by the slave, use `encode_slave` instead"
+    )]
     pub fn calculate_v_m(&self, force_m: &VectorN<N, D>, vel_m: &VectorN<N, D>) -> VectorN<N, D> {
-        self.calculate_u_s(force_m, vel_m)
+        self.encode_slave(force_m, vel_m)
     }
 
     /// Calculates the output wave by the slave.
+    #[deprecated(
+        note = "the wave received by the slave is the one encoded by the master, use `encode_master` instead"
+    )]
     pub fn calculate_v_s(&self, force_s: &VectorN<N, D>, vel_s: &VectorN<N, D>) -> VectorN<N, D> {
-        self.calculate_u_m(force_s, vel_s)
+        self.encode_master(force_s, vel_s)
     }
 
     /// Calculates the force for the master.
+    #[deprecated(note = "use `decode_master` instead")]
     pub fn calculate_force_m(&self, u_m: &VectorN<N, D>, v_m: &VectorN<N, D>) -> VectorN<N, D> {
-        (u_m + v_m) * (self.b / convert(2.0)).sqrt()
+        self.decode_master(u_m, v_m).0
     }
 
     /// Calculates the force for the slave.
+    #[deprecated(note = "use `decode_slave` instead")]
     pub fn calculate_force_s(&self, u_s: &VectorN<N, D>, v_s: &VectorN<N, D>) -> VectorN<N, D> {
-        (u_s + v_s) * (self.b / convert(2.0)).sqrt()
+        self.decode_slave(v_s, u_s).0
     }
 
     /// Calculates the velocity for the master.
+    #[deprecated(
+        note = "this is not the velocity decoded from the waves, use `decode_master` instead"
+    )]
     pub fn calculate_vel_m(&self, u_m: &VectorN<N, D>, vel_m: &VectorN<N, D>) -> VectorN<N, D> {
         (u_m - vel_m) / (self.b * convert(2.0))
     }
 
     /// Calculates the velocity for the slave.
+    #[deprecated(
+        note = "this is not the velocity decoded from the waves, use `decode_slave` instead"
+    )]
     pub fn calculate_vel_s(&self, u_s: &VectorN<N, D>, vel_s: &VectorN<N, D>) -> VectorN<N, D> {
         (u_s + vel_s) / (self.b * convert(2.0))
     }
 
+    /// Filters the wave `v` received by the master.
+    ///
+    /// ```rust
+    /// use nalgebra::{Vector1, U1};
+    /// use haptic_toolbox::WAVE;
+    ///
+    /// let mut wave = WAVE::<f64, U1>::new(2.0);
+    /// wave.set_cutoff(Some(10.0));
+    ///
+    /// let (mut energy_in, mut energy_out) = (0.0, 0.0);
+    /// for i in 0..1000 {
+    ///     // The cutoff can be changed online without generating energy.
+    ///     if i == 500 {
+    ///         wave.set_cutoff(Some(1.0));
+    ///     }
+    ///     let v = Vector1::new(if i % 20 < 10 { 1.0 } else { -1.0 });
+    ///     let filtered = wave.filter_master(&v, 0.001);
+    ///     energy_in += v.norm_squared();
+    ///     energy_out += filtered.norm_squared();
+    ///     assert!(energy_out <= energy_in);
+    /// }
+    /// ```
+    pub fn filter_master(&mut self, v: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        self.master_filter.apply(v, self.cutoff, dt)
+    }
+
+    /// Filters the wave `u` received by the slave.
+    pub fn filter_slave(&mut self, u: &VectorN<N, D>, dt: N) -> VectorN<N, D> {
+        self.slave_filter.apply(u, self.cutoff, dt)
+    }
+
+    /// Returns the cutoff frequency of the wave filters.
+    pub fn cutoff(&self) -> Option<N> {
+        self.cutoff
+    }
+
+    /// Sets the cutoff frequency in Hz of the wave filters, defaults to `None`
+    /// for no filtering.
+    pub fn set_cutoff(&mut self, cutoff: Option<N>) {
+        if let Some(cutoff) = cutoff {
+            assert!(cutoff > N::zero(), "the cutoff has to be positive");
+        }
+        self.cutoff = cutoff;
+    }
+
+    /// Returns the encoder for the current wave impedance.
+    pub fn encoder(&self) -> WaveEncoder<N> {
+        WaveEncoder::new(self.b)
+    }
+
+    /// Returns the decoder for the current wave impedance.
+    pub fn decoder(&self) -> WaveDecoder<N> {
+        WaveDecoder::new(self.b)
+    }
+
     /// Returns wave impedance b.
     pub fn b(&self) -> N {
         self.b
@@ -81,6 +876,7 @@ where
 
     /// Sets the wave impedance b.
     pub fn set_b(&mut self, b: N) {
+        assert!(b > N::zero(), "the wave impedance has to be positive");
         self.b = b;
     }
 }
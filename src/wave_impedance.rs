@@ -0,0 +1,135 @@
+//! Adaptive Wave Impedance
+//!
+//! A large wave impedance `b` renders stiff contacts well but adds drag in free
+//! space, a small one does the opposite. `AdaptiveWaveImpedance` adapts `b`
+//! online from the contact state or from an estimate of the environment
+//! impedance.
+//!
+//! The wave transformation relates the power at a port to the waves for every
+//! positive `b`, so the transformation stays lossless while `b` varies as long
+//! as encoding and decoding at a port use the same `b` in a step. The change
+//! is still blended over time, since jumps of `b` make the force jump.
+use nalgebra::RealField;
+
+/// Adapts the wave impedance between a free space and a contact value.
+///
+/// ```rust
+/// use nalgebra::U1;
+/// use haptic_toolbox::{AdaptiveWaveImpedance, WAVE};
+///
+/// let mut adaptive = AdaptiveWaveImpedance::new(1.0, 100.0, 0.01);
+/// let mut wave = WAVE::<f64, U1>::new(adaptive.b());
+///
+/// for _ in 0..100 {
+///     wave.set_b(adaptive.update_contact(true, 0.001));
+/// }
+/// assert!(wave.b() > 99.0);
+///
+/// // The estimated impedance is kept within the free space and contact values.
+/// for _ in 0..100 {
+///     wave.set_b(adaptive.update_impedance(1e6, 0.001));
+/// }
+/// assert!((wave.b() - 100.0).abs() < 1e-3);
+/// ```
+#[derive(Clone, Debug)]
+pub struct AdaptiveWaveImpedance<N>
+where
+    N: RealField,
+{
+    free_space_b: N,
+    contact_b: N,
+    blend_time: N,
+    b: N,
+}
+
+impl<N> AdaptiveWaveImpedance<N>
+where
+    N: RealField,
+{
+    /// Creates a new `AdaptiveWaveImpedance` starting at `free_space_b` that
+    /// blends to a new value with the time constant `blend_time` in seconds.
+    pub fn new(free_space_b: N, contact_b: N, blend_time: N) -> Self {
+        assert!(
+            free_space_b > N::zero() && free_space_b <= contact_b,
+            "the wave impedances have to be positive and ordered"
+        );
+        assert!(
+            blend_time >= N::zero(),
+            "the blend time must not be negative"
+        );
+        Self {
+            free_space_b,
+            contact_b,
+            blend_time,
+            b: free_space_b,
+        }
+    }
+
+    /// Blends towards the contact value while in contact and towards the free
+    /// space value otherwise, and returns the wave impedance.
+    pub fn update_contact(&mut self, in_contact: bool, dt: N) -> N {
+        let target = if in_contact {
+            self.contact_b
+        } else {
+            self.free_space_b
+        };
+        self.blend(target, dt)
+    }
+
+    /// Blends towards the estimated `environment_impedance` clamped to the free
+    /// space and contact values, and returns the wave impedance.
+    ///
+    /// Matching the wave impedance to the environment impedance minimizes
+    /// wave reflections.
+    pub fn update_impedance(&mut self, environment_impedance: N, dt: N) -> N {
+        let target = environment_impedance
+            .max(self.free_space_b)
+            .min(self.contact_b);
+        self.blend(target, dt)
+    }
+
+    fn blend(&mut self, target: N, dt: N) -> N {
+        let alpha = if self.blend_time + dt > N::zero() {
+            dt / (self.blend_time + dt)
+        } else {
+            N::one()
+        };
+        self.b += (target - self.b) * alpha;
+        self.b
+    }
+
+    /// Returns the wave impedance.
+    pub fn b(&self) -> N {
+        self.b
+    }
+
+    /// Returns the free space and contact wave impedances.
+    pub fn bounds(&self) -> (N, N) {
+        (self.free_space_b, self.contact_b)
+    }
+
+    /// Sets the free space and contact wave impedances.
+    pub fn set_bounds(&mut self, free_space_b: N, contact_b: N) {
+        assert!(
+            free_space_b > N::zero() && free_space_b <= contact_b,
+            "the wave impedances have to be positive and ordered"
+        );
+        self.free_space_b = free_space_b;
+        self.contact_b = contact_b;
+    }
+
+    /// Returns the blend time.
+    pub fn blend_time(&self) -> N {
+        self.blend_time
+    }
+
+    /// Sets the time constant in seconds a new wave impedance is blended in
+    /// with.
+    pub fn set_blend_time(&mut self, blend_time: N) {
+        assert!(
+            blend_time >= N::zero(),
+            "the blend time must not be negative"
+        );
+        self.blend_time = blend_time;
+    }
+}
@@ -0,0 +1,150 @@
+//! Wave Variable Predictor
+//!
+//! Estimates the incoming wave while packets are delayed or lost. Since a
+//! prediction error could make the channel active, an energy supervisor limits
+//! the output to the energy that was sent but not yet output. The energy of
+//! the sent waves is transmitted in every `WavePacket`, so the energy of lost
+//! packets becomes available once the next packet arrives.
+use nalgebra::{
+    allocator::Allocator,
+    dimension::{Dim, DimName},
+    DefaultAllocator, RealField, VectorN,
+};
+use num_traits::Zero;
+
+use crate::{wave::supervise_energy, WavePacket};
+
+/// How missing waves are predicted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WavePrediction {
+    /// Holds the last received wave.
+    #[default]
+    LastValue,
+    /// Extrapolates the last two received waves linearly.
+    Linear,
+}
+
+/// Predicts missing waves under an energy supervisor.
+///
+/// ```rust
+/// use nalgebra::{Vector1, U1};
+/// use haptic_toolbox::{WavePredictor, WAVE};
+///
+/// let mut wave = WAVE::<f64, U1>::new(2.0);
+/// let mut predictor = WavePredictor::new();
+/// let dt = 0.001;
+///
+/// let mut received = Vec::new();
+/// for i in 0..10 {
+///     let packet = wave.encode_master_packet(&Vector1::new(2.0), &Vector1::new(0.0), dt);
+///     let lost = i == 3 || i == 4 || i == 8;
+///     received.push(predictor.predict(if lost { None } else { Some(&packet) }, dt)[0]);
+///     assert!(predictor.output_energy() <= packet.energy + 1e-12);
+/// }
+///
+/// // The first losses can not be predicted, since no energy is available yet.
+/// assert_eq!(received[3], 0.0);
+/// // The energy of the lost packets arrives with the next packet and is spent
+/// // on the prediction of the next loss.
+/// assert!((received[8] - received[7]).abs() < 1e-12);
+/// ```
+#[derive(Debug)]
+pub struct WavePredictor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    prediction: WavePrediction,
+    last: VectorN<N, D>,
+    slope: VectorN<N, D>,
+    elapsed: N,
+    sent_energy: N,
+    output_energy: N,
+}
+
+impl<N, D> Default for WavePredictor<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    fn default() -> Self {
+        Self {
+            prediction: WavePrediction::default(),
+            last: Zero::zero(),
+            slope: Zero::zero(),
+            elapsed: N::zero(),
+            sent_energy: N::zero(),
+            output_energy: N::zero(),
+        }
+    }
+}
+
+impl<N, D> WavePredictor<N, D>
+where
+    N: RealField,
+    D: Dim + DimName,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Creates a new `WavePredictor` that holds the last received wave.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<N, D> WavePredictor<N, D>
+where
+    N: RealField,
+    D: Dim,
+    DefaultAllocator: Allocator<N, D>,
+{
+    /// Returns the wave given the latest packet, or the predicted wave if the
+    /// packet is `None` because it is delayed or lost.
+    pub fn predict(&mut self, packet: Option<&WavePacket<N, D>>, dt: N) -> VectorN<N, D> {
+        let mut wave = match packet {
+            Some(packet) => {
+                let interval = self.elapsed + dt;
+                if interval > N::zero() {
+                    self.slope = (&packet.wave - &self.last) / interval;
+                }
+                self.last = packet.wave.clone();
+                self.elapsed = N::zero();
+                self.sent_energy = self.sent_energy.max(packet.energy);
+                packet.wave.clone()
+            }
+            None => {
+                self.elapsed += dt;
+                match self.prediction {
+                    WavePrediction::LastValue => self.last.clone(),
+                    WavePrediction::Linear => &self.last + &self.slope * self.elapsed,
+                }
+            }
+        };
+
+        supervise_energy(&mut wave, self.sent_energy, self.output_energy, dt);
+        self.output_energy += wave.norm_squared() * dt;
+        wave
+    }
+
+    /// Returns the energy of all sent waves known from the received packets.
+    pub fn sent_energy(&self) -> N {
+        self.sent_energy
+    }
+
+    /// Returns the energy of all output waves.
+    pub fn output_energy(&self) -> N {
+        self.output_energy
+    }
+
+    /// Returns how missing waves are predicted.
+    pub fn prediction(&self) -> WavePrediction {
+        self.prediction
+    }
+
+    /// Sets how missing waves are predicted, defaults to
+    /// `WavePrediction::LastValue`.
+    pub fn set_prediction(&mut self, prediction: WavePrediction) {
+        self.prediction = prediction;
+    }
+}
@@ -0,0 +1,192 @@
+//! Z-Width Analysis
+//!
+//! The Z-width of a haptic system is the range of impedances it can render
+//! passively. A controller stack is described as a two-port that depends on
+//! the delay. Terminated with an environment impedance `z_e`, the operator
+//! feels the transmitted impedance
+//!
+//! `z_t = (a * z_e + b) / (c * z_e + d)`
+//!
+//! with the chain matrix `[[a, b], [c, d]]` of the stack. The environment is
+//! rendered passively if `re(z_t) >= 0` over the whole frequency grid, and the
+//! stack is transparent if `z_t` equals `z_e`.
+use nalgebra::{convert, Complex, ComplexField, RealField};
+
+use crate::{MechanicalImpedance, TwoPort};
+
+/// Result of analyzing a controller stack for one delay.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ZWidthPoint<N>
+where
+    N: RealField,
+{
+    /// One-way delay in seconds.
+    pub delay: N,
+    /// Whether every environment is rendered passively.
+    pub passive: Vec<bool>,
+    /// Smallest magnitude of the transmitted impedance of the passively
+    /// rendered environments over the frequency grid.
+    pub min_impedance: N,
+    /// Largest magnitude of the transmitted impedance of the passively
+    /// rendered environments over the frequency grid.
+    pub max_impedance: N,
+    /// Largest transparency error `|z_t - z_e| / |z_e|` of the passively
+    /// rendered environments over the frequency grid. It is the absolute error
+    /// `|z_t|` for free space.
+    pub transparency_error: N,
+}
+
+impl<N> ZWidthPoint<N>
+where
+    N: RealField,
+{
+    /// Returns the Z-width as the ratio of the largest and the smallest
+    /// transmitted impedance, or `None` if no environment is rendered
+    /// passively.
+    pub fn z_width(&self) -> Option<N> {
+        if self.passive.iter().any(|&passive| passive) && self.min_impedance > N::zero() {
+            Some(self.max_impedance / self.min_impedance)
+        } else {
+            None
+        }
+    }
+}
+
+/// Analyzes the Z-width and the transparency of controller stacks.
+///
+/// ```rust
+/// use haptic_toolbox::{DelayChannel, MechanicalImpedance, TwoPortChain, WaveChannel, ZWidthAnalyzer};
+///
+/// let frequencies = (1..200).map(|i| i as f64).collect::<Vec<_>>();
+/// let analyzer = ZWidthAnalyzer::new(frequencies, vec![0.0, 0.001, 0.1]);
+/// let environments = [
+///     MechanicalImpedance::new(0.0, 1.0, 0.0),
+///     MechanicalImpedance::new(0.0, 10.0, 1000.0),
+/// ];
+///
+/// // The wave channel renders every passive environment passively, but gets
+/// // less transparent with the delay.
+/// let points = analyzer.analyze(
+///     |delay| TwoPortChain::new().with(WaveChannel::new(10.0, delay)),
+///     &environments,
+/// );
+/// assert!(points.iter().all(|point| point.passive.iter().all(|&passive| passive)));
+/// assert!(points[0].transparency_error < 1e-12);
+/// assert!(points[2].transparency_error > points[1].transparency_error);
+///
+/// // The direct channel does not once there is delay.
+/// let points = analyzer.analyze(|delay| DelayChannel::new(delay), &environments);
+/// assert_eq!(points[0].passive, vec![true, true]);
+/// assert_eq!(points[2].passive, vec![false, false]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ZWidthAnalyzer<N>
+where
+    N: RealField,
+{
+    frequencies: Vec<N>,
+    delays: Vec<N>,
+}
+
+impl<N> ZWidthAnalyzer<N>
+where
+    N: RealField,
+{
+    /// Creates a new `ZWidthAnalyzer` for the frequency grid in rad/s and the
+    /// one-way delays in seconds.
+    pub fn new(frequencies: Vec<N>, delays: Vec<N>) -> Self {
+        assert!(
+            frequencies.iter().all(|&omega| omega > N::zero()),
+            "the frequencies have to be positive"
+        );
+        assert!(
+            delays.iter().all(|&delay| delay >= N::zero()),
+            "the delays must not be negative"
+        );
+        Self {
+            frequencies,
+            delays,
+        }
+    }
+
+    /// Analyzes the controller stack built by `stack` for every delay with
+    /// all `environments`.
+    pub fn analyze<F, T>(
+        &self,
+        stack: F,
+        environments: &[MechanicalImpedance<N>],
+    ) -> Vec<ZWidthPoint<N>>
+    where
+        F: Fn(N) -> T,
+        T: TwoPort<N>,
+    {
+        // Passivity is judged within a small tolerance, so that lossless
+        // stacks count as passive.
+        let tolerance = convert::<f64, N>(1e-9);
+        self.delays
+            .iter()
+            .map(|&delay| {
+                let stack = stack(delay);
+                let mut passive = vec![true; environments.len()];
+                let mut min_impedance = None::<N>;
+                let mut max_impedance = N::zero();
+                let mut transparency_error = N::zero();
+                for (environment, passive) in environments.iter().zip(passive.iter_mut()) {
+                    let transmitted = self
+                        .frequencies
+                        .iter()
+                        .map(|&omega| {
+                            let z_e = environment.impedance(omega);
+                            (z_e, Self::transmitted_impedance(&stack, z_e, omega))
+                        })
+                        .collect::<Vec<_>>();
+                    *passive = transmitted
+                        .iter()
+                        .all(|(_, z_t)| z_t.re >= -tolerance * (N::one() + z_t.modulus()));
+                    if !*passive {
+                        continue;
+                    }
+                    for (z_e, z_t) in transmitted {
+                        let magnitude = z_t.modulus();
+                        min_impedance = Some(match min_impedance {
+                            Some(min_impedance) => min_impedance.min(magnitude),
+                            None => magnitude,
+                        });
+                        max_impedance = max_impedance.max(magnitude);
+                        let error = if z_e.modulus() > N::zero() {
+                            (z_t - z_e).modulus() / z_e.modulus()
+                        } else {
+                            magnitude
+                        };
+                        transparency_error = transparency_error.max(error);
+                    }
+                }
+                ZWidthPoint {
+                    delay,
+                    passive,
+                    min_impedance: min_impedance.unwrap_or_else(N::zero),
+                    max_impedance,
+                    transparency_error,
+                }
+            })
+            .collect()
+    }
+
+    fn transmitted_impedance<T>(stack: &T, z_e: Complex<N>, omega: N) -> Complex<N>
+    where
+        T: TwoPort<N>,
+    {
+        let t = stack.chain_matrix(omega);
+        (t[(0, 0)] * z_e + t[(0, 1)]) / (t[(1, 0)] * z_e + t[(1, 1)])
+    }
+
+    /// Returns the frequency grid.
+    pub fn frequencies(&self) -> &[N] {
+        &self.frequencies
+    }
+
+    /// Returns the delays.
+    pub fn delays(&self) -> &[N] {
+        &self.delays
+    }
+}